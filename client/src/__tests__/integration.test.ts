@@ -226,7 +226,7 @@ describe('Janecek Voting Tests', () => {
         expect(voter1state!.positive_used).toBe(1);
         expect(voter1state!.negative_used).toBe(0);
         expect(voter1state!.voted_parties).toHaveLength(1);
-        expect(new PublicKey(voter1state!.voted_parties[0]).toString()).toBe(partyAPda.toString());
+        expect(new PublicKey(voter1state!.voted_parties[0].party).toString()).toBe(partyAPda.toString());
 
         console.log('Voter 1 voted for Party A with signature:', signature1);
     });
@@ -263,7 +263,7 @@ describe('Janecek Voting Tests', () => {
         expect(voter1state!.positive_used).toBe(2);
         expect(voter1state!.negative_used).toBe(0);
         expect(voter1state!.voted_parties).toHaveLength(2);
-        expect(new PublicKey(voter1state!.voted_parties[1]).toString()).toBe(partyBPda.toString());
+        expect(new PublicKey(voter1state!.voted_parties[1].party).toString()).toBe(partyBPda.toString());
 
         console.log('Voter 1 voted for Party B with signature:', signature1);
     });
@@ -292,7 +292,7 @@ describe('Janecek Voting Tests', () => {
         expect(voter1state!.positive_used).toBe(2);
         expect(voter1state!.negative_used).toBe(1);
         expect(voter1state!.voted_parties).toHaveLength(3);
-        expect(new PublicKey(voter1state!.voted_parties[2]).toString()).toBe(partyCPda.toString());
+        expect(new PublicKey(voter1state!.voted_parties[2].party).toString()).toBe(partyCPda.toString());
 
         console.log('Voter 1 voted for Party C with signature:', signature);
     });
@@ -0,0 +1,17 @@
+import { PublicKey } from "@solana/web3.js";
+
+// ["creator", creator], poll_count is the nonce of the creator's next poll
+export class CreatorPolls {
+    discriminator!: Uint8Array;
+    version!: number;
+    creator!: Uint8Array;
+    poll_count!: bigint;
+
+    constructor(fields: Partial<CreatorPolls> = {}) {
+        Object.assign(this, fields);
+    }
+
+    getCreatorPubkey(): PublicKey {
+        return new PublicKey(this.creator);
+    }
+}
@@ -1,11 +1,18 @@
 import { PublicKey } from "@solana/web3.js";
 
 export class PartyAccount {
-    discriminator!: string;
+    discriminator!: Uint8Array;
+    version!: number;
+    initialized!: number;
     poll_id!: Uint8Array;
     title!: string;
-    positive_votes!: bigint;
-    negative_votes!: bigint;
+    description!: string;
+    metadata_uri!: string;
+    // Hash of the off-chain JSON behind metadata_uri
+    metadata_hash!: Uint8Array;
+    positive_votes!: number;
+    negative_votes!: number;
+    rent_payer!: Uint8Array;
 
     constructor(fields: Partial<PartyAccount> = {}) {
         Object.assign(this, fields);
@@ -2,7 +2,8 @@ import { PublicKey } from "@solana/web3.js";
 import { VotingPhase } from "../types/VotingPhase"
 
 export class PollState {
-    discriminator!: string;
+    discriminator!: Uint8Array;
+    version!: number;
     title!: string;
     description!: string;
     phase!: number;
@@ -11,6 +12,30 @@ export class PollState {
     expected_new_owner!: Uint8Array;
     created_at!: bigint;
     voting_start_at!: bigint;
+    registration_duration!: bigint;
+    voting_duration!: bigint;
+    vote_rules!: {
+        positive_votes: number;
+        negative_votes: number;
+        positives_per_negative: number;
+        allow_negative: boolean;
+    };
+    closed!: boolean;
+    eligible_voters!: bigint;
+    token_gate!: { mint: Uint8Array; min_balance: bigint } | null;
+    voting_mode!: number;
+    rent_payer!: Uint8Array;
+    vault_rent_payer!: Uint8Array;
+    archive_period!: bigint;
+    ended_at!: bigint;
+    seeds!: { title_seed: Uint8Array; description_seed: Uint8Array } | { creator: Uint8Array; poll_nonce: bigint };
+    paused!: boolean;
+    paused_at!: bigint;
+    paused_duration!: bigint;
+    program_paused_duration!: bigint;
+    owner_transfer_expires_at!: bigint;
+    committee!: { members: Uint8Array[]; threshold: number } | null;
+    proposal_counter!: bigint;
 
     constructor(fields: Partial<PollState> = {}) {
         Object.assign(this, fields);
@@ -28,22 +53,3 @@ export class PollState {
         return this.phase as VotingPhase;
     }
 }
-
-export const PollStateSchema = new Map([
-    [
-        PollState, {
-            kind: "struct",
-            fields: [
-                ["discriminator", "string"],
-                ["title", "string"],
-                ["description", "string"],
-                ["phase", "u8"],
-                ["parties", ["vector", [32]]],
-                ["owner", [32]],
-                ["expected_new_owner", [32]],
-                ["created_at", "i64"],
-                ["voting_start_at", "i64"],
-            ],
-        },
-    ],
-])
\ No newline at end of file
@@ -0,0 +1,32 @@
+import { PublicKey } from "@solana/web3.js";
+
+// ["config"], created once by the program upgrade authority
+export class ProgramConfig {
+    discriminator!: Uint8Array;
+    version!: number;
+    admin!: Uint8Array;
+    defaults!: {
+        registration_duration: bigint;
+        voting_duration: bigint;
+        vote_rules: {
+            positive_votes: number;
+            negative_votes: number;
+            positives_per_negative: number;
+            allow_negative: boolean;
+        };
+    };
+    creation_fee!: bigint;
+    poll_count!: bigint;
+    paused!: boolean;
+    paused_at!: bigint;
+    paused_duration!: bigint;
+    bump!: number;
+
+    constructor(fields: Partial<ProgramConfig> = {}) {
+        Object.assign(this, fields);
+    }
+
+    getAdminPubkey(): PublicKey {
+        return new PublicKey(this.admin);
+    }
+}
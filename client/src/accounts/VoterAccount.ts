@@ -1,12 +1,25 @@
 import { PublicKey } from "@solana/web3.js";
 
+export class VotedParty {
+    party!: Uint8Array;
+    vote_type!: number;
+    voted_at!: bigint;
+    slot!: bigint;
+}
+
 export class VoterAccount {
-    discriminator!: string;
+    discriminator!: Uint8Array;
+    version!: number;
+    initialized!: number;
+    bump!: number;
     poll_key!: Uint8Array;
     voter_key!: Uint8Array;
     positive_used!: number;
     negative_used!: number;
-    voted_parties!: Uint8Array[];
+    vote_weight!: bigint;
+    locked_tokens!: bigint;
+    voted_count!: number;
+    voted_parties!: VotedParty[];
 
     constructor(fields: Partial<VoterAccount> = {}) {
         Object.assign(this, fields);
@@ -21,6 +34,6 @@ export class VoterAccount {
     }
 
     getVotedPartiesPubkeys(): PublicKey[] {
-        return this.voted_parties.map(party => new PublicKey(party));
+        return this.voted_parties.map(voted => new PublicKey(voted.party));
     }
 }
@@ -2,7 +2,14 @@ import { PublicKey, TransactionInstruction, Transaction, Connection, Keypair, Sy
 import { serializeAccount } from "../utils/serialize";
 import { PdaDerivation } from "../utils/derivePda";
 import { InstructionDiscriminator } from "../types/Discriminator";
-import { CreatePartyPayload, CreatePollPayload, CreateTransferOwnerPayload, PayloadSchemas, VotePayload } from "../types/payloads";
+import { CreatePartyPayload, CreatePollPayload, CreateTransferOwnerPayload, InitializeConfigPayload, PayloadSchemas, PollDefaults, VotePayload } from "../types/payloads";
+import { createAccountDeserializer } from "./accountService";
+import { VotingMode } from "../types/VotingMode";
+
+// CreatePoll settings besides title and description, left out ones use the program config defaults
+export type PollOptions = Omit<CreatePollPayload, "title" | "description">;
+
+export type PartyMetadata = Pick<CreatePartyPayload, "description" | "metadata_uri" | "metadata_hash">;
 
 
 export class TransactionService {
@@ -14,14 +21,53 @@ export class TransactionService {
         this.connection = connection;
     }
 
+    // Must be signed by the program upgrade authority, before it is removed
+    async initializeConfig(
+        payer: Keypair,
+        defaults: PollDefaults,
+        creationFee: number,
+    ) {
+        const [configPda] = PdaDerivation.deriveConfigPda(this.programId);
+        const [programDataPda] = PdaDerivation.deriveProgramDataPda(this.programId);
+
+        const payload = new InitializeConfigPayload({defaults, creation_fee: creationFee});
+        const serialized = serializeAccount(PayloadSchemas.InitializeConfigSchema, InitializeConfigPayload, payload);
+
+        const variant = Buffer.from(Uint8Array.of(InstructionDiscriminator.InitializeConfig));
+        const data = Buffer.concat([variant, serialized]);
+
+        const instruction = new TransactionInstruction({
+            keys: [
+                { pubkey: payer.publicKey, isSigner: true, isWritable: true },
+                { pubkey: configPda, isSigner: false, isWritable: true },
+                { pubkey: SystemProgram.programId, isSigner: false, isWritable: false },
+                { pubkey: programDataPda, isSigner: false, isWritable: false },
+            ],
+            programId: this.programId,
+            data
+        });
+
+        const tx = new Transaction().add(instruction);
+        const signature = await this.connection.sendTransaction(tx, [payer]);
+        return { configPda, signature }
+    }
+
     async createParty(
         payer: Keypair,
         pollPda: PublicKey,
         title: string,
+        metadata: Partial<PartyMetadata> = {},
     ) { 
         const [partyPda] = PdaDerivation.derivePartyPda(this.programId, pollPda, title);
+        const [configPda] = PdaDerivation.deriveConfigPda(this.programId);
     
-        const payload = new CreatePartyPayload({title});
+        const payload = new CreatePartyPayload({
+            title,
+            description: "",
+            metadata_uri: "",
+            metadata_hash: new Uint8Array(32),
+            ...metadata,
+        });
         const serialized = serializeAccount(PayloadSchemas.CreatePartySchema, CreatePartyPayload, payload);
     
         const variant = Buffer.from(Uint8Array.of(InstructionDiscriminator.CreateParty));
@@ -31,6 +77,7 @@ export class TransactionService {
             keys: [
                 { pubkey: payer.publicKey, isSigner: true, isWritable: true },
                 { pubkey: pollPda, isSigner: false, isWritable: true },
+                { pubkey: configPda, isSigner: false, isWritable: false },
                 { pubkey: partyPda, isSigner: false, isWritable: true },
                 { pubkey: SystemProgram.programId, isSigner: false, isWritable: false },
             ],
@@ -43,14 +90,36 @@ export class TransactionService {
         return { partyPda, signature }
     }
 
+    // Poll address comes from the creator's poll counter, the registry entry from the config's
     async createPoll(
         payer: Keypair,
         title: string,
         description: string,
+        options: Partial<PollOptions> = {},
     ) {
-        const [pollPda] = PdaDerivation.derivePollPda(this.programId, title, description);
+        const deserializer = createAccountDeserializer(this.connection);
+        const [creatorPda] = PdaDerivation.deriveCreatorPda(this.programId, payer.publicKey);
+        const [configPda] = PdaDerivation.deriveConfigPda(this.programId);
+
+        const creatorInfo = await this.connection.getAccountInfo(creatorPda);
+        const pollNonce = creatorInfo ? (await deserializer.getCreatorPolls(creatorPda)).poll_count : BigInt(0);
+        const config = await deserializer.getProgramConfig(configPda);
+
+        const [pollPda] = PdaDerivation.derivePollPda(this.programId, payer.publicKey, pollNonce);
+        const [registryEntryPda] = PdaDerivation.deriveRegistryEntryPda(this.programId, config.poll_count);
     
-        const payload = new CreatePollPayload({title, description});
+        const payload = new CreatePollPayload({
+            title,
+            description,
+            registration_duration: null,
+            voting_duration: null,
+            vote_rules: null,
+            token_gate: null,
+            voting_mode: VotingMode.OnePersonOneVote,
+            archive_period: 0,
+            closed: 0,
+            ...options,
+        });
         const serialized = serializeAccount(PayloadSchemas.CreatePollSchema, CreatePollPayload, payload);
     
         const variant = Buffer.from(Uint8Array.of(InstructionDiscriminator.CreatePoll));
@@ -60,6 +129,11 @@ export class TransactionService {
             keys: [
                 { pubkey: payer.publicKey, isSigner: true, isWritable: true },
                 { pubkey: pollPda, isSigner: false, isWritable: true },
+                { pubkey: creatorPda, isSigner: false, isWritable: true },
+                { pubkey: configPda, isSigner: false, isWritable: true },
+                { pubkey: registryEntryPda, isSigner: false, isWritable: true },
+                // Receives the creation fee
+                { pubkey: config.getAdminPubkey(), isSigner: false, isWritable: true },
                 { pubkey: SystemProgram.programId, isSigner: false, isWritable: false },
             ],
             programId: this.programId,
@@ -81,6 +155,7 @@ export class TransactionService {
             keys: [
                 { pubkey: payer.publicKey, isSigner: true, isWritable: true },
                 { pubkey: pollPda, isSigner: false, isWritable: true },
+                { pubkey: PdaDerivation.deriveConfigPda(this.programId)[0], isSigner: false, isWritable: false },
             ],
             programId: this.programId,
             data
@@ -113,6 +188,7 @@ export class TransactionService {
             keys: [
                 { pubkey: payer.publicKey, isSigner: true, isWritable: true },
                 { pubkey: pollPda, isSigner: false, isWritable: true },
+                { pubkey: PdaDerivation.deriveConfigPda(this.programId)[0], isSigner: false, isWritable: false },
             ],
             programId: this.programId,
             data
@@ -133,6 +209,7 @@ export class TransactionService {
             keys: [
                 { pubkey: payer.publicKey, isSigner: true, isWritable: true },
                 { pubkey: pollPda, isSigner: false, isWritable: true },
+                { pubkey: PdaDerivation.deriveConfigPda(this.programId)[0], isSigner: false, isWritable: false },
             ],
             programId: this.programId,
             data
@@ -156,6 +233,7 @@ export class TransactionService {
         voteType: number,
     ) {
         const [voterPda] = PdaDerivation.deriveVoterPda(this.programId, pollPda, payer.publicKey);
+        const [configPda] = PdaDerivation.deriveConfigPda(this.programId);
         const payload = new VotePayload({vote_type: voteType});
         const serialized = serializeAccount(PayloadSchemas.VoteSchema, VotePayload, payload);
 
@@ -165,7 +243,8 @@ export class TransactionService {
         const instruction = new TransactionInstruction({
             keys: [
                 { pubkey: payer.publicKey, isSigner: true, isWritable: true },
-                { pubkey: pollPda, isSigner: false, isWritable: false },
+                { pubkey: pollPda, isSigner: false, isWritable: true },
+                { pubkey: configPda, isSigner: false, isWritable: false },
                 { pubkey: partyPda, isSigner: false, isWritable: true },
                 { pubkey: voterPda, isSigner: false, isWritable: true },
                 { pubkey: SystemProgram.programId, isSigner: false, isWritable: false },
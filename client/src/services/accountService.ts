@@ -2,7 +2,11 @@ import { Connection, PublicKey } from "@solana/web3.js";
 import { PollState } from "../accounts/PollAccount";
 import { PartyAccount } from "../accounts/PartyAccount";
 import { VoterAccount } from "../accounts/VoterAccount";
+import { ProgramConfig } from "../accounts/ProgramConfig";
+import { CreatorPolls } from "../accounts/CreatorPolls";
 import { 
+    deserializeProgramConfig,
+    deserializeCreatorPolls,
     deserializePollState, 
     deserializePartyAccount, 
     deserializeVoterAccount,
@@ -35,6 +39,14 @@ export class AccountDeserializer {
     }
 
     // Specific methods for each account type
+    async getProgramConfig(configPda: PublicKey): Promise<ProgramConfig> {
+        return this.deserializeAccount(configPda, deserializeProgramConfig, ProgramConfig);
+    }
+
+    async getCreatorPolls(creatorPda: PublicKey): Promise<CreatorPolls> {
+        return this.deserializeAccount(creatorPda, deserializeCreatorPolls, CreatorPolls);
+    }
+
     async getPollState(pollPda: PublicKey): Promise<PollState> {
         return this.deserializeAccount(pollPda, deserializePollState, PollState);
     }
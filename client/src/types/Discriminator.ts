@@ -6,5 +6,18 @@ export enum InstructionDiscriminator {
     StartVoting = 4,
     Vote = 5,
     EndVoting = 6,
+    InitializeConfig = 17,
   }
-  
\ No newline at end of file
+  
+// First 8 bytes of every program account, a version byte follows
+export const AccountDiscriminator = {
+    Poll: Buffer.from("poll\0\0\0\0"),
+    Party: Buffer.from("party\0\0\0"),
+    Voter: Buffer.from("voter\0\0\0"),
+    Config: Buffer.from("config\0\0"),
+    Creator: Buffer.from("creator\0"),
+    RegistryEntry: Buffer.from("entry\0\0\0"),
+    Proposal: Buffer.from("proposal"),
+    EligibleVoter: Buffer.from("eligible"),
+    Results: Buffer.from("results\0"),
+};
@@ -0,0 +1,4 @@
+export enum VotingMode {
+    OnePersonOneVote = 0,
+    TokenWeighted = 1,
+}
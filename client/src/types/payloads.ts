@@ -1,6 +1,23 @@
+export class TokenGate {
+    mint!: Uint8Array;
+    min_balance!: number;
+
+    constructor(fields: Partial<TokenGate> = {}) {
+        Object.assign(this, fields)
+    }
+}
+
+// Left out schedule and rules are taken from the program config defaults
 export class CreatePollPayload {
     title!: string;
     description!: string;
+    registration_duration!: number | null;
+    voting_duration!: number | null;
+    vote_rules!: VoteRules | null;
+    token_gate!: TokenGate | null;
+    voting_mode!: number;
+    archive_period!: number;
+    closed!: number; // bool
 
     constructor(fields: Partial<CreatePollPayload> = {}) {
         Object.assign(this, fields);
@@ -9,6 +26,10 @@ export class CreatePollPayload {
 
 export class CreatePartyPayload {
     title!: string;
+    description!: string;
+    metadata_uri!: string;
+    // Hash of the off-chain JSON behind metadata_uri
+    metadata_hash!: Uint8Array;
 
     constructor(fields: Partial<CreatePartyPayload> = {}) {
         Object.assign(this, fields)
@@ -33,8 +54,78 @@ export class VotePayload {
 
 
 
+export class VoteRules {
+    positive_votes!: number;
+    negative_votes!: number;
+    positives_per_negative!: number;
+    allow_negative!: number; // bool
+
+    constructor(fields: Partial<VoteRules> = {}) {
+        Object.assign(this, fields)
+    }
+}
+
+export class PollDefaults {
+    registration_duration!: number;
+    voting_duration!: number;
+    vote_rules!: VoteRules;
+
+    constructor(fields: Partial<PollDefaults> = {}) {
+        Object.assign(this, fields)
+    }
+}
+
+export class InitializeConfigPayload {
+    defaults!: PollDefaults;
+    creation_fee!: number;
+
+    constructor(fields: Partial<InitializeConfigPayload> = {}) {
+        Object.assign(this, fields)
+    }
+}
+
+// Durations are never negative, so u64 gives the same bytes as the program's i64.
+// Bools are written as u8
+const VoteRulesSchema: [any, any] = [
+    VoteRules,
+    {
+        kind: "struct",
+        fields: [
+            ["positive_votes", "u8"],
+            ["negative_votes", "u8"],
+            ["positives_per_negative", "u8"],
+            ["allow_negative", "u8"],
+        ],
+    },
+];
+
+const TokenGateSchema: [any, any] = [
+    TokenGate,
+    {
+        kind: "struct",
+        fields: [
+            ["mint", [32]],
+            ["min_balance", "u64"],
+        ],
+    },
+];
+
+const PollDefaultsSchema: [any, any] = [
+    PollDefaults,
+    {
+        kind: "struct",
+        fields: [
+            ["registration_duration", "u64"],
+            ["voting_duration", "u64"],
+            ["vote_rules", VoteRules],
+        ],
+    },
+];
+
 export const PayloadSchemas = {
-    CreatePollSchema: new Map([
+    CreatePollSchema: new Map<any, any>([
+        VoteRulesSchema,
+        TokenGateSchema,
         [
             CreatePollPayload,
             {
@@ -42,6 +133,13 @@ export const PayloadSchemas = {
                 fields: [
                     ["title", "string"],
                     ["description", "string"],
+                    ["registration_duration", { kind: "option", type: "u64" }],
+                    ["voting_duration", { kind: "option", type: "u64" }],
+                    ["vote_rules", { kind: "option", type: VoteRules }],
+                    ["token_gate", { kind: "option", type: TokenGate }],
+                    ["voting_mode", "u8"],
+                    ["archive_period", "u64"],
+                    ["closed", "u8"],
                 ],
             },
         ],
@@ -54,6 +152,9 @@ export const PayloadSchemas = {
                 kind: "struct",
                 fields: [
                     ["title", "string"],
+                    ["description", "string"],
+                    ["metadata_uri", "string"],
+                    ["metadata_hash", [32]],
                 ],
             },
         ],
@@ -71,6 +172,21 @@ export const PayloadSchemas = {
         ],
     ]),
 
+    InitializeConfigSchema: new Map<any, any>([
+        VoteRulesSchema,
+        PollDefaultsSchema,
+        [
+            InitializeConfigPayload,
+            {
+                kind: "struct",
+                fields: [
+                    ["defaults", PollDefaults],
+                    ["creation_fee", "u64"],
+                ],
+            },
+        ],
+    ]),
+
     VoteSchema: new Map([
         [
             VotePayload,
@@ -1,6 +1,7 @@
 import { PublicKey } from "@solana/web3.js";
 import { sha256 } from "@noble/hashes/sha256";
 
+const BPF_LOADER_UPGRADEABLE_PROGRAM_ID = new PublicKey("BPFLoaderUpgradeab1e11111111111111111111111");
 
 
 export class PdaDerivation {
@@ -10,7 +11,62 @@ export class PdaDerivation {
         return Buffer.from(hashBytes); // бинарные байты
     }
 
+    static deriveConfigPda(programId: PublicKey): [PublicKey, number] {
+        return PublicKey.findProgramAddressSync(
+            [Buffer.from('config')],
+            programId
+        );
+    }
+
+    // InitializeConfig reads the upgrade authority from the loader's ProgramData account
+    static deriveProgramDataPda(programId: PublicKey): [PublicKey, number] {
+        return PublicKey.findProgramAddressSync(
+            [programId.toBuffer()],
+            BPF_LOADER_UPGRADEABLE_PROGRAM_ID
+        );
+    }
+
+    static u64Bytes(value: bigint): Buffer {
+        const bytes = Buffer.alloc(8);
+        bytes.writeBigUInt64LE(value);
+        return bytes;
+    }
+
+    // Counts the polls of a creator, its poll_count is the nonce of the next poll
+    static deriveCreatorPda(
+        programId: PublicKey,
+        creator: PublicKey,
+    ): [PublicKey, number] {
+        return PublicKey.findProgramAddressSync(
+            [Buffer.from('creator'), creator.toBuffer()],
+            programId
+        );
+    }
+
     static derivePollPda(
+        programId: PublicKey,
+        creator: PublicKey,
+        pollNonce: bigint,
+    ): [PublicKey, number] {
+        return PublicKey.findProgramAddressSync(
+            [Buffer.from('poll'), creator.toBuffer(), this.u64Bytes(pollNonce)],
+            programId
+        );
+    }
+
+    // Entries are numbered by the config's poll_count
+    static deriveRegistryEntryPda(
+        programId: PublicKey,
+        index: bigint,
+    ): [PublicKey, number] {
+        return PublicKey.findProgramAddressSync(
+            [Buffer.from('registry'), this.u64Bytes(index)],
+            programId
+        );
+    }
+
+    // Polls created before creator nonces, their texts may have been edited since
+    static deriveLegacyPollPda(
         programId: PublicKey,
         title: string,
         description: string
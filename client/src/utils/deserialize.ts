@@ -10,6 +10,8 @@
 //     return deserialize(schema, classType, rawData) as T;
 // }
 
+import { AccountDiscriminator } from "../types/Discriminator";
+
 // Generic Borsh deserializer that handles Rust enums properly
 export class BorshDeserializer {
     private offset: number = 0;
@@ -35,8 +37,33 @@ export class BorshDeserializer {
         return value;
     }
 
-    // Read a u32
-    readU32(): number {
+    // Read a bool
+    readBool(): boolean {
+        return this.readU8() !== 0;
+    }
+
+    // Read a fixed number of bytes
+    readBytes(length: number): Uint8Array {
+        const bytes = new Uint8Array(this.data.slice(this.offset, this.offset + length));
+        this.offset += length;
+        return bytes;
+    }
+
+    // Read the 8-byte discriminator, accounts of the first release need MigrateAccount first
+    readDiscriminator(expected: Buffer): Uint8Array {
+        const discriminator = this.readBytes(8);
+        if (!expected.equals(Buffer.from(discriminator))) {
+            throw new Error(`Unexpected account discriminator, expected ${expected.toString().replace(/\0/g, "")}`);
+        }
+        return discriminator;
+    }
+
+    // Read an Option using a custom reader function
+    readOption<T>(reader: () => T): T | null {
+        return this.readBool() ? reader() : null;
+    }
+
+    // Read a u32(): number {
         const value = this.data.readUInt32LE(this.offset);
         this.offset += 4;
         return value;
@@ -100,11 +127,53 @@ export function createDeserializer(data: Buffer): BorshDeserializer {
 }
 
 // Specific deserializers for each account type
+function readVoteRules(deserializer: BorshDeserializer): any {
+    return {
+        positive_votes: deserializer.readU8(),
+        negative_votes: deserializer.readU8(),
+        positives_per_negative: deserializer.readU8(),
+        allow_negative: deserializer.readBool()
+    };
+}
+
+export function deserializeProgramConfig(data: Buffer): any {
+    const deserializer = createDeserializer(data);
+
+    return {
+        discriminator: deserializer.readDiscriminator(AccountDiscriminator.Config),
+        version: deserializer.readU8(),
+        admin: deserializer.readPubkey(),
+        defaults: {
+            registration_duration: deserializer.readI64(),
+            voting_duration: deserializer.readI64(),
+            vote_rules: readVoteRules(deserializer)
+        },
+        creation_fee: deserializer.readU64(),
+        poll_count: deserializer.readU64(),
+        paused: deserializer.readBool(),
+        paused_at: deserializer.readI64(),
+        paused_duration: deserializer.readI64(),
+        bump: deserializer.readU8()
+    };
+}
+
+export function deserializeCreatorPolls(data: Buffer): any {
+    const deserializer = createDeserializer(data);
+
+    return {
+        discriminator: deserializer.readDiscriminator(AccountDiscriminator.Creator),
+        version: deserializer.readU8(),
+        creator: deserializer.readPubkey(),
+        poll_count: deserializer.readU64()
+    };
+}
+
 export function deserializePollState(data: Buffer): any {
     const deserializer = createDeserializer(data);
     
     return {
-        discriminator: deserializer.readString(),
+        discriminator: deserializer.readDiscriminator(AccountDiscriminator.Poll),
+        version: deserializer.readU8(),
         title: deserializer.readString(),
         description: deserializer.readString(),
         phase: deserializer.readU8(), // VotingPhase enum as u8
@@ -112,31 +181,95 @@ export function deserializePollState(data: Buffer): any {
         owner: deserializer.readPubkey(),
         expected_new_owner: deserializer.readPubkey(),
         created_at: deserializer.readI64(),
-        voting_start_at: deserializer.readI64()
+        voting_start_at: deserializer.readI64(),
+        registration_duration: deserializer.readI64(),
+        voting_duration: deserializer.readI64(),
+        vote_rules: readVoteRules(deserializer),
+        closed: deserializer.readBool(),
+        eligible_voters: deserializer.readU64(),
+        token_gate: deserializer.readOption(() => ({
+            mint: deserializer.readPubkey(),
+            min_balance: deserializer.readU64()
+        })),
+        voting_mode: deserializer.readU8(), // VotingMode enum as u8
+        rent_payer: deserializer.readPubkey(),
+        vault_rent_payer: deserializer.readPubkey(),
+        archive_period: deserializer.readI64(),
+        ended_at: deserializer.readI64(),
+        // PollSeeds enum, Text or Creator
+        seeds: deserializer.readU8() === 0
+            ? { title_seed: deserializer.readPubkey(), description_seed: deserializer.readPubkey() }
+            : { creator: deserializer.readPubkey(), poll_nonce: deserializer.readU64() },
+        paused: deserializer.readBool(),
+        paused_at: deserializer.readI64(),
+        paused_duration: deserializer.readI64(),
+        program_paused_duration: deserializer.readI64(),
+        owner_transfer_expires_at: deserializer.readI64(),
+        committee: deserializer.readOption(() => ({
+            members: deserializer.readPubkeyVector(),
+            threshold: deserializer.readU8()
+        })),
+        proposal_counter: deserializer.readU64()
     };
 }
 
+// Fixed layouts used in place by the program, no Borsh length prefixes
+export const PARTY_ACCOUNT_SIZE = 646;
+export const VOTER_ACCOUNT_SIZE = 94;
+
 export function deserializePartyAccount(data: Buffer): any {
     const deserializer = createDeserializer(data);
-    
+
+    const discriminator = deserializer.readDiscriminator(AccountDiscriminator.Party);
+    const version = deserializer.readU8();
+    const initialized = deserializer.readU8();
+    const poll_id = deserializer.readPubkey();
+    const title = deserializer.readFixedString(64, deserializer.readU8());
+    const description = deserializer.readFixedString(256, deserializer.readU16());
+    const metadata_uri = deserializer.readFixedString(200, deserializer.readU8());
+
     return {
-        discriminator: deserializer.readString(),
-        poll_id: deserializer.readPubkey(),
-        title: deserializer.readString(),
+        discriminator,
+        version,
+        initialized,
+        poll_id,
+        title,
+        description,
+        metadata_uri,
+        metadata_hash: deserializer.readBytes(32),
         positive_votes: Number(deserializer.readU64()),
-        negative_votes: Number(deserializer.readU64())
+        negative_votes: Number(deserializer.readU64()),
+        rent_payer: deserializer.readPubkey()
     };
 }
 
+// Header followed by one slot per vote the poll rules allow, only `voted_count` are in use
 export function deserializeVoterAccount(data: Buffer): any {
     const deserializer = createDeserializer(data);
-    
-    return {
-        discriminator: deserializer.readString(),
+
+    const voter = {
+        discriminator: deserializer.readDiscriminator(AccountDiscriminator.Voter),
+        version: deserializer.readU8(),
+        initialized: deserializer.readU8(),
+        bump: deserializer.readU8(),
         poll_key: deserializer.readPubkey(),
         voter_key: deserializer.readPubkey(),
         positive_used: deserializer.readU8(),
         negative_used: deserializer.readU8(),
-        voted_parties: deserializer.readPubkeyVector()
+        vote_weight: deserializer.readU64(),
+        locked_tokens: deserializer.readU64(),
+        voted_count: deserializer.readU8()
     };
-}
\ No newline at end of file
+
+    const voted_parties = [];
+    for (let i = 0; i < voter.voted_count; i++) {
+        voted_parties.push({
+            party: deserializer.readPubkey(),
+            vote_type: deserializer.readU8(), // VoteType enum as u8
+            voted_at: deserializer.readI64(),
+            slot: deserializer.readU64()
+        });
+    }
+
+    return { ...voter, voted_parties };
+}
@@ -2,6 +2,7 @@ import { Connection, PublicKey } from "@solana/web3.js";
 import { createAccountDeserializer } from "../services/accountService";
 import { CONFIG } from "../config";
 import { PartyAccount } from "../accounts/PartyAccount";
+import { PARTY_ACCOUNT_SIZE } from "./deserialize";
 
 export async function getAllPartiesForPoll(
     connection: Connection,
@@ -10,9 +11,11 @@ export async function getAllPartiesForPoll(
 
     const accounts = await connection.getProgramAccounts(CONFIG.programId, {
         filters: [
+            { dataSize: PARTY_ACCOUNT_SIZE },
             {
                 memcmp: {
-                    offset: 4 + "party".length,
+                    // poll_id follows the discriminator, version and initialized flag
+                    offset: 8 + 1 + 1,
                     bytes: pollPda.toBase58(),
                 },
             },
@@ -7,7 +7,7 @@ pub enum JanecekError {
     InvalidInstruction,
     #[error("Input data exceeds max length")]
     InvalidDataLength,
-    #[error("Registration must be minimum 24h")]
+    #[error("Registration period is not finished yet")]
     RegistrationPhaseTooShort,
     #[error("Voting period is finished")]
     VotingPeriodFinished,
@@ -17,8 +17,68 @@ pub enum JanecekError {
     NoNegativeVoice,
     #[error("Voter already voted for this party")]
     AlreadyVoted,
-    #[error("Voter must use all positive voices")]
+    #[error("Voter must use more positive voices before a negative one")]
     MustUseAllPositiveVoices,
+    #[error("Voting period is not finished yet")]
+    VotingPeriodNotFinished,
+    #[error("Party accounts do not match the poll")]
+    PartyListMismatch,
+    #[error("Duration is outside allowed bounds")]
+    InvalidDuration,
+    #[error("Vote rules are invalid")]
+    InvalidVoteRules,
+    #[error("Voter has not voted for this party")]
+    VoteNotFound,
+    #[error("Negative votes must be retracted first")]
+    MustRetractNegativeFirst,
+    #[error("Voter is not eligible to vote in this poll")]
+    VoterNotEligible,
+    #[error("Token gate is invalid")]
+    InvalidTokenGate,
+    #[error("Voter token balance is below the poll minimum")]
+    InsufficientTokenBalance,
+    #[error("Vote counter overflow")]
+    VoteOverflow,
+    #[error("Voter has no locked tokens")]
+    NoLockedTokens,
+    #[error("Archive period is not finished yet")]
+    ArchivePeriodNotFinished,
+    #[error("All parties must be closed before the poll")]
+    PartiesNotClosed,
+    #[error("Voter tokens are still locked")]
+    TokensStillLocked,
+    #[error("Poll is paused by its owner")]
+    PollPaused,
+    #[error("Program is paused by the admin")]
+    ProgramPaused,
+    #[error("No owner transfer is pending")]
+    NoPendingOwnerTransfer,
+    #[error("Owner transfer has expired")]
+    OwnerTransferExpired,
+    #[error("Committee members or threshold are invalid")]
+    InvalidCommittee,
+    #[error("Signer is not a committee member")]
+    NotCommitteeMember,
+    #[error("Member already approved this proposal")]
+    AlreadyApproved,
+    #[error("Proposal does not have enough approvals")]
+    ProposalNotApproved,
+    #[error("Proposal does not match this action")]
+    ProposalMismatch,
+    #[error("Proposal was already executed")]
+    ProposalAlreadyExecuted,
+    #[error("Account has the wrong type for this instruction")]
+    AccountTypeMismatch,
+    #[error("Account version is not supported by this program")]
+    UnsupportedAccountVersion,
+    #[error("Poll already has the maximum number of parties")]
+    TooManyParties,
+    #[error("Poll is open, voters can not be registered")]
+    PollNotClosed,
+    #[error("Registered voters must be closed before the poll")]
+    EligibleVotersNotClosed,
+    #[error("Program has no upgrade authority to initialize the config")]
+    ProgramImmutable,
 }
 
 impl From<JanecekError> for ProgramError {
@@ -1,15 +1,28 @@
 use borsh::{BorshDeserialize, BorshSerialize};
 use solana_program::{program_error::ProgramError, pubkey::Pubkey};
-use crate::state::VoteType;
+use crate::state::{BallotEntry, Committee, PollDefaults, ProposalAction, TokenGate, VoteRules, VoteType, VotingMode};
 
 #[derive(BorshSerialize, BorshDeserialize, Debug)]
 pub enum JanecekInstruction {
+    // Left out schedule and rules are taken from the program config defaults
     CreatePoll {
         title: String,
         description: String,
+        registration_duration: Option<i64>,
+        voting_duration: Option<i64>,
+        vote_rules: Option<VoteRules>,
+        token_gate: Option<TokenGate>,
+        voting_mode: VotingMode,
+        archive_period: i64,
+        // Only voters registered by the owner can vote
+        closed: bool,
     },
     CreateParty {
         title: String,
+        description: String,
+        metadata_uri: String,
+        // Hash of the off-chain JSON behind `metadata_uri`
+        metadata_hash: [u8; 32],
     },
     InitiateOwnerTransfer {
         new_owner: Pubkey,
@@ -21,19 +34,153 @@ pub enum JanecekInstruction {
     Vote {
         vote_type: VoteType,   
     },
+    // Owner can end voting at any time, anyone can finalize the poll after the deadline
     EndVoting {
-    }
+    },
+    // Rewrites an account written by an older program version in the current layout,
+    // first release parties also take their poll so its owner becomes their rent payer.
+    // Works while paused, it changes no votes or balances and only the signer pays
+    MigrateAccount {
+    },
+    RetractVote {
+    },
+    RegisterVoter {
+        voter: Pubkey,
+    },
+    UnlockTokens {
+    },
+    // Rent goes back to the original payer once the archive period is over,
+    // the results and the token vault are closed with the poll
+    ClosePoll {
+    },
+    CloseParty {
+    },
+    CloseVoter {
+    },
+    RemoveParty {
+    },
+    // Title is part of the PDA seeds, renaming is RemoveParty followed by CreateParty
+    UpdatePartyMetadata {
+        description: String,
+        metadata_uri: String,
+        // Hash of the off-chain JSON behind `metadata_uri`
+        metadata_hash: [u8; 32],
+    },
+    // PDA does not depend on the texts, see `PollSeeds::find_address`.
+    // Never grows the poll, one migrated with longer texts shrinks and its rent payer gets the excess
+    UpdatePoll {
+        title: String,
+        description: String,
+    },
+    // Signed by the program upgrade authority, which becomes the admin.
+    // Run it before making the program immutable, without a config no poll can be created
+    InitializeConfig {
+        defaults: PollDefaults,
+        creation_fee: u64,
+    },
+    UpdateConfig {
+        admin: Pubkey,
+        defaults: PollDefaults,
+        creation_fee: u64,
+    },
+    // Owner or committee halts or resumes a poll, resuming can push the voting deadline by the paused time
+    SetPollPaused {
+        paused: bool,
+        extend_deadline: bool,
+    },
+    // Admin halts or resumes every poll at once, polls in voting get the paused time added to their deadline
+    SetProgramPaused {
+        paused: bool,
+    },
+    CancelOwnerTransfer {},
+    // Owner hands the gated actions to a committee and pays for its room in the poll, can not be undone
+    SetCommittee {
+        committee: Committee,
+    },
+    // Proposer's approval is counted right away
+    ProposeAction {
+        action: ProposalAction,
+    },
+    ApproveProposal {},
+    // All votes of a voter at once, the party accounts follow in ballot order
+    CastBallot {
+        ballot: Vec<BallotEntry>,
+    },
+    // Rent goes back to whoever paid for the registration
+    UnregisterVoter {
+        voter: Pubkey,
+    },
+    CloseEligibleVoter {},
+    // Proposer takes the rent back once the proposal ran or the poll is archived
+    CloseProposal {},
 }
 
 #[derive(BorshDeserialize)]
 struct CreatePollPayload {
     title: String,
     description: String,
+    registration_duration: Option<i64>,
+    voting_duration: Option<i64>,
+    vote_rules: Option<VoteRules>,
+    token_gate: Option<TokenGate>,
+    voting_mode: VotingMode,
+    archive_period: i64,
+    closed: bool,
 }
 
 #[derive(BorshDeserialize)]
 struct CreatePartyPayload {
-    title: String
+    title: String,
+    description: String,
+    metadata_uri: String,
+    metadata_hash: [u8; 32],
+}
+
+#[derive(BorshDeserialize)]
+struct UpdatePartyMetadataPayload {
+    description: String,
+    metadata_uri: String,
+    metadata_hash: [u8; 32],
+}
+
+#[derive(BorshDeserialize)]
+struct UpdatePollPayload {
+    title: String,
+    description: String,
+}
+
+#[derive(BorshDeserialize)]
+struct InitializeConfigPayload {
+    defaults: PollDefaults,
+    creation_fee: u64,
+}
+
+#[derive(BorshDeserialize)]
+struct UpdateConfigPayload {
+    admin: Pubkey,
+    defaults: PollDefaults,
+    creation_fee: u64,
+}
+
+#[derive(BorshDeserialize)]
+struct SetPollPausedPayload {
+    paused: bool,
+    extend_deadline: bool,
+}
+
+#[derive(BorshDeserialize)]
+struct SetProgramPausedPayload {
+    paused: bool,
+}
+
+#[derive(BorshDeserialize)]
+struct SetCommitteePayload {
+    committee: Committee,
+}
+
+#[derive(BorshDeserialize)]
+struct ProposeActionPayload {
+    action: ProposalAction,
 }
 
 #[derive(BorshDeserialize)]
@@ -46,6 +193,16 @@ struct VotePayload {
     vote_type: VoteType,
 }
 
+#[derive(BorshDeserialize)]
+struct RegisterVoterPayload {
+    voter: Pubkey,
+}
+
+#[derive(BorshDeserialize)]
+struct CastBallotPayload {
+    ballot: Vec<BallotEntry>,
+}
+
 
 impl JanecekInstruction {
     pub fn unpack(input: &[u8]) -> Result<Self, ProgramError> {
@@ -57,14 +214,24 @@ impl JanecekInstruction {
                     .map_err(|_| ProgramError::InvalidInstructionData)?;
                 Ok(Self::CreatePoll { 
                     title: payload.title, 
-                    description: payload.description 
+                    description: payload.description,
+                    registration_duration: payload.registration_duration,
+                    voting_duration: payload.voting_duration,
+                    vote_rules: payload.vote_rules,
+                    token_gate: payload.token_gate,
+                    voting_mode: payload.voting_mode,
+                    archive_period: payload.archive_period,
+                    closed: payload.closed,
                 })
             }
             1 => { // CreateParty
                 let payload = CreatePartyPayload::try_from_slice(rest)
                     .map_err(|_| ProgramError::InvalidInstructionData)?;
                 Ok(Self::CreateParty {
-                    title: payload.title
+                    title: payload.title,
+                    description: payload.description,
+                    metadata_uri: payload.metadata_uri,
+                    metadata_hash: payload.metadata_hash,
                 })
             }
             2 => { // InitiateOwnerTransfer
@@ -90,7 +257,124 @@ impl JanecekInstruction {
             6 => { // EndVoting
                 Ok(Self::EndVoting {})
             }
-            _ => return Err(ProgramError::InvalidInstructionData),
+            7 => { // MigrateAccount
+                Ok(Self::MigrateAccount {})
+            }
+            8 => { // RetractVote
+                Ok(Self::RetractVote {})
+            }
+            9 => { // RegisterVoter
+                let payload = RegisterVoterPayload::try_from_slice(rest)
+                    .map_err(|_| ProgramError::InvalidInstructionData)?;
+                Ok(Self::RegisterVoter {
+                    voter: payload.voter
+                })
+            }
+            10 => { // UnlockTokens
+                Ok(Self::UnlockTokens {})
+            }
+            11 => { // ClosePoll
+                Ok(Self::ClosePoll {})
+            }
+            12 => { // CloseParty
+                Ok(Self::CloseParty {})
+            }
+            13 => { // CloseVoter
+                Ok(Self::CloseVoter {})
+            }
+            14 => { // RemoveParty
+                Ok(Self::RemoveParty {})
+            }
+            15 => { // UpdatePartyMetadata
+                let payload = UpdatePartyMetadataPayload::try_from_slice(rest)
+                    .map_err(|_| ProgramError::InvalidInstructionData)?;
+                Ok(Self::UpdatePartyMetadata {
+                    description: payload.description,
+                    metadata_uri: payload.metadata_uri,
+                    metadata_hash: payload.metadata_hash,
+                })
+            }
+            16 => { // UpdatePoll
+                let payload = UpdatePollPayload::try_from_slice(rest)
+                    .map_err(|_| ProgramError::InvalidInstructionData)?;
+                Ok(Self::UpdatePoll {
+                    title: payload.title,
+                    description: payload.description,
+                })
+            }
+            17 => { // InitializeConfig
+                let payload = InitializeConfigPayload::try_from_slice(rest)
+                    .map_err(|_| ProgramError::InvalidInstructionData)?;
+                Ok(Self::InitializeConfig {
+                    defaults: payload.defaults,
+                    creation_fee: payload.creation_fee,
+                })
+            }
+            18 => { // UpdateConfig
+                let payload = UpdateConfigPayload::try_from_slice(rest)
+                    .map_err(|_| ProgramError::InvalidInstructionData)?;
+                Ok(Self::UpdateConfig {
+                    admin: payload.admin,
+                    defaults: payload.defaults,
+                    creation_fee: payload.creation_fee,
+                })
+            }
+            19 => { // SetPollPaused
+                let payload = SetPollPausedPayload::try_from_slice(rest)
+                    .map_err(|_| ProgramError::InvalidInstructionData)?;
+                Ok(Self::SetPollPaused {
+                    paused: payload.paused,
+                    extend_deadline: payload.extend_deadline,
+                })
+            }
+            20 => { // SetProgramPaused
+                let payload = SetProgramPausedPayload::try_from_slice(rest)
+                    .map_err(|_| ProgramError::InvalidInstructionData)?;
+                Ok(Self::SetProgramPaused {
+                    paused: payload.paused,
+                })
+            }
+            21 => { // CancelOwnerTransfer
+                Ok(Self::CancelOwnerTransfer {})
+            }
+            22 => { // SetCommittee
+                let payload = SetCommitteePayload::try_from_slice(rest)
+                    .map_err(|_| ProgramError::InvalidInstructionData)?;
+                Ok(Self::SetCommittee {
+                    committee: payload.committee,
+                })
+            }
+            23 => { // ProposeAction
+                let payload = ProposeActionPayload::try_from_slice(rest)
+                    .map_err(|_| ProgramError::InvalidInstructionData)?;
+                Ok(Self::ProposeAction {
+                    action: payload.action,
+                })
+            }
+            24 => { // ApproveProposal
+                Ok(Self::ApproveProposal {})
+            }
+            25 => { // CastBallot
+                let payload = CastBallotPayload::try_from_slice(rest)
+                    .map_err(|_| ProgramError::InvalidInstructionData)?;
+                Ok(Self::CastBallot {
+                    ballot: payload.ballot,
+                })
+            }
+            26 => { // UnregisterVoter
+                let payload = RegisterVoterPayload::try_from_slice(rest)
+                    .map_err(|_| ProgramError::InvalidInstructionData)?;
+                Ok(Self::UnregisterVoter {
+                    voter: payload.voter
+                })
+            }
+            27 => { // CloseEligibleVoter
+                Ok(Self::CloseEligibleVoter {})
+            }
+            28 => { // CloseProposal
+                Ok(Self::CloseProposal {})
+            }
+            _ => Err(ProgramError::InvalidInstructionData),
         }
     }
 }
\ No newline at end of file
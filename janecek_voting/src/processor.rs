@@ -1,14 +1,19 @@
 use crate::error::JanecekError;
 use crate::instruction::JanecekInstruction;
-use crate::state::{PollState, PartyAccount, VoterAccount, VotingPhase, VoteType};
-use borsh::{BorshDeserialize, BorshSerialize};
+use crate::state::{account_version, load_account, load_zero_copy, load_zero_copy_mut, store_zero_copy, unpack_account, zero_copy_version, AccountType, ZeroCopyAccount, PollState, PollSeeds, Committee, Proposal, ProposalAction, CreatorPolls, ProgramConfig, PollDefaults, RegistryEntry, PartyAccount, VoterAccount, VotedParty, BallotEntry, EligibleVoter, VotingPhase, VoteType, VoteRules, VotingMode, TokenGate, PollResults, PartyResult};
+use std::cell::RefMut;
+
+use borsh::BorshSerialize;
 use solana_program::clock::Clock;
 use solana_program::{
     account_info::{next_account_info, AccountInfo},
+    bpf_loader_upgradeable::{self, UpgradeableLoaderState},
     entrypoint::ProgramResult,
     msg,
-    program::invoke_signed,
+    program::{invoke, invoke_signed},
     program_error::ProgramError,
+    program_pack::Pack,
+    program_utils::limited_deserialize,
     pubkey::Pubkey,
     system_instruction,
     sysvar::{rent::Rent, Sysvar},
@@ -27,11 +32,21 @@ pub fn process_instruction(
         JanecekInstruction::CreatePoll {
             title,
             description,
-        } => create_poll(program_id, accounts, title, description),
+            registration_duration,
+            voting_duration,
+            vote_rules,
+            token_gate,
+            voting_mode,
+            archive_period,
+            closed,
+        } => create_poll(program_id, accounts, title, description, registration_duration, voting_duration, vote_rules, token_gate, voting_mode, archive_period, closed),
         
         JanecekInstruction::CreateParty {
             title,
-        } => create_party(program_id, accounts, title),
+            description,
+            metadata_uri,
+            metadata_hash,
+        } => create_party(program_id, accounts, title, description, metadata_uri, metadata_hash),
         
         JanecekInstruction::InitiateOwnerTransfer {
             new_owner
@@ -48,21 +63,113 @@ pub fn process_instruction(
 
         JanecekInstruction::EndVoting {
         } => end_voting(program_id, accounts),
+
+        JanecekInstruction::MigrateAccount {
+        } => migrate_account(program_id, accounts),
+
+        JanecekInstruction::RetractVote {
+        } => retract_vote(program_id, accounts),
+
+        JanecekInstruction::RegisterVoter {
+            voter,
+        } => register_voter(program_id, accounts, voter),
+
+        JanecekInstruction::UnlockTokens {
+        } => unlock_tokens(program_id, accounts),
+
+        JanecekInstruction::ClosePoll {
+        } => close_poll(program_id, accounts),
+
+        JanecekInstruction::CloseParty {
+        } => close_party(program_id, accounts),
+
+        JanecekInstruction::CloseVoter {
+        } => close_voter(program_id, accounts),
+
+        JanecekInstruction::RemoveParty {
+        } => remove_party(program_id, accounts),
+
+        JanecekInstruction::UpdatePartyMetadata {
+            description,
+            metadata_uri,
+            metadata_hash,
+        } => update_party_metadata(program_id, accounts, description, metadata_uri, metadata_hash),
+
+        JanecekInstruction::UpdatePoll {
+            title,
+            description,
+        } => update_poll(program_id, accounts, title, description),
+
+        JanecekInstruction::InitializeConfig {
+            defaults,
+            creation_fee,
+        } => initialize_config(program_id, accounts, defaults, creation_fee),
+
+        JanecekInstruction::UpdateConfig {
+            admin,
+            defaults,
+            creation_fee,
+        } => update_config(program_id, accounts, admin, defaults, creation_fee),
+
+        JanecekInstruction::SetPollPaused {
+            paused,
+            extend_deadline,
+        } => set_poll_paused(program_id, accounts, paused, extend_deadline),
+
+        JanecekInstruction::SetProgramPaused {
+            paused,
+        } => set_program_paused(program_id, accounts, paused),
+
+        JanecekInstruction::CancelOwnerTransfer {} => cancel_owner_transfer(program_id, accounts),
+
+        JanecekInstruction::SetCommittee {
+            committee,
+        } => set_committee(program_id, accounts, committee),
+
+        JanecekInstruction::ProposeAction {
+            action,
+        } => propose_action(program_id, accounts, action),
+
+        JanecekInstruction::ApproveProposal {} => approve_proposal(program_id, accounts),
+
+        JanecekInstruction::CastBallot {
+            ballot,
+        } => cast_ballot(program_id, accounts, ballot),
+
+        JanecekInstruction::UnregisterVoter {
+            voter,
+        } => unregister_voter(program_id, accounts, voter),
+
+        JanecekInstruction::CloseEligibleVoter {} => close_eligible_voter(program_id, accounts),
+
+        JanecekInstruction::CloseProposal {} => close_proposal(program_id, accounts),
     }
 }
 
 
+#[allow(clippy::too_many_arguments)]
 pub fn create_poll(
     program_id: &Pubkey,
     accounts: &[AccountInfo],
     title: String,
     description: String,
+    registration_duration: Option<i64>,
+    voting_duration: Option<i64>,
+    vote_rules: Option<VoteRules>,
+    token_gate: Option<TokenGate>,
+    voting_mode: VotingMode,
+    archive_period: i64,
+    closed: bool,
 ) -> ProgramResult {
     msg!("Creating poll...");
     msg!("Title: {}", title);
     msg!("Description: {}", description);
+    msg!("Token gate: {:?}", token_gate);
+    msg!("Voting mode: {:?}", voting_mode);
+    msg!("Archive period: {}s", archive_period);
+    msg!("Closed: {}", closed);
 
-    if accounts.len() < 3 {
+    if accounts.len() < 7 {
         msg!("Insufficient accounts provided");
         return Err(ProgramError::NotEnoughAccountKeys);
     }
@@ -71,6 +178,10 @@ pub fn create_poll(
 
     let initializer = next_account_info(account_info_iter)?;
     let poll_account = next_account_info(account_info_iter)?;
+    let creator_account = next_account_info(account_info_iter)?;
+    let config_account = next_account_info(account_info_iter)?;
+    let registry_entry_account = next_account_info(account_info_iter)?;
+    let admin_account = next_account_info(account_info_iter)?;
     let system_program_account = next_account_info(account_info_iter)?;
 
     if !initializer.is_signer {
@@ -83,13 +194,68 @@ pub fn create_poll(
         return Err(ProgramError::IncorrectProgramId);
     }
 
+    check_poll_texts(&title, &description)?;
+
+    let mut config = load_program_config(program_id, config_account)?;
+
+    if config.paused {
+        msg!("Program is paused");
+        return Err(JanecekError::ProgramPaused.into());
+    }
+
+    // Fill in what the creator left out from the program defaults
+    let defaults = PollDefaults {
+        registration_duration: registration_duration.unwrap_or(config.defaults.registration_duration),
+        voting_duration: voting_duration.unwrap_or(config.defaults.voting_duration),
+        vote_rules: vote_rules.unwrap_or(config.defaults.vote_rules),
+    };
+    msg!("Registration duration: {}s, voting duration: {}s", defaults.registration_duration, defaults.voting_duration);
+    msg!("Vote rules: {:?}", defaults.vote_rules);
+
+    check_poll_defaults(&defaults)?;
+
+    if !(0..=PollState::MAX_ARCHIVE_PERIOD).contains(&archive_period) {
+        msg!("Archive period must be between 0s and {}s", PollState::MAX_ARCHIVE_PERIOD);
+        return Err(JanecekError::InvalidDuration.into());
+    }
+
+    // Validate token gate
+    if token_gate.is_some_and(|gate| gate.min_balance == 0) {
+        msg!("Token gate minimum balance must be positive");
+        return Err(JanecekError::InvalidTokenGate.into());
+    }
+
+    // Token-weighted voting needs a mint to weigh votes by
+    if matches!(voting_mode, VotingMode::TokenWeighted) && token_gate.is_none() {
+        msg!("Token-weighted voting requires a token gate");
+        return Err(JanecekError::InvalidTokenGate.into());
+    }
+
+    // Creation fee goes straight to the admin
+    if config.creation_fee > 0 {
+        if *admin_account.key != config.admin {
+            msg!("Fee recipient is not the config admin");
+            return Err(ProgramError::InvalidArgument);
+        }
+
+        invoke(
+            &system_instruction::transfer(initializer.key, admin_account.key, config.creation_fee),
+            &[
+                initializer.clone(),
+                admin_account.clone(),
+                system_program_account.clone(),
+            ],
+        )?;
+        msg!("Creation fee paid: {} lamports", config.creation_fee);
+    }
+
+    // Poll nonce is the number of polls this creator made so far
+    let mut creator_polls = load_or_create_creator_polls(program_id, initializer, creator_account, system_program_account)?;
+    let poll_nonce = creator_polls.poll_count;
+
     // Validate poll PDA
-    let title_hash = hash(title.as_bytes());
-    let description_hash = hash(description.as_bytes());
-    let (pda, bump_seed) = Pubkey::find_program_address(
-        &[b"poll", title_hash.as_ref(), description_hash.as_ref()],
-        program_id,
-    );
+    let poll_seeds = PollSeeds::Creator { creator: *initializer.key, poll_nonce };
+    let (pda, bump_seed) = poll_seeds.find_address(program_id);
     if pda != *poll_account.key {
         msg!("Invalid seeds for PDA :(");
         msg!("pda: {}", pda);
@@ -97,28 +263,20 @@ pub fn create_poll(
         return Err(ProgramError::InvalidArgument);
     }
     
-    // Create poll PDA
-    let account_len: usize = PollState::get_account_size(&title, &description);
-    let rent = Rent::get()?;
-    let rent_lamports = rent.minimum_balance(account_len);
-    let seeds: &[&[u8]] = &[ b"poll", title_hash.as_ref(), description_hash.as_ref(), &[bump_seed]];
+    // Create poll PDA, a committee gets its room once it is set
+    let account_len: usize = PollState::SIZE + token_gate.map_or(0, |_| TokenGate::SIZE);
+    let poll_nonce_bytes = poll_nonce.to_le_bytes();
+    let seeds: &[&[u8]] = &[ b"poll", initializer.key.as_ref(), &poll_nonce_bytes, &[bump_seed]];
 
     msg!("Start invoke-signed");
 
-    invoke_signed(
-        &system_instruction::create_account(
-            initializer.key,
-            poll_account.key,
-            rent_lamports,
-            account_len.try_into().unwrap(),
-            program_id,
-        ),
-        &[
-            initializer.clone(),
-            poll_account.clone(),
-            system_program_account.clone(),
-        ],
-        &[seeds],
+    create_program_account(
+        program_id,
+        initializer,
+        poll_account,
+        system_program_account,
+        account_len,
+        seeds,
     )?;
     
     msg!("PDA creation: {}", pda);
@@ -126,20 +284,47 @@ pub fn create_poll(
     let clock = Clock::get()?;
     // Initialize PollState
     let poll_state = PollState {
-        discriminator: PollState::DISCRIMINATOR.to_string(),
+        discriminator: PollState::DISCRIMINATOR,
+        version: PollState::VERSION,
         title,
         description,
         phase: VotingPhase::Registration,
         party_counter: 0,
         owner: *initializer.key,
-        expected_new_owner: *initializer.key,
+        expected_new_owner: Pubkey::default(),
         created_at: clock.unix_timestamp,
         voting_start_at: 0,
+        registration_duration: defaults.registration_duration,
+        voting_duration: defaults.voting_duration,
+        vote_rules: defaults.vote_rules,
+        closed,
+        eligible_voters: 0,
+        token_gate,
+        voting_mode,
+        rent_payer: *initializer.key,
+        vault_rent_payer: Pubkey::default(),
+        archive_period,
+        ended_at: 0,
+        seeds: poll_seeds,
+        paused: false,
+        paused_at: 0,
+        paused_duration: 0,
+        program_paused_duration: 0,
+        owner_transfer_expires_at: 0,
+        committee: None,
+        proposal_counter: 0,
     };
     msg!("Serializing poll_state: {:?}", poll_state);
     poll_state.serialize(&mut &mut poll_account.data.borrow_mut()[..])?;
     msg!("Poll state serialized");
 
+    creator_polls.poll_count += 1;
+    creator_polls.serialize(&mut &mut creator_account.data.borrow_mut()[..])?;
+    msg!("Creator polls updated");
+
+    register_poll(program_id, initializer, poll_account, &mut config, registry_entry_account, system_program_account)?;
+    config.serialize(&mut &mut config_account.data.borrow_mut()[..])?;
+
     Ok(())
 }
 
@@ -147,12 +332,16 @@ pub fn create_poll(
 pub fn create_party(
     program_id: &Pubkey,
     accounts: &[AccountInfo],
-    title: String
+    title: String,
+    description: String,
+    metadata_uri: String,
+    metadata_hash: [u8; 32],
 ) -> ProgramResult {
     msg!("Creating party...");
     msg!("Title: {}", title);
+    msg!("Metadata URI: {}", metadata_uri);
 
-    if accounts.len() < 4 {
+    if accounts.len() < 5 {
         msg!("Insufficient accounts provided");
         return Err(ProgramError::NotEnoughAccountKeys);
     }
@@ -161,33 +350,28 @@ pub fn create_party(
 
     let initializer = next_account_info(account_info_iter)?;
     let poll_account = next_account_info(account_info_iter)?;
+    let config_account = next_account_info(account_info_iter)?;
     let party_account = next_account_info(account_info_iter)?;
     let system_program_account = next_account_info(account_info_iter)?;
+    let proposal_account = next_account_info(account_info_iter).ok();
 
     if !initializer.is_signer {
         msg!("Missing required signature");
         return Err(ProgramError::MissingRequiredSignature);
     }
 
-    if poll_account.owner != program_id {
-        msg!("Poll account not owned by program");
-        return Err(ProgramError::IncorrectProgramId);
-    }
+    let poll_state = load_account::<PollState>(program_id, poll_account)?;
 
-    // Validate poll account is initialized
-    let poll_data = poll_account.data.borrow();
-    if poll_data.iter().all(|&b| b == 0) {
-        msg!("Poll account not initialized");
-        return Err(ProgramError::UninitializedAccount);
-    }
-    let poll_state = PollState::try_from_slice(&poll_data)?;
-    drop(poll_data);
+    check_not_paused(program_id, config_account, &poll_state)?;
 
-    // Only the current owner can initiate
-    if poll_state.owner != *initializer.key {
-        msg!("Only the current owner can create party");
-        return Err(ProgramError::IllegalOwner);
-    }
+    // Only the current owner or an approved committee proposal can create a party
+    let action = ProposalAction::CreateParty {
+        title: title.clone(),
+        description: description.clone(),
+        metadata_uri: metadata_uri.clone(),
+        metadata_hash,
+    };
+    authorize_owner_action(program_id, poll_account, &poll_state, initializer, proposal_account, action)?;
 
     // Only allow creating a party during registration phase
     if !matches!(poll_state.phase, VotingPhase::Registration) {
@@ -195,6 +379,12 @@ pub fn create_party(
         return Err(ProgramError::InvalidAccountData);
     }
 
+    check_party_metadata(&title, &description, &metadata_uri)?;
+
+    if poll_state.party_counter >= PollState::MAX_PARTIES {
+        msg!("Poll can have at most {} parties", PollState::MAX_PARTIES);
+        return Err(JanecekError::TooManyParties.into());
+    }
 
     // Validate party PDA
     let party_title_hash = hash(title.as_bytes());
@@ -208,9 +398,7 @@ pub fn create_party(
     }
 
     // Create party PDA 
-    let account_len = PartyAccount::get_account_size(&title);
-    let rent = Rent::get()?;
-    let rent_lamport = rent.minimum_balance(account_len);
+    let account_len = PartyAccount::SIZE;
     let seeds = &[
         b"party",
         poll_account.key.as_ref(),
@@ -218,40 +406,28 @@ pub fn create_party(
         &[party_bump_seed],
     ];
 
-    invoke_signed(
-        &system_instruction::create_account(
-            initializer.key,
-            party_account.key,
-            rent_lamport,
-            account_len.try_into().unwrap(),
-            program_id,
-        ),
-        &[
-            initializer.clone(),
-            party_account.clone(),
-            system_program_account.clone(),
-        ],
-        &[seeds],
+    create_program_account(
+        program_id,
+        initializer,
+        party_account,
+        system_program_account,
+        account_len,
+        seeds,
     )?;
 
     msg!("PDA creation: {}", party_pda);
 
 
     // Initialize PartyAccount
-    let party_state = PartyAccount {
-        discriminator: PartyAccount::DISCRIMINATOR.to_string(),
-        poll_id: *poll_account.key,
-        title,
-        positive_votes: 0,
-        negative_votes: 0,
-    };
-    party_state.serialize(&mut &mut party_account.data.borrow_mut()[..])?;
+    let mut party_state = PartyAccount::new(*poll_account.key, *initializer.key);
+    party_state.set_metadata(&title, &description, &metadata_uri, metadata_hash)?;
+    store_zero_copy(&party_state, &mut party_account.data.borrow_mut())?;
     msg!("Party state serialized");
 
     // Update PollState
-    let mut poll_state = PollState::try_from_slice(&poll_account.data.borrow())?;
+    let mut poll_state = load_account::<PollState>(program_id, poll_account)?;
     poll_state.party_counter += 1;
-    poll_state.serialize(&mut &mut poll_account.data.borrow_mut()[..])?;
+    save_poll(&poll_state, poll_account, initializer, Some(system_program_account))?;
     msg!("Poll state updated");
 
     Ok(())
@@ -267,7 +443,7 @@ pub fn initiate_owner_transfer(
     msg!("New owner: {}", new_owner);
 
 
-    if accounts.len() < 2 {
+    if accounts.len() < 3 {
         msg!("Insufficient accounts provided");
         return Err(ProgramError::NotEnoughAccountKeys);
     }
@@ -276,25 +452,16 @@ pub fn initiate_owner_transfer(
 
     let initializer = next_account_info(account_info_iter)?;
     let poll_account = next_account_info(account_info_iter)?;
+    let config_account = next_account_info(account_info_iter)?;
 
     if !initializer.is_signer {
         msg!("Missing required signature");
         return Err(ProgramError::MissingRequiredSignature);
     }
 
-    if poll_account.owner != program_id {
-        msg!("Poll account not owned by program");
-        return Err(ProgramError::IncorrectProgramId);
-    }
+    let mut poll_state = load_account::<PollState>(program_id, poll_account)?;
 
-    // Validate poll account is initialized
-    let poll_data = poll_account.data.borrow();
-    if poll_data.iter().all(|&b| b == 0) {
-        msg!("Poll account not initialized");
-        return Err(ProgramError::UninitializedAccount);
-    }
-    let mut poll_state = PollState::try_from_slice(&poll_account.data.borrow())?;
-    drop(poll_data);
+    check_not_paused(program_id, config_account, &poll_state)?;
 
     // Only the current owner can initiate
     if poll_state.owner != *initializer.key {
@@ -302,9 +469,17 @@ pub fn initiate_owner_transfer(
         return Err(ProgramError::IllegalOwner);
     }
 
-    // Update PollState
+    // Default key marks that no transfer is pending
+    if new_owner == Pubkey::default() {
+        msg!("New owner can not be the default key");
+        return Err(ProgramError::InvalidArgument);
+    }
+
+    // Update PollState, a new nomination replaces the pending one
+    let clock = Clock::get()?;
     poll_state.expected_new_owner = new_owner;
-    poll_state.serialize(&mut &mut poll_account.data.borrow_mut()[..])?;
+    poll_state.owner_transfer_expires_at = clock.unix_timestamp + PollState::OWNER_TRANSFER_DURATION;
+    save_poll(&poll_state, poll_account, initializer, None)?;
     msg!("Poll state updated");
 
     Ok(())
@@ -317,7 +492,7 @@ pub fn accept_owner_transfer(
 ) -> ProgramResult {
     msg!("Accepting owner transfer...");
 
-    if accounts.len() < 2 {
+    if accounts.len() < 3 {
         msg!("Insufficient accounts provided");
         return Err(ProgramError::NotEnoughAccountKeys);
     }
@@ -326,25 +501,16 @@ pub fn accept_owner_transfer(
 
     let initializer = next_account_info(account_info_iter)?;
     let poll_account = next_account_info(account_info_iter)?;
+    let config_account = next_account_info(account_info_iter)?;
 
     if !initializer.is_signer {
         msg!("Missing required signature");
         return Err(ProgramError::MissingRequiredSignature);
     }
 
-    if poll_account.owner != program_id {
-        msg!("Poll account not owned by program");
-        return Err(ProgramError::IncorrectProgramId);
-    }
+    let mut poll_state = load_account::<PollState>(program_id, poll_account)?;
 
-    // Validate poll account is initialized
-    let poll_data = poll_account.data.borrow();
-    if poll_data.iter().all(|&b| b == 0) {
-        msg!("Poll account not initialized");
-        return Err(ProgramError::UninitializedAccount);
-    }
-    let mut poll_state = PollState::try_from_slice(&poll_account.data.borrow())?;
-    drop(poll_data);
+    check_not_paused(program_id, config_account, &poll_state)?;
 
     // Only the expacted owner can initiate
     if poll_state.expected_new_owner != *initializer.key {
@@ -352,8 +518,17 @@ pub fn accept_owner_transfer(
         return Err(ProgramError::IllegalOwner);
     }
 
+    let clock = Clock::get()?;
+    if clock.unix_timestamp > poll_state.owner_transfer_expires_at {
+        msg!("Owner transfer expired at {}", poll_state.owner_transfer_expires_at);
+        return Err(JanecekError::OwnerTransferExpired.into());
+    }
+
+    // Clear the nomination so it can not be accepted again
     poll_state.owner = *initializer.key;
-    poll_state.serialize(&mut &mut poll_account.data.borrow_mut()[..])?;
+    poll_state.expected_new_owner = Pubkey::default();
+    poll_state.owner_transfer_expires_at = 0;
+    save_poll(&poll_state, poll_account, initializer, None)?;
 
     msg!("Poll state updated");
     
@@ -361,11 +536,11 @@ pub fn accept_owner_transfer(
 }
 
 
-pub fn start_voting(
+pub fn cancel_owner_transfer(
     program_id: &Pubkey,
     accounts: &[AccountInfo],
 ) -> ProgramResult {
-    msg!("Starting voting...");
+    msg!("Cancelling owner transfer...");
 
     if accounts.len() < 2 {
         msg!("Insufficient accounts provided");
@@ -382,74 +557,48 @@ pub fn start_voting(
         return Err(ProgramError::MissingRequiredSignature);
     }
 
-    if poll_account.owner != program_id {
-        msg!("Poll account not owned by program");
-        return Err(ProgramError::IncorrectProgramId);
-    }
+    let mut poll_state = load_account::<PollState>(program_id, poll_account)?;
 
-    // Validate poll account is initialized
-    let poll_data = poll_account.data.borrow();
-    if poll_data.iter().all(|&b| b == 0) {
-        msg!("Poll account not initialized");
-        return Err(ProgramError::UninitializedAccount);
-    }
-    let mut poll_state = PollState::try_from_slice(&poll_account.data.borrow())?;
-    drop(poll_data);
-    
-    // Only the current owner can initiate
+    // Not checked against the pause, withdrawing a nomination only takes power away
+
+    // Only the current owner can cancel
     if poll_state.owner != *initializer.key {
-        msg!("Only the current owner can initiate transfer");
+        msg!("Only the current owner can cancel transfer");
         return Err(ProgramError::IllegalOwner);
     }
 
-    // Only allow starting voting during registration phase
-    if !matches!(poll_state.phase, VotingPhase::Registration) {
-        msg!("Can only start voting during registration phase");
-        return Err(ProgramError::InvalidAccountData);
-    }
-    
-    let clock = Clock::get()?;
-    let elapsed = clock.unix_timestamp - poll_state.created_at;
-
-    #[cfg(feature = "test-mode")]
-    const MIN_TIME_DELTA: i64 = 1;
-
-    #[cfg(not(feature = "test-mode"))]
-    const MIN_TIME_DELTA: i64 = 60 * 60 * 24;
-
-    // Check that the registration period is not too short
-    if elapsed < MIN_TIME_DELTA {
-        msg!("Registration period must last at least 24 hours");
-        return Err(JanecekError::RegistrationPhaseTooShort.into());
+    if poll_state.expected_new_owner == Pubkey::default() {
+        msg!("No owner transfer is pending");
+        return Err(JanecekError::NoPendingOwnerTransfer.into());
     }
 
-    poll_state.phase = VotingPhase::Voting;
-    poll_state.voting_start_at = clock.unix_timestamp;
-    poll_state.serialize(&mut &mut poll_account.data.borrow_mut()[..])?;
+    poll_state.expected_new_owner = Pubkey::default();
+    poll_state.owner_transfer_expires_at = 0;
+    save_poll(&poll_state, poll_account, initializer, None)?;
     msg!("Poll state updated");
-    
+
     Ok(())
 }
 
 
-pub fn vote(
+pub fn set_committee(
     program_id: &Pubkey,
     accounts: &[AccountInfo],
-    vote_type: VoteType
+    committee: Committee,
 ) -> ProgramResult {
-    msg!("Voting...");
+    msg!("Setting committee...");
+    msg!("Members: {}, threshold: {}", committee.members.len(), committee.threshold);
 
-    if accounts.len() < 5 {
+    if accounts.len() < 4 {
         msg!("Insufficient accounts provided");
         return Err(ProgramError::NotEnoughAccountKeys);
     }
 
     let account_info_iter = &mut accounts.iter();
-    
+
     let initializer = next_account_info(account_info_iter)?;
     let poll_account = next_account_info(account_info_iter)?;
-    let party_account = next_account_info(account_info_iter)?;
-    let voter_account = next_account_info(account_info_iter)?;
+    let config_account = next_account_info(account_info_iter)?;
     let system_program_account = next_account_info(account_info_iter)?;
 
     if !initializer.is_signer {
@@ -457,188 +606,2644 @@ pub fn vote(
         return Err(ProgramError::MissingRequiredSignature);
     }
 
-    if poll_account.owner != program_id {
-        msg!("Poll account not owned by program");
-        return Err(ProgramError::IncorrectProgramId);
-    }
+    let mut poll_state = load_account::<PollState>(program_id, poll_account)?;
 
-    let poll_data = poll_account.data.borrow();
+    check_not_paused(program_id, config_account, &poll_state)?;
 
-    if poll_data.iter().all(|&b| b == 0) {
-        msg!("Poll account not initialized");
-        return Err(ProgramError::UninitializedAccount);
+    // Only the current owner can set the committee
+    if poll_state.owner != *initializer.key {
+        msg!("Only the current owner can set the committee");
+        return Err(ProgramError::IllegalOwner);
     }
 
-    let mut poll_state = PollState::try_from_slice(&poll_data)?;
-    drop(poll_data);
-
-    // Check Registration phase
-    if !matches!(poll_state.phase, VotingPhase::Voting) {
-        msg!("User can vote only during voting phase");
+    if !matches!(poll_state.phase, VotingPhase::Registration) {
+        msg!("Can only set the committee during registration phase");
         return Err(ProgramError::InvalidAccountData);
     }
 
-    // Check that the voting period is not finished
-    let clock = Clock::get()?;
-    let elapsed = clock.unix_timestamp - poll_state.created_at;
-
-
-    #[cfg(feature = "test-mode")]
-    const MIN_TIME_DELTA: i64 = 60;
-
-    #[cfg(not(feature = "test-mode"))]
-    const MIN_TIME_DELTA: i64 = 60 * 60 * 24 * 7;
-
-
-    if elapsed > MIN_TIME_DELTA {
-        msg!("Voting period is finished");
-        poll_state.phase = VotingPhase::Results;
-        poll_state.serialize(&mut &mut poll_account.data.borrow_mut()[..])?;
-        return Ok(()); 
+    // The owner alone must not be able to replace a committee
+    if poll_state.committee.is_some() {
+        msg!("Poll already has a committee");
+        return Err(JanecekError::InvalidCommittee.into());
     }
 
-    // Validate party account is initialized
-    let party_data: std::cell::Ref<'_, &mut [u8]> = party_account.data.borrow();
-    
-    if party_data.iter().all(|&b| b == 0) {
-        msg!("Party account not initialized");
-        return Err(ProgramError::UninitializedAccount);
+    if !committee.is_valid() {
+        msg!("Committee needs 1 to {} distinct members and a threshold of at most the member count", Committee::MAX_MEMBERS);
+        return Err(JanecekError::InvalidCommittee.into());
     }
-    
-    let mut party_state = PartyAccount::try_from_slice(&party_data)?;
-    drop(party_data);
 
+    poll_state.committee = Some(committee);
 
-    // Validate voter account is initialized
-    let voter_initialized = {
-        let voter_data = voter_account.data.borrow();
-        !voter_data.iter().all(|&b| b == 0)
-    };
-    
-    if !voter_initialized {
-        msg!("Voter account not initialized");
-        
-        // Compute voter PDA 
-        let (voter_pda, voter_bump_seed) = Pubkey::find_program_address(
-            &[b"voter", poll_account.key.as_ref(), initializer.key.as_ref()],
-            program_id,
-        );
+    // Polls are created without room for a committee, the owner pays for it
+    resize_account(poll_account, initializer, system_program_account, poll_state.get_account_size())?;
+    save_poll(&poll_state, poll_account, initializer, None)?;
+    msg!("Poll state updated");
 
-        // Validate voter PDA
-        if voter_pda != *voter_account.key {
-            msg!("Invalid seeds for PDA");
-            return Err(ProgramError::InvalidArgument);
-        }
+    Ok(())
+}
 
-        // Create voter PDA 
-        let account_len = VoterAccount::get_account_size();
-        let rent = Rent::get()?;
-        let rent_lamport = rent.minimum_balance(account_len);
-        let seeds = &[
-            b"voter",
-            poll_account.key.as_ref(),
-            initializer.key.as_ref(),
-            &[voter_bump_seed],
-        ];
 
-        invoke_signed(
-            &system_instruction::create_account(
-                initializer.key,
-                voter_account.key,
-                rent_lamport,
-                account_len.try_into().unwrap(),
-                program_id,
-            ),
-            &[
-                initializer.clone(),
-                voter_account.clone(),
-                system_program_account.clone(),
-            ],
-            &[seeds],
-        )?;
+pub fn propose_action(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    action: ProposalAction,
+) -> ProgramResult {
+    msg!("Proposing action...");
+    msg!("Action: {:?}", action);
 
-        // Initialize VoterAccount
-        let voter_state = VoterAccount {
-            discriminator: VoterAccount::DISCRIMINATOR.to_string(),
-            poll_key: *poll_account.key,
-            voter_key: *initializer.key,
-            positive_used: 0,
-            negative_used: 0,
-            voted_parties: Vec::new()
-        };
-    
-        voter_state.serialize(&mut &mut voter_account.data.borrow_mut()[..])?;
-        msg!("Voter serialized successfully");
-        msg!("Voter state serialized");
-    } 
+    if accounts.len() < 5 {
+        msg!("Insufficient accounts provided");
+        return Err(ProgramError::NotEnoughAccountKeys);
+    }
 
-    let mut voter_state = {
-        let voter_data = voter_account.data.borrow();
-        let mut slice: &[u8] = &voter_data;          // &mut &[u8]
-        let vs = VoterAccount::deserialize(&mut slice)?;
-        vs
-    };
+    let account_info_iter = &mut accounts.iter();
 
-    // Only the current owner can initiate
-    if voter_state.voter_key != *initializer.key {
-        msg!("Only the owner of Voter account can vote");
-        return Err(ProgramError::IllegalOwner);
-    }
+    let initializer = next_account_info(account_info_iter)?;
+    let poll_account = next_account_info(account_info_iter)?;
+    let config_account = next_account_info(account_info_iter)?;
+    let proposal_account = next_account_info(account_info_iter)?;
+    let system_program_account = next_account_info(account_info_iter)?;
 
-    // Can not vote twice
-    if voter_state.voted_parties.contains(party_account.key) {
-        msg!("Voter already voted for this party");
-        return Err(JanecekError::AlreadyVoted.into());
+    if !initializer.is_signer {
+        msg!("Missing required signature");
+        return Err(ProgramError::MissingRequiredSignature);
     }
 
-    match vote_type {
-        VoteType::Positive => {
-            if voter_state.positive_used >= 2 {
-                return Err(JanecekError::NoPositiveVoice.into());
-            }
-            voter_state.positive_used += 1;
-            msg!("Voter state updated");
-        },
+    let mut poll_state = load_account::<PollState>(program_id, poll_account)?;
 
-        VoteType::Negative => {
-            if voter_state.negative_used >= 1 {
-                return Err(JanecekError::NoNegativeVoice.into());
-            }
-            if voter_state.positive_used < 2 {
-                return Err(JanecekError::MustUseAllPositiveVoices.into());
-            }
-            voter_state.negative_used += 1;
-            msg!("Voter state updated");
-        },
+    // Like SetPollPaused itself, pause proposals go through so a paused poll can be resumed
+    if !matches!(action, ProposalAction::SetPollPaused { .. }) {
+        check_not_paused(program_id, config_account, &poll_state)?;
     }
 
-    voter_state.voted_parties.push(*party_account.key);
-    voter_state.serialize(&mut &mut voter_account.data.borrow_mut()[..])?;
-    msg!("Voter serialized successfully");
+    let member_index = committee_member_index(&poll_state, initializer)?;
 
+    // Texts must fit the fixed-size proposal
+    match &action {
+        ProposalAction::CreateParty { title, description, metadata_uri, .. } => check_party_metadata(title, description, metadata_uri)?,
+        ProposalAction::UpdatePartyMetadata { description, metadata_uri, .. } => check_party_metadata("", description, metadata_uri)?,
+        ProposalAction::UpdatePoll { title, description } => check_poll_texts(title, description)?,
+        _ => {}
+    }
 
-    match vote_type {
-        VoteType::Positive => {
-            party_state.positive_votes += 1;
-        },
-        VoteType::Negative => {
-            party_state.negative_votes += 1;
-        },
+    // Validate proposal PDA
+    let index_bytes = poll_state.proposal_counter.to_le_bytes();
+    let (proposal_pda, proposal_bump_seed) = Pubkey::find_program_address(
+        &[b"proposal", poll_account.key.as_ref(), &index_bytes],
+        program_id,
+    );
+    if proposal_pda != *proposal_account.key {
+        msg!("Invalid seeds for PDA");
+        return Err(ProgramError::InvalidArgument);
     }
 
-    party_state.serialize(&mut &mut party_account.data.borrow_mut()[..])?;
-    
+    create_program_account(
+        program_id,
+        initializer,
+        proposal_account,
+        system_program_account,
+        Proposal::SIZE,
+        &[b"proposal", poll_account.key.as_ref(), &index_bytes, &[proposal_bump_seed]],
+    )?;
+    msg!("PDA creation: {}", proposal_pda);
+
+    let proposal = Proposal {
+        discriminator: Proposal::DISCRIMINATOR,
+        version: Proposal::VERSION,
+        poll_id: *poll_account.key,
+        index: poll_state.proposal_counter,
+        proposer: *initializer.key,
+        action,
+        approvals: 1 << member_index,
+        executed: false,
+    };
+    proposal.serialize(&mut &mut proposal_account.data.borrow_mut()[..])?;
+    msg!("Proposal #{} created", proposal.index);
+
+    poll_state.proposal_counter += 1;
+    save_poll(&poll_state, poll_account, initializer, Some(system_program_account))?;
+    msg!("Poll state updated");
+
     Ok(())
 }
 
 
-pub fn end_voting(
+pub fn approve_proposal(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+) -> ProgramResult {
+    msg!("Approving proposal...");
+
+    if accounts.len() < 4 {
+        msg!("Insufficient accounts provided");
+        return Err(ProgramError::NotEnoughAccountKeys);
+    }
+
+    let account_info_iter = &mut accounts.iter();
+
+    let initializer = next_account_info(account_info_iter)?;
+    let poll_account = next_account_info(account_info_iter)?;
+    let config_account = next_account_info(account_info_iter)?;
+    let proposal_account = next_account_info(account_info_iter)?;
+
+    if !initializer.is_signer {
+        msg!("Missing required signature");
+        return Err(ProgramError::MissingRequiredSignature);
+    }
+
+    let poll_state = load_account::<PollState>(program_id, poll_account)?;
+
+    let member_index = committee_member_index(&poll_state, initializer)?;
+    let mut proposal = load_proposal(program_id, poll_account, proposal_account)?;
+
+    // Pause proposals go through so a paused poll can be resumed, see `propose_action`
+    if !matches!(proposal.action, ProposalAction::SetPollPaused { .. }) {
+        check_not_paused(program_id, config_account, &poll_state)?;
+    }
+
+    if proposal.approvals & (1 << member_index) != 0 {
+        msg!("Member already approved proposal #{}", proposal.index);
+        return Err(JanecekError::AlreadyApproved.into());
+    }
+
+    proposal.approvals |= 1 << member_index;
+    proposal.serialize(&mut &mut proposal_account.data.borrow_mut()[..])?;
+    msg!("Proposal #{} has {} approvals", proposal.index, proposal.approval_count());
+
+    Ok(())
+}
+
+
+pub fn start_voting(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+) -> ProgramResult {
+    msg!("Starting voting...");
+
+    if accounts.len() < 3 {
+        msg!("Insufficient accounts provided");
+        return Err(ProgramError::NotEnoughAccountKeys);
+    }
+
+    let account_info_iter = &mut accounts.iter();
+
+    let initializer = next_account_info(account_info_iter)?;
+    let poll_account = next_account_info(account_info_iter)?;
+    let config_account = next_account_info(account_info_iter)?;
+    let proposal_account = next_account_info(account_info_iter).ok();
+
+    if !initializer.is_signer {
+        msg!("Missing required signature");
+        return Err(ProgramError::MissingRequiredSignature);
+    }
+
+    let mut poll_state = load_account::<PollState>(program_id, poll_account)?;
+
+    let config = check_not_paused(program_id, config_account, &poll_state)?;
+    
+    // Only the current owner or an approved committee proposal can start voting
+    authorize_owner_action(program_id, poll_account, &poll_state, initializer, proposal_account, ProposalAction::StartVoting)?;
+
+    // Only allow starting voting during registration phase
+    if !matches!(poll_state.phase, VotingPhase::Registration) {
+        msg!("Can only start voting during registration phase");
+        return Err(ProgramError::InvalidAccountData);
+    }
+    
+    let clock = Clock::get()?;
+    let elapsed = clock.unix_timestamp - poll_state.created_at;
+
+    // Check that the registration period is not too short
+    if elapsed < poll_state.registration_duration {
+        msg!("Registration period must last at least {} seconds", poll_state.registration_duration);
+        return Err(JanecekError::RegistrationPhaseTooShort.into());
+    }
+
+    poll_state.phase = VotingPhase::Voting;
+    poll_state.voting_start_at = clock.unix_timestamp;
+    poll_state.program_paused_duration = config.paused_duration;
+    save_poll(&poll_state, poll_account, initializer, None)?;
+    msg!("Poll state updated");
+    
+    Ok(())
+}
+
+
+pub fn vote(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    vote_type: VoteType
+) -> ProgramResult {
+    msg!("Voting...");
+
+    if accounts.len() < 6 {
+        msg!("Insufficient accounts provided");
+        return Err(ProgramError::NotEnoughAccountKeys);
+    }
+
+    let account_info_iter = &mut accounts.iter();
+    
+    let initializer = next_account_info(account_info_iter)?;
+    let poll_account = next_account_info(account_info_iter)?;
+    let config_account = next_account_info(account_info_iter)?;
+    let party_account = next_account_info(account_info_iter)?;
+    let voter_account = next_account_info(account_info_iter)?;
+    let system_program_account = next_account_info(account_info_iter)?;
+
+    // A single vote is a ballot of one
+    cast_votes(
+        program_id,
+        initializer,
+        poll_account,
+        config_account,
+        voter_account,
+        system_program_account,
+        &[(party_account, vote_type)],
+        account_info_iter,
+    )
+}
+
+
+pub fn cast_ballot(
     program_id: &Pubkey,
     accounts: &[AccountInfo],
+    ballot: Vec<BallotEntry>,
+) -> ProgramResult {
+    msg!("Casting ballot...");
+
+    if ballot.is_empty() {
+        msg!("Ballot has no votes");
+        return Err(ProgramError::InvalidInstructionData);
+    }
+
+    if accounts.len() < 5 + ballot.len() {
+        msg!("Insufficient accounts provided");
+        return Err(ProgramError::NotEnoughAccountKeys);
+    }
+
+    let account_info_iter = &mut accounts.iter();
+
+    let initializer = next_account_info(account_info_iter)?;
+    let poll_account = next_account_info(account_info_iter)?;
+    let config_account = next_account_info(account_info_iter)?;
+    let voter_account = next_account_info(account_info_iter)?;
+    let system_program_account = next_account_info(account_info_iter)?;
+
+    // Party accounts follow in ballot order
+    let mut votes = Vec::with_capacity(ballot.len());
+    for entry in &ballot {
+        let party_account = next_account_info(account_info_iter)?;
+        if *party_account.key != entry.party {
+            msg!("Party account {} does not match ballot entry {}", party_account.key, entry.party);
+            return Err(JanecekError::PartyListMismatch.into());
+        }
+        votes.push((party_account, entry.vote_type));
+    }
+
+    cast_votes(
+        program_id,
+        initializer,
+        poll_account,
+        config_account,
+        voter_account,
+        system_program_account,
+        &votes,
+        account_info_iter,
+    )
+}
+
+
+// Checks the whole set of votes against the rules before any of them is applied,
+// the remaining accounts are the optional eligibility and token accounts of `Vote`
+#[allow(clippy::too_many_arguments)]
+fn cast_votes<'a, 'b>(
+    program_id: &Pubkey,
+    initializer: &'b AccountInfo<'a>,
+    poll_account: &'b AccountInfo<'a>,
+    config_account: &'b AccountInfo<'a>,
+    voter_account: &'b AccountInfo<'a>,
+    system_program_account: &'b AccountInfo<'a>,
+    votes: &[(&'b AccountInfo<'a>, VoteType)],
+    account_info_iter: &mut std::slice::Iter<'b, AccountInfo<'a>>,
 ) -> ProgramResult {
-    msg!("Ending voting... {} {}", program_id, accounts.len());
+    if !initializer.is_signer {
+        msg!("Missing required signature");
+        return Err(ProgramError::MissingRequiredSignature);
+    }
+
+    let mut poll_state = load_account::<PollState>(program_id, poll_account)?;
+
+    let config = check_not_paused(program_id, config_account, &poll_state)?;
 
-    // let account_info_iter = &mut accounts.iter();
+    // Check Results phase
+    if matches!(poll_state.phase, VotingPhase::Results) {
+        msg!("Voting period is finished");
+        return Err(JanecekError::VotingPeriodFinished.into());
+    }
+
+    // Check Registration phase
+    if !matches!(poll_state.phase, VotingPhase::Voting) {
+        msg!("User can vote only during voting phase");
+        return Err(ProgramError::InvalidAccountData);
+    }
+
+    // Check that the voting period is not finished, the poll itself is finalized by EndVoting
+    let clock = Clock::get()?;
+
+    if clock.unix_timestamp > poll_state.voting_ends_at(&config) {
+        msg!("Voting period is finished");
+        return Err(JanecekError::VotingPeriodFinished.into());
+    }
+
+    // Closed polls require the voter's eligibility PDA
+    if poll_state.closed {
+        let eligible_voter_account = next_account_info(account_info_iter).map_err(|_| {
+            msg!("Missing eligibility account for closed poll");
+            JanecekError::VoterNotEligible
+        })?;
+
+        // Registered voters have an initialized account of the eligibility type, read from its header
+        let registered = load_eligible_voter(program_id, poll_account, eligible_voter_account)
+            .and_then(|eligible_state| {
+                if eligible_state.voter_key != *initializer.key {
+                    return Err(ProgramError::InvalidAccountData);
+                }
+                check_pda(program_id, eligible_voter_account, &[b"eligible", poll_account.key.as_ref(), initializer.key.as_ref()], eligible_state.bump)
+            })
+            .is_ok();
+
+        if !registered {
+            msg!("Voter {} is not registered for this poll", initializer.key);
+            return Err(JanecekError::VoterNotEligible.into());
+        }
+    }
+
+    // Voter accounts are created with their data, an empty one was never created
+    let voter_initialized = !voter_account.data_is_empty();
+
+    // Token-weighted voters only prove their balance once, later votes reuse the locked weight
+    let token_weighted = matches!(poll_state.voting_mode, VotingMode::TokenWeighted);
+    let mut vote_weight: u64 = 1;
+    let mut locked_tokens: u64 = 0;
+
+    // Token-gated polls require the voter's token account for the gate mint
+    if let Some(token_gate) = poll_state.token_gate {
+        if !(token_weighted && voter_initialized) {
+            let token_account = next_account_info(account_info_iter).map_err(|_| {
+                msg!("Missing token account for token-gated poll");
+                JanecekError::VoterNotEligible
+            })?;
+
+            if token_account.owner != &spl_token::id() {
+                msg!("Token account not owned by token program");
+                return Err(ProgramError::IncorrectProgramId);
+            }
+
+            let token_state = spl_token::state::Account::unpack(&token_account.data.borrow())?;
+
+            if token_state.owner != *initializer.key || token_state.mint != token_gate.mint {
+                msg!("Token account does not belong to voter or poll mint");
+                return Err(JanecekError::VoterNotEligible.into());
+            }
+
+            if token_state.amount < token_gate.min_balance {
+                msg!("Token balance {} is below required {}", token_state.amount, token_gate.min_balance);
+                return Err(JanecekError::InsufficientTokenBalance.into());
+            }
+
+            // Lock the whole balance so it can not be moved to a fresh wallet and weighed again
+            if token_weighted {
+                let vault_account = next_account_info(account_info_iter)?;
+                let mint_account = next_account_info(account_info_iter)?;
+                let token_program_account = next_account_info(account_info_iter)?;
+
+                let vault_created = lock_voter_tokens(
+                    program_id,
+                    initializer,
+                    poll_account,
+                    token_account,
+                    vault_account,
+                    mint_account,
+                    token_program_account,
+                    system_program_account,
+                    &token_gate,
+                    token_state.amount,
+                )?;
+
+                if vault_created {
+                    poll_state.vault_rent_payer = *initializer.key;
+                    save_poll(&poll_state, poll_account, initializer, Some(system_program_account))?;
+                }
+
+                vote_weight = token_state.amount;
+                locked_tokens = token_state.amount;
+            }
+        }
+    }
+
+    // Every party at most once per ballot, its account can only be borrowed once anyway
+    for (index, (party_account, _)) in votes.iter().enumerate() {
+        if votes[..index].iter().any(|(other, _)| other.key == party_account.key) {
+            msg!("Party {} appears more than once in the ballot", party_account.key);
+            return Err(JanecekError::AlreadyVoted.into());
+        }
+    }
+
+    let mut party_data = Vec::with_capacity(votes.len());
+    for (party_account, _) in votes {
+        party_data.push(load_party_mut(program_id, poll_account, party_account, &poll_state.owner)?);
+    }
+
+
+    let voter_len = VoterAccount::get_account_size(&poll_state.vote_rules);
+    if !voter_initialized {
+        msg!("Voter account not initialized");
+        
+        // Compute voter PDA 
+        let (voter_pda, voter_bump_seed) = Pubkey::find_program_address(
+            &[b"voter", poll_account.key.as_ref(), initializer.key.as_ref()],
+            program_id,
+        );
+
+        // Validate voter PDA
+        if voter_pda != *voter_account.key {
+            msg!("Invalid seeds for PDA");
+            return Err(ProgramError::InvalidArgument);
+        }
+
+        // Create voter PDA 
+        let seeds = &[
+            b"voter",
+            poll_account.key.as_ref(),
+            initializer.key.as_ref(),
+            &[voter_bump_seed],
+        ];
+
+        create_program_account(
+            program_id,
+            initializer,
+            voter_account,
+            system_program_account,
+            voter_len,
+            seeds,
+        )?;
+
+        // Initialize VoterAccount
+        let voter_state = VoterAccount::new(*poll_account.key, *initializer.key, vote_weight, locked_tokens, voter_bump_seed);
+        store_zero_copy(&voter_state, &mut voter_account.data.borrow_mut())?;
+        msg!("Voter serialized successfully");
+        msg!("Voter state serialized");
+    } else if voter_account.owner == program_id
+        && voter_account.data_len() < voter_len
+        && zero_copy_version::<VoterAccount>(&voter_account.data.borrow()).is_some_and(|version| version < VoterAccount::VERSION)
+    {
+        // Voters of an older, smaller layout grow to the slots of the rules while they are upgraded
+        check_voter_pda(program_id, poll_account, initializer, voter_account)?;
+        upgrade_zero_copy::<VoterAccount>(voter_account, initializer, system_program_account, voter_len, initializer.key)?;
+    }
+
+    // Existing voter accounts are re-derived as well, not just freshly created ones
+    let mut voter_data = load_voter_mut(program_id, poll_account, initializer, voter_account)?;
+    let (voter_state, voted_parties) = VoterAccount::unpack_mut(&mut voter_data);
+
+    // Can not vote twice
+    for (party_account, _) in votes {
+        if voter_state.find_vote(voted_parties, party_account.key).is_some() {
+            msg!("Voter already voted for party {}", party_account.key);
+            return Err(JanecekError::AlreadyVoted.into());
+        }
+    }
+
+    let positive_cast = votes.iter().filter(|(_, vote_type)| *vote_type == VoteType::Positive).count();
+    let negative_cast = votes.len() - positive_cast;
+    check_ballot(&poll_state.vote_rules, voter_state, positive_cast, negative_cast)?;
+
+    for ((party_account, vote_type), party_data) in votes.iter().zip(party_data.iter_mut()) {
+        let party_state = PartyAccount::from_bytes_mut(party_data);
+
+        voter_state.push_vote(
+            voted_parties,
+            VotedParty::new(*party_account.key, *vote_type, clock.unix_timestamp, clock.slot),
+        )?;
+
+        // Counters are updated in place, nothing is serialized
+        match vote_type {
+            VoteType::Positive => {
+                party_state.positive_votes = party_state.positive_votes.get()
+                    .checked_add(voter_state.vote_weight.get())
+                    .ok_or(JanecekError::VoteOverflow)?
+                    .into();
+            },
+            VoteType::Negative => {
+                party_state.negative_votes = party_state.negative_votes.get()
+                    .checked_add(voter_state.vote_weight.get())
+                    .ok_or(JanecekError::VoteOverflow)?
+                    .into();
+            },
+        }
+    }
+
+    voter_state.positive_used += positive_cast as u8;
+    voter_state.negative_used += negative_cast as u8;
+    msg!("Voter state updated");
+    
+    Ok(())
+}
+
+
+// Voices left after the ballot, negative votes need enough positive ones in the same or earlier ballots
+fn check_ballot(vote_rules: &VoteRules, voter_state: &VoterAccount, positive_cast: usize, negative_cast: usize) -> ProgramResult {
+    let positive_used = voter_state.positive_used as usize + positive_cast;
+    let negative_used = voter_state.negative_used as usize + negative_cast;
+
+    if positive_used > vote_rules.positive_votes as usize {
+        return Err(JanecekError::NoPositiveVoice.into());
+    }
+
+    if negative_cast > 0 {
+        if !vote_rules.allow_negative || negative_used > vote_rules.negative_votes as usize {
+            return Err(JanecekError::NoNegativeVoice.into());
+        }
+        if (positive_used as u16) < vote_rules.positives_required_for(negative_used as u8) {
+            return Err(JanecekError::MustUseAllPositiveVoices.into());
+        }
+    }
 
     Ok(())
-}
\ No newline at end of file
+}
+
+
+pub fn end_voting(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+) -> ProgramResult {
+    msg!("Ending voting...");
+
+    if accounts.len() < 5 {
+        msg!("Insufficient accounts provided");
+        return Err(ProgramError::NotEnoughAccountKeys);
+    }
+
+    let account_info_iter = &mut accounts.iter();
+
+    let initializer = next_account_info(account_info_iter)?;
+    let poll_account = next_account_info(account_info_iter)?;
+    let config_account = next_account_info(account_info_iter)?;
+    let results_account = next_account_info(account_info_iter)?;
+    let system_program_account = next_account_info(account_info_iter)?;
+    let mut party_accounts = account_info_iter.as_slice();
+
+    if !initializer.is_signer {
+        msg!("Missing required signature");
+        return Err(ProgramError::MissingRequiredSignature);
+    }
+
+    let mut poll_state = load_account::<PollState>(program_id, poll_account)?;
+
+    let config = check_not_paused(program_id, config_account, &poll_state)?;
+
+    // Only allow ending voting during voting phase
+    if !matches!(poll_state.phase, VotingPhase::Voting) {
+        msg!("Can only end voting during voting phase");
+        return Err(ProgramError::InvalidAccountData);
+    }
+
+    // Anyone can end an expired poll, only the owner can close it early
+    let clock = Clock::get()?;
+
+    if clock.unix_timestamp <= poll_state.voting_ends_at(&config) {
+        if poll_state.committee.is_some() {
+            // Approved proposal comes right before the parties
+            let (proposal_account, rest) = party_accounts.split_first().ok_or(ProgramError::NotEnoughAccountKeys)?;
+            party_accounts = rest;
+            authorize_committee_action(program_id, poll_account, &poll_state, initializer, proposal_account, ProposalAction::EndVoting)?;
+        } else if poll_state.owner != *initializer.key {
+            msg!("Only the current owner can end voting before the deadline");
+            return Err(JanecekError::VotingPeriodNotFinished.into());
+        }
+    }
+
+    // Every party of the poll must be passed exactly once
+    if party_accounts.len() as u64 != poll_state.party_counter {
+        msg!("Expected {} party accounts, got {}", poll_state.party_counter, party_accounts.len());
+        return Err(JanecekError::PartyListMismatch.into());
+    }
+
+    let mut party_results: Vec<PartyResult> = Vec::with_capacity(party_accounts.len());
+
+    for party_account in party_accounts {
+        let party_state = load_party(program_id, poll_account, party_account)?;
+
+        if party_results.iter().any(|result| result.party == *party_account.key) {
+            msg!("Party {} passed more than once", party_account.key);
+            return Err(JanecekError::PartyListMismatch.into());
+        }
+
+        party_results.push(PartyResult {
+            party: *party_account.key,
+            positive_votes: party_state.positive_votes.get(),
+            negative_votes: party_state.negative_votes.get(),
+            net_votes: party_state.positive_votes.get() as i128 - party_state.negative_votes.get() as i128,
+        });
+    }
+
+    // Final standings, best net score first
+    party_results.sort_by_key(|result| std::cmp::Reverse(result.net_votes));
+
+    // Validate results PDA
+    let (results_pda, results_bump_seed) = Pubkey::find_program_address(
+        &[b"results", poll_account.key.as_ref()],
+        program_id,
+    );
+    if results_pda != *results_account.key {
+        msg!("Invalid seeds for PDA");
+        return Err(ProgramError::InvalidArgument);
+    }
+
+    // Create results PDA
+    let account_len = PollResults::get_account_size(party_results.len());
+    let seeds = &[
+        b"results",
+        poll_account.key.as_ref(),
+        &[results_bump_seed],
+    ];
+
+    create_program_account(
+        program_id,
+        initializer,
+        results_account,
+        system_program_account,
+        account_len,
+        seeds,
+    )?;
+
+    msg!("PDA creation: {}", results_pda);
+
+    // Freeze final tally
+    let results_state = PollResults {
+        discriminator: PollResults::DISCRIMINATOR,
+        version: PollResults::VERSION,
+        poll_id: *poll_account.key,
+        finalized_at: clock.unix_timestamp,
+        rent_payer: *initializer.key,
+        parties: party_results,
+    };
+    results_state.serialize(&mut &mut results_account.data.borrow_mut()[..])?;
+    msg!("Results state serialized");
+
+    poll_state.phase = VotingPhase::Results;
+    poll_state.ended_at = clock.unix_timestamp;
+    save_poll(&poll_state, poll_account, initializer, Some(system_program_account))?;
+    msg!("Poll state updated");
+
+    Ok(())
+}
+
+
+pub fn migrate_account(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+) -> ProgramResult {
+    msg!("Migrating account...");
+
+    if accounts.len() < 3 {
+        msg!("Insufficient accounts provided");
+        return Err(ProgramError::NotEnoughAccountKeys);
+    }
+
+    let account_info_iter = &mut accounts.iter();
+
+    let initializer = next_account_info(account_info_iter)?;
+    let account = next_account_info(account_info_iter)?;
+    let system_program_account = next_account_info(account_info_iter)?;
+
+    if !initializer.is_signer {
+        msg!("Missing required signature");
+        return Err(ProgramError::MissingRequiredSignature);
+    }
+
+    if account.owner != program_id {
+        msg!("Account not owned by program");
+        return Err(ProgramError::IncorrectProgramId);
+    }
+
+    // Validate account is initialized
+    if account.data.borrow().iter().all(|&b| b == 0) {
+        msg!("Account not initialized");
+        return Err(ProgramError::UninitializedAccount);
+    }
+
+    // Parties of the first release did not record a rent payer, the owner of their poll gets the rent back
+    let party_rent_payer = if zero_copy_version::<PartyAccount>(&account.data.borrow()) == Some(0) {
+        let poll_account = next_account_info(account_info_iter).map_err(|_| {
+            msg!("Missing poll account of the party");
+            ProgramError::NotEnoughAccountKeys
+        })?;
+        let party_state = load_zero_copy::<PartyAccount>(program_id, account)?;
+        check_party_poll(poll_account, account, &party_state)?;
+
+        load_account::<PollState>(program_id, poll_account)?.owner
+    } else {
+        *initializer.key
+    };
+
+    // Polls and parties keep room for the longest texts so they can be edited in place,
+    // voters of the first release always had the default rules
+    let migrated = migrate_layout::<PollState>(initializer, account, system_program_account, PollState::SIZE)?
+        || migrate_zero_copy::<PartyAccount>(initializer, account, system_program_account, PartyAccount::SIZE, &party_rent_payer)?
+        || migrate_zero_copy::<VoterAccount>(initializer, account, system_program_account, VoterAccount::get_account_size(&VoteRules::default()), initializer.key)?
+        || migrate_layout::<Proposal>(initializer, account, system_program_account, Proposal::SIZE)?
+        || migrate_layout::<ProgramConfig>(initializer, account, system_program_account, 0)?
+        || migrate_layout::<CreatorPolls>(initializer, account, system_program_account, 0)?
+        || migrate_layout::<RegistryEntry>(initializer, account, system_program_account, 0)?
+        || migrate_layout::<EligibleVoter>(initializer, account, system_program_account, 0)?
+        || migrate_layout::<PollResults>(initializer, account, system_program_account, 0)?;
+
+    if !migrated {
+        msg!("Account is not a known account type");
+        return Err(JanecekError::AccountTypeMismatch.into());
+    }
+
+    Ok(())
+}
+
+
+pub fn retract_vote(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+) -> ProgramResult {
+    msg!("Retracting vote...");
+
+    if accounts.len() < 5 {
+        msg!("Insufficient accounts provided");
+        return Err(ProgramError::NotEnoughAccountKeys);
+    }
+
+    let account_info_iter = &mut accounts.iter();
+
+    let initializer = next_account_info(account_info_iter)?;
+    let poll_account = next_account_info(account_info_iter)?;
+    let config_account = next_account_info(account_info_iter)?;
+    let party_account = next_account_info(account_info_iter)?;
+    let voter_account = next_account_info(account_info_iter)?;
+
+    if !initializer.is_signer {
+        msg!("Missing required signature");
+        return Err(ProgramError::MissingRequiredSignature);
+    }
+
+    let poll_state = load_account::<PollState>(program_id, poll_account)?;
+
+    let config = check_not_paused(program_id, config_account, &poll_state)?;
+
+    // Votes can only be retracted while voting is open
+    if !matches!(poll_state.phase, VotingPhase::Voting) {
+        msg!("User can retract vote only during voting phase");
+        return Err(ProgramError::InvalidAccountData);
+    }
+
+    let clock = Clock::get()?;
+    if clock.unix_timestamp > poll_state.voting_ends_at(&config) {
+        msg!("Voting period is finished");
+        return Err(JanecekError::VotingPeriodFinished.into());
+    }
+
+    let mut party_data = load_party_mut(program_id, poll_account, party_account, &poll_state.owner)?;
+    let party_state = PartyAccount::from_bytes_mut(&mut party_data);
+    let mut voter_data = load_voter_mut(program_id, poll_account, initializer, voter_account)?;
+    let (voter_state, voted_parties) = VoterAccount::unpack_mut(&mut voter_data);
+
+    let vote_index = voter_state.find_vote(voted_parties, party_account.key).ok_or_else(|| {
+        msg!("Voter has not voted for this party");
+        JanecekError::VoteNotFound
+    })?;
+    let retracted = voter_state.remove_vote(voted_parties, vote_index);
+
+    match retracted.vote_type() {
+        VoteType::Positive => {
+            // Remaining negative votes must stay covered by positive votes
+            let positive_left = voter_state.positive_used - 1;
+            if (positive_left as u16) < poll_state.vote_rules.positives_required_for(voter_state.negative_used) {
+                msg!("Negative votes must be retracted before positive ones");
+                return Err(JanecekError::MustRetractNegativeFirst.into());
+            }
+            voter_state.positive_used = positive_left;
+            party_state.positive_votes = party_state.positive_votes.get()
+                .checked_sub(voter_state.vote_weight.get())
+                .ok_or(ProgramError::InvalidAccountData)?
+                .into();
+        },
+        VoteType::Negative => {
+            voter_state.negative_used -= 1;
+            party_state.negative_votes = party_state.negative_votes.get()
+                .checked_sub(voter_state.vote_weight.get())
+                .ok_or(ProgramError::InvalidAccountData)?
+                .into();
+        },
+    }
+    msg!("Voter and party state updated");
+
+    Ok(())
+}
+
+
+pub fn register_voter(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    voter: Pubkey,
+) -> ProgramResult {
+    msg!("Registering voter...");
+    msg!("Voter: {}", voter);
+
+    if accounts.len() < 5 {
+        msg!("Insufficient accounts provided");
+        return Err(ProgramError::NotEnoughAccountKeys);
+    }
+
+    let account_info_iter = &mut accounts.iter();
+
+    let initializer = next_account_info(account_info_iter)?;
+    let poll_account = next_account_info(account_info_iter)?;
+    let config_account = next_account_info(account_info_iter)?;
+    let eligible_voter_account = next_account_info(account_info_iter)?;
+    let system_program_account = next_account_info(account_info_iter)?;
+    let proposal_account = next_account_info(account_info_iter).ok();
+
+    if !initializer.is_signer {
+        msg!("Missing required signature");
+        return Err(ProgramError::MissingRequiredSignature);
+    }
+
+    let mut poll_state = load_account::<PollState>(program_id, poll_account)?;
+
+    check_not_paused(program_id, config_account, &poll_state)?;
+
+    // Only the current owner or an approved committee proposal can register voters
+    authorize_owner_action(program_id, poll_account, &poll_state, initializer, proposal_account, ProposalAction::RegisterVoter { voter })?;
+
+    // Only allow registering voters during registration phase
+    if !matches!(poll_state.phase, VotingPhase::Registration) {
+        msg!("Can only register voters during registration phase");
+        return Err(ProgramError::InvalidAccountData);
+    }
+
+    // Anyone can vote in an open poll
+    if !poll_state.closed {
+        msg!("Poll is open to every voter");
+        return Err(JanecekError::PollNotClosed.into());
+    }
+
+    // Validate eligibility PDA
+    let (eligible_pda, eligible_bump_seed) = Pubkey::find_program_address(
+        &[b"eligible", poll_account.key.as_ref(), voter.as_ref()],
+        program_id,
+    );
+    if eligible_pda != *eligible_voter_account.key {
+        msg!("Invalid seeds for PDA");
+        return Err(ProgramError::InvalidArgument);
+    }
+
+    // Create eligibility PDA, fails if the voter is already registered
+    let account_len = EligibleVoter::get_account_size();
+    let seeds = &[
+        b"eligible",
+        poll_account.key.as_ref(),
+        voter.as_ref(),
+        &[eligible_bump_seed],
+    ];
+
+    create_program_account(
+        program_id,
+        initializer,
+        eligible_voter_account,
+        system_program_account,
+        account_len,
+        seeds,
+    )?;
+
+    msg!("PDA creation: {}", eligible_pda);
+
+    let eligible_state = EligibleVoter {
+        discriminator: EligibleVoter::DISCRIMINATOR,
+        version: EligibleVoter::VERSION,
+        poll_key: *poll_account.key,
+        voter_key: voter,
+        rent_payer: *initializer.key,
+        bump: eligible_bump_seed,
+    };
+    eligible_state.serialize(&mut &mut eligible_voter_account.data.borrow_mut()[..])?;
+    msg!("Eligible voter serialized");
+
+    poll_state.eligible_voters += 1;
+    save_poll(&poll_state, poll_account, initializer, Some(system_program_account))?;
+    msg!("Poll state updated");
+
+    Ok(())
+}
+
+
+pub fn unregister_voter(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    voter: Pubkey,
+) -> ProgramResult {
+    msg!("Unregistering voter...");
+    msg!("Voter: {}", voter);
+
+    if accounts.len() < 5 {
+        msg!("Insufficient accounts provided");
+        return Err(ProgramError::NotEnoughAccountKeys);
+    }
+
+    let account_info_iter = &mut accounts.iter();
+
+    let initializer = next_account_info(account_info_iter)?;
+    let poll_account = next_account_info(account_info_iter)?;
+    let config_account = next_account_info(account_info_iter)?;
+    let eligible_voter_account = next_account_info(account_info_iter)?;
+    let rent_payer_account = next_account_info(account_info_iter)?;
+    let proposal_account = next_account_info(account_info_iter).ok();
+
+    if !initializer.is_signer {
+        msg!("Missing required signature");
+        return Err(ProgramError::MissingRequiredSignature);
+    }
+
+    let mut poll_state = load_account::<PollState>(program_id, poll_account)?;
+
+    check_not_paused(program_id, config_account, &poll_state)?;
+
+    // Only the current owner or an approved committee proposal can unregister voters
+    authorize_owner_action(program_id, poll_account, &poll_state, initializer, proposal_account, ProposalAction::UnregisterVoter { voter })?;
+
+    // Voter list is frozen once voting starts
+    if !matches!(poll_state.phase, VotingPhase::Registration) {
+        msg!("Can only unregister voters during registration phase");
+        return Err(ProgramError::InvalidAccountData);
+    }
+
+    // Validate eligibility PDA
+    let (eligible_pda, _eligible_bump_seed) = Pubkey::find_program_address(
+        &[b"eligible", poll_account.key.as_ref(), voter.as_ref()],
+        program_id,
+    );
+    if eligible_pda != *eligible_voter_account.key {
+        msg!("Invalid seeds for PDA");
+        return Err(ProgramError::InvalidArgument);
+    }
+
+    let eligible_state = load_eligible_voter(program_id, poll_account, eligible_voter_account)?;
+    check_rent_payer(eligible_voter_account, &eligible_state.rent_payer, rent_payer_account)?;
+
+    close_account(eligible_voter_account, rent_payer_account)?;
+    msg!("Eligible voter closed");
+
+    poll_state.eligible_voters -= 1;
+    save_poll(&poll_state, poll_account, initializer, None)?;
+    msg!("Poll state updated");
+
+    Ok(())
+}
+
+
+pub fn unlock_tokens(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+) -> ProgramResult {
+    msg!("Unlocking tokens...");
+
+    if accounts.len() < 7 {
+        msg!("Insufficient accounts provided");
+        return Err(ProgramError::NotEnoughAccountKeys);
+    }
+
+    let account_info_iter = &mut accounts.iter();
+
+    let initializer = next_account_info(account_info_iter)?;
+    let poll_account = next_account_info(account_info_iter)?;
+    let config_account = next_account_info(account_info_iter)?;
+    let voter_account = next_account_info(account_info_iter)?;
+    let token_account = next_account_info(account_info_iter)?;
+    let vault_account = next_account_info(account_info_iter)?;
+    let token_program_account = next_account_info(account_info_iter)?;
+
+    if !initializer.is_signer {
+        msg!("Missing required signature");
+        return Err(ProgramError::MissingRequiredSignature);
+    }
+
+    check_program_not_paused(program_id, config_account)?;
+
+    let poll_state = load_account::<PollState>(program_id, poll_account)?;
+
+    // Tokens stay locked until the poll is finalized
+    if !matches!(poll_state.phase, VotingPhase::Results) {
+        msg!("Tokens can only be unlocked after voting has ended");
+        return Err(ProgramError::InvalidAccountData);
+    }
+
+    let token_gate = poll_state.token_gate.ok_or(JanecekError::NoLockedTokens)?;
+
+    let locked_tokens = load_voter(program_id, poll_account, initializer, voter_account)?.locked_tokens.get();
+
+    if locked_tokens == 0 {
+        msg!("Voter has no locked tokens");
+        return Err(JanecekError::NoLockedTokens.into());
+    }
+
+    // Validate vault PDA
+    let (vault_pda, vault_bump_seed) = Pubkey::find_program_address(
+        &[b"vault", poll_account.key.as_ref()],
+        program_id,
+    );
+    if vault_pda != *vault_account.key {
+        msg!("Invalid seeds for PDA");
+        return Err(ProgramError::InvalidArgument);
+    }
+
+    if *token_program_account.key != spl_token::id() || token_account.owner != &spl_token::id() {
+        msg!("Invalid token program");
+        return Err(ProgramError::IncorrectProgramId);
+    }
+
+    // Tokens can only go back to the voter's own account for the poll mint
+    let token_state = spl_token::state::Account::unpack(&token_account.data.borrow())?;
+    if token_state.owner != *initializer.key || token_state.mint != token_gate.mint {
+        msg!("Token account does not belong to voter or poll mint");
+        return Err(ProgramError::InvalidArgument);
+    }
+
+    let seeds = &[
+        b"vault",
+        poll_account.key.as_ref(),
+        &[vault_bump_seed],
+    ];
+
+    invoke_signed(
+        &spl_token::instruction::transfer(
+            token_program_account.key,
+            vault_account.key,
+            token_account.key,
+            vault_account.key,
+            &[],
+            locked_tokens,
+        )?,
+        &[
+            vault_account.clone(),
+            token_account.clone(),
+            token_program_account.clone(),
+        ],
+        &[seeds],
+    )?;
+
+    msg!("Unlocked {} tokens", locked_tokens);
+
+    let mut voter_data = load_voter_mut(program_id, poll_account, initializer, voter_account)?;
+    VoterAccount::unpack_mut(&mut voter_data).0.locked_tokens = 0.into();
+    msg!("Voter state updated");
+
+    Ok(())
+}
+
+
+pub fn close_poll(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+) -> ProgramResult {
+    msg!("Closing poll...");
+
+    if accounts.len() < 6 {
+        msg!("Insufficient accounts provided");
+        return Err(ProgramError::NotEnoughAccountKeys);
+    }
+
+    let account_info_iter = &mut accounts.iter();
+
+    let initializer = next_account_info(account_info_iter)?;
+    let poll_account = next_account_info(account_info_iter)?;
+    let config_account = next_account_info(account_info_iter)?;
+    let rent_payer_account = next_account_info(account_info_iter)?;
+    let results_account = next_account_info(account_info_iter)?;
+    let results_rent_payer_account = next_account_info(account_info_iter)?;
+
+    if !initializer.is_signer {
+        msg!("Missing required signature");
+        return Err(ProgramError::MissingRequiredSignature);
+    }
+
+    check_program_not_paused(program_id, config_account)?;
+
+    let poll_state = load_account::<PollState>(program_id, poll_account)?;
+
+    // Only the current owner can close
+    if poll_state.owner != *initializer.key {
+        msg!("Only the current owner can close the poll");
+        return Err(ProgramError::IllegalOwner);
+    }
+
+    check_archive_finished(&poll_state)?;
+
+    // Parties point at the poll, so they have to go first
+    if poll_state.party_counter > 0 {
+        msg!("{} parties are still open", poll_state.party_counter);
+        return Err(JanecekError::PartiesNotClosed.into());
+    }
+
+    // Same for eligibility accounts, see `CloseEligibleVoter`
+    if poll_state.eligible_voters > 0 {
+        msg!("{} registered voters are still open", poll_state.eligible_voters);
+        return Err(JanecekError::EligibleVotersNotClosed.into());
+    }
+
+    check_rent_payer(poll_account, &poll_state.rent_payer, rent_payer_account)?;
+
+    // Validate results PDA, polls that ended before results were frozen have none
+    let (results_pda, _results_bump_seed) = Pubkey::find_program_address(
+        &[b"results", poll_account.key.as_ref()],
+        program_id,
+    );
+    if results_pda != *results_account.key {
+        msg!("Invalid seeds for PDA");
+        return Err(ProgramError::InvalidArgument);
+    }
+
+    if results_account.owner == program_id {
+        let results_state = load_account::<PollResults>(program_id, results_account)?;
+        check_rent_payer(results_account, &results_state.rent_payer, results_rent_payer_account)?;
+
+        close_account(results_account, results_rent_payer_account)?;
+        msg!("Results closed");
+    }
+
+    if matches!(poll_state.voting_mode, VotingMode::TokenWeighted) {
+        let vault_account = next_account_info(account_info_iter)?;
+
+        let (vault_pda, vault_bump_seed) = Pubkey::find_program_address(
+            &[b"vault", poll_account.key.as_ref()],
+            program_id,
+        );
+        if vault_pda != *vault_account.key {
+            msg!("Invalid seeds for PDA");
+            return Err(ProgramError::InvalidArgument);
+        }
+
+        // Vault only exists once someone voted
+        if vault_account.owner == &spl_token::id() {
+            let vault_rent_payer_account = next_account_info(account_info_iter)?;
+            let token_program_account = next_account_info(account_info_iter)?;
+
+            if *token_program_account.key != spl_token::id() {
+                msg!("Invalid token program");
+                return Err(ProgramError::IncorrectProgramId);
+            }
+
+            // Do not strand tokens voters have not unlocked yet
+            let vault_state = spl_token::state::Account::unpack(&vault_account.data.borrow())?;
+            if vault_state.amount > 0 {
+                msg!("Vault still holds {} tokens", vault_state.amount);
+                return Err(JanecekError::TokensStillLocked.into());
+            }
+
+            // Vaults created before their payer was recorded refund the poll rent payer
+            let vault_rent_payer = if poll_state.vault_rent_payer == Pubkey::default() {
+                poll_state.rent_payer
+            } else {
+                poll_state.vault_rent_payer
+            };
+            check_rent_payer(vault_account, &vault_rent_payer, vault_rent_payer_account)?;
+
+            let seeds = &[
+                b"vault",
+                poll_account.key.as_ref(),
+                &[vault_bump_seed],
+            ];
+
+            invoke_signed(
+                &spl_token::instruction::close_account(
+                    token_program_account.key,
+                    vault_account.key,
+                    vault_rent_payer_account.key,
+                    vault_account.key,
+                    &[],
+                )?,
+                &[
+                    vault_account.clone(),
+                    vault_rent_payer_account.clone(),
+                    token_program_account.clone(),
+                ],
+                &[seeds],
+            )?;
+            msg!("Vault closed");
+        }
+    }
+
+    close_account(poll_account, rent_payer_account)?;
+    msg!("Poll closed");
+
+    Ok(())
+}
+
+
+pub fn close_party(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+) -> ProgramResult {
+    msg!("Closing party...");
+
+    if accounts.len() < 5 {
+        msg!("Insufficient accounts provided");
+        return Err(ProgramError::NotEnoughAccountKeys);
+    }
+
+    let account_info_iter = &mut accounts.iter();
+
+    let initializer = next_account_info(account_info_iter)?;
+    let poll_account = next_account_info(account_info_iter)?;
+    let config_account = next_account_info(account_info_iter)?;
+    let party_account = next_account_info(account_info_iter)?;
+    let rent_payer_account = next_account_info(account_info_iter)?;
+
+    if !initializer.is_signer {
+        msg!("Missing required signature");
+        return Err(ProgramError::MissingRequiredSignature);
+    }
+
+    check_program_not_paused(program_id, config_account)?;
+
+    let mut poll_state = load_account::<PollState>(program_id, poll_account)?;
+
+    // Only the current owner can close
+    if poll_state.owner != *initializer.key {
+        msg!("Only the current owner can close parties");
+        return Err(ProgramError::IllegalOwner);
+    }
+
+    check_archive_finished(&poll_state)?;
+
+    let party_state = load_party(program_id, poll_account, party_account)?;
+
+    check_rent_payer(party_account, &party_state.rent_payer, rent_payer_account)?;
+
+    close_account(party_account, rent_payer_account)?;
+    msg!("Party closed");
+
+    poll_state.party_counter -= 1;
+    save_poll(&poll_state, poll_account, initializer, None)?;
+    msg!("Poll state updated");
+
+    Ok(())
+}
+
+
+pub fn close_voter(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+) -> ProgramResult {
+    msg!("Closing voter...");
+
+    if accounts.len() < 4 {
+        msg!("Insufficient accounts provided");
+        return Err(ProgramError::NotEnoughAccountKeys);
+    }
+
+    let account_info_iter = &mut accounts.iter();
+
+    let initializer = next_account_info(account_info_iter)?;
+    let poll_account = next_account_info(account_info_iter)?;
+    let config_account = next_account_info(account_info_iter)?;
+    let voter_account = next_account_info(account_info_iter)?;
+
+    if !initializer.is_signer {
+        msg!("Missing required signature");
+        return Err(ProgramError::MissingRequiredSignature);
+    }
+
+    check_program_not_paused(program_id, config_account)?;
+
+    // The voter is always the one who paid for the account
+    let voter_state = load_voter(program_id, poll_account, initializer, voter_account)?;
+
+    // A poll that was already closed no longer holds anything back
+    let poll_open = poll_account.owner == program_id
+        && !poll_account.data.borrow().iter().all(|&b| b == 0);
+
+    if poll_open {
+        let poll_state = load_account::<PollState>(program_id, poll_account)?;
+        check_archive_finished(&poll_state)?;
+    }
+
+    // Closing would lose track of tokens still held in the vault
+    if voter_state.locked_tokens.get() > 0 {
+        msg!("Unlock {} tokens before closing", voter_state.locked_tokens.get());
+        return Err(JanecekError::TokensStillLocked.into());
+    }
+
+    close_account(voter_account, initializer)?;
+    msg!("Voter closed");
+
+    Ok(())
+}
+
+
+pub fn close_eligible_voter(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+) -> ProgramResult {
+    msg!("Closing eligible voter...");
+
+    if accounts.len() < 5 {
+        msg!("Insufficient accounts provided");
+        return Err(ProgramError::NotEnoughAccountKeys);
+    }
+
+    let account_info_iter = &mut accounts.iter();
+
+    let initializer = next_account_info(account_info_iter)?;
+    let poll_account = next_account_info(account_info_iter)?;
+    let config_account = next_account_info(account_info_iter)?;
+    let eligible_voter_account = next_account_info(account_info_iter)?;
+    let rent_payer_account = next_account_info(account_info_iter)?;
+
+    if !initializer.is_signer {
+        msg!("Missing required signature");
+        return Err(ProgramError::MissingRequiredSignature);
+    }
+
+    check_program_not_paused(program_id, config_account)?;
+
+    let mut poll_state = load_account::<PollState>(program_id, poll_account)?;
+
+    // Only the current owner can close
+    if poll_state.owner != *initializer.key {
+        msg!("Only the current owner can close registered voters");
+        return Err(ProgramError::IllegalOwner);
+    }
+
+    check_archive_finished(&poll_state)?;
+
+    let eligible_state = load_eligible_voter(program_id, poll_account, eligible_voter_account)?;
+    check_rent_payer(eligible_voter_account, &eligible_state.rent_payer, rent_payer_account)?;
+
+    close_account(eligible_voter_account, rent_payer_account)?;
+    msg!("Eligible voter closed");
+
+    poll_state.eligible_voters -= 1;
+    save_poll(&poll_state, poll_account, initializer, None)?;
+    msg!("Poll state updated");
+
+    Ok(())
+}
+
+
+pub fn close_proposal(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+) -> ProgramResult {
+    msg!("Closing proposal...");
+
+    if accounts.len() < 4 {
+        msg!("Insufficient accounts provided");
+        return Err(ProgramError::NotEnoughAccountKeys);
+    }
+
+    let account_info_iter = &mut accounts.iter();
+
+    let initializer = next_account_info(account_info_iter)?;
+    let poll_account = next_account_info(account_info_iter)?;
+    let config_account = next_account_info(account_info_iter)?;
+    let proposal_account = next_account_info(account_info_iter)?;
+
+    if !initializer.is_signer {
+        msg!("Missing required signature");
+        return Err(ProgramError::MissingRequiredSignature);
+    }
+
+    check_program_not_paused(program_id, config_account)?;
+
+    let proposal = load_account::<Proposal>(program_id, proposal_account)?;
+
+    if proposal.poll_id != *poll_account.key {
+        msg!("Proposal does not belong to this poll");
+        return Err(JanecekError::ProposalMismatch.into());
+    }
+
+    // The proposer is always the one who paid for the account
+    if proposal.proposer != *initializer.key {
+        msg!("Only the proposer can close the proposal");
+        return Err(ProgramError::IllegalOwner);
+    }
+
+    // Executed proposals can not run again, open ones wait for the archive or the closed poll
+    let poll_open = poll_account.owner == program_id
+        && !poll_account.data.borrow().iter().all(|&b| b == 0);
+
+    if poll_open && !proposal.executed {
+        let poll_state = load_account::<PollState>(program_id, poll_account)?;
+        check_archive_finished(&poll_state)?;
+    }
+
+    close_account(proposal_account, initializer)?;
+    msg!("Proposal closed");
+
+    Ok(())
+}
+
+
+pub fn remove_party(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+) -> ProgramResult {
+    msg!("Removing party...");
+
+    if accounts.len() < 5 {
+        msg!("Insufficient accounts provided");
+        return Err(ProgramError::NotEnoughAccountKeys);
+    }
+
+    let account_info_iter = &mut accounts.iter();
+
+    let initializer = next_account_info(account_info_iter)?;
+    let poll_account = next_account_info(account_info_iter)?;
+    let config_account = next_account_info(account_info_iter)?;
+    let party_account = next_account_info(account_info_iter)?;
+    let rent_payer_account = next_account_info(account_info_iter)?;
+    let proposal_account = next_account_info(account_info_iter).ok();
+
+    let action = ProposalAction::RemoveParty { party: *party_account.key };
+    let (mut poll_state, party_state) = load_party_for_owner(program_id, initializer, poll_account, party_account, proposal_account, action)?;
+
+    check_not_paused(program_id, config_account, &poll_state)?;
+
+    check_rent_payer(party_account, &party_state.rent_payer, rent_payer_account)?;
+
+    close_account(party_account, rent_payer_account)?;
+    msg!("Party removed: {}", party_state.title());
+
+    poll_state.party_counter -= 1;
+    save_poll(&poll_state, poll_account, initializer, None)?;
+    msg!("Poll state updated");
+
+    Ok(())
+}
+
+
+pub fn update_party_metadata(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    description: String,
+    metadata_uri: String,
+    metadata_hash: [u8; 32],
+) -> ProgramResult {
+    msg!("Updating party metadata...");
+    msg!("Metadata URI: {}", metadata_uri);
+
+    if accounts.len() < 4 {
+        msg!("Insufficient accounts provided");
+        return Err(ProgramError::NotEnoughAccountKeys);
+    }
+
+    let account_info_iter = &mut accounts.iter();
+
+    let initializer = next_account_info(account_info_iter)?;
+    let poll_account = next_account_info(account_info_iter)?;
+    let config_account = next_account_info(account_info_iter)?;
+    let party_account = next_account_info(account_info_iter)?;
+    let proposal_account = next_account_info(account_info_iter).ok();
+
+    let action = ProposalAction::UpdatePartyMetadata {
+        party: *party_account.key,
+        description: description.clone(),
+        metadata_uri: metadata_uri.clone(),
+        metadata_hash,
+    };
+    let (poll_state, mut party_state) = load_party_for_owner(program_id, initializer, poll_account, party_account, proposal_account, action)?;
+
+    check_not_paused(program_id, config_account, &poll_state)?;
+
+    // Title stays, the party PDA is seeded by its hash
+    let title = party_state.title().to_string();
+    check_party_metadata(&title, &description, &metadata_uri)?;
+
+    party_state.set_metadata(&title, &description, &metadata_uri, metadata_hash)?;
+
+    // Party accounts are sized for the longest texts, no realloc needed
+    store_zero_copy(&party_state, &mut party_account.data.borrow_mut())?;
+    msg!("Party state updated");
+
+    Ok(())
+}
+
+
+pub fn update_poll(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    title: String,
+    description: String,
+) -> ProgramResult {
+    msg!("Updating poll...");
+    msg!("Title: {}", title);
+    msg!("Description: {}", description);
+
+    if accounts.len() < 4 {
+        msg!("Insufficient accounts provided");
+        return Err(ProgramError::NotEnoughAccountKeys);
+    }
+
+    let account_info_iter = &mut accounts.iter();
+
+    let initializer = next_account_info(account_info_iter)?;
+    let poll_account = next_account_info(account_info_iter)?;
+    let config_account = next_account_info(account_info_iter)?;
+    let system_program_account = next_account_info(account_info_iter)?;
+    let proposal_account = next_account_info(account_info_iter).ok();
+
+    if !initializer.is_signer {
+        msg!("Missing required signature");
+        return Err(ProgramError::MissingRequiredSignature);
+    }
+
+    let mut poll_state = load_account::<PollState>(program_id, poll_account)?;
+
+    check_not_paused(program_id, config_account, &poll_state)?;
+
+    // Only the current owner or an approved committee proposal can edit
+    let action = ProposalAction::UpdatePoll { title: title.clone(), description: description.clone() };
+    authorize_owner_action(program_id, poll_account, &poll_state, initializer, proposal_account, action)?;
+
+    // Texts are fixed once voting starts
+    if !matches!(poll_state.phase, VotingPhase::Registration) {
+        msg!("Can only update poll during registration phase");
+        return Err(ProgramError::InvalidAccountData);
+    }
+
+    check_poll_texts(&title, &description)?;
+
+    poll_state.title = title;
+    poll_state.description = description;
+
+    // Texts have fixed room so the poll never grows here, only one migrated with over-long texts
+    // shrinks. The excess rent is the rent payer's, for other signers it stays in the poll until it is closed
+    let account_len = poll_state.get_account_size();
+    if poll_account.data_len() > account_len {
+        if *initializer.key == poll_state.rent_payer {
+            resize_account(poll_account, initializer, system_program_account, account_len)?;
+        } else {
+            poll_account.realloc(account_len, false)?;
+        }
+    }
+
+    save_poll(&poll_state, poll_account, initializer, Some(system_program_account))?;
+    msg!("Poll state updated");
+
+    Ok(())
+}
+
+
+// Reads the upgrade authority from the ProgramData account of the upgradeable loader
+fn upgrade_authority(program_id: &Pubkey, program_data_account: &AccountInfo) -> Result<Option<Pubkey>, ProgramError> {
+    let (program_data_address, _) = Pubkey::find_program_address(&[program_id.as_ref()], &bpf_loader_upgradeable::id());
+    if program_data_address != *program_data_account.key || *program_data_account.owner != bpf_loader_upgradeable::id() {
+        msg!("Invalid program data account");
+        return Err(ProgramError::InvalidArgument);
+    }
+
+    let data = program_data_account.data.borrow();
+    let metadata_size = UpgradeableLoaderState::size_of_programdata_metadata();
+    if data.len() < metadata_size {
+        msg!("Invalid program data account");
+        return Err(ProgramError::InvalidAccountData);
+    }
+
+    match limited_deserialize(&data[..metadata_size], metadata_size as u64) {
+        Ok(UpgradeableLoaderState::ProgramData { upgrade_authority_address, .. }) => Ok(upgrade_authority_address),
+        _ => {
+            msg!("Invalid program data account");
+            Err(ProgramError::InvalidAccountData)
+        }
+    }
+}
+
+pub fn initialize_config(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    defaults: PollDefaults,
+    creation_fee: u64,
+) -> ProgramResult {
+    msg!("Initializing program config...");
+    msg!("Defaults: {:?}", defaults);
+    msg!("Creation fee: {} lamports", creation_fee);
+
+    if accounts.len() < 4 {
+        msg!("Insufficient accounts provided");
+        return Err(ProgramError::NotEnoughAccountKeys);
+    }
+
+    let account_info_iter = &mut accounts.iter();
+
+    let initializer = next_account_info(account_info_iter)?;
+    let config_account = next_account_info(account_info_iter)?;
+    let system_program_account = next_account_info(account_info_iter)?;
+    let program_data_account = next_account_info(account_info_iter)?;
+
+    if !initializer.is_signer {
+        msg!("Missing required signature");
+        return Err(ProgramError::MissingRequiredSignature);
+    }
+
+    // Whoever deployed the program sets it up, otherwise anyone watching the deploy could grab the admin.
+    // Nobody can once the program is immutable, so the config has to exist before the authority is dropped
+    match upgrade_authority(program_id, program_data_account)? {
+        Some(authority) if authority == *initializer.key => {}
+        Some(_) => {
+            msg!("Only the program upgrade authority can initialize the config");
+            return Err(ProgramError::IllegalOwner);
+        }
+        None => {
+            msg!("Program has no upgrade authority, initialize the config before removing it");
+            return Err(JanecekError::ProgramImmutable.into());
+        }
+    }
+
+    let (config_pda, config_bump_seed) = Pubkey::find_program_address(&[b"config"], program_id);
+    if config_pda != *config_account.key {
+        msg!("Invalid seeds for PDA");
+        return Err(ProgramError::InvalidArgument);
+    }
+
+    // Config is a singleton, the upgrade authority becomes the admin
+    if !config_account.data_is_empty() {
+        msg!("Program config already initialized");
+        return Err(ProgramError::AccountAlreadyInitialized);
+    }
+
+    check_poll_defaults(&defaults)?;
+
+    create_program_account(
+        program_id,
+        initializer,
+        config_account,
+        system_program_account,
+        ProgramConfig::get_account_size(),
+        &[b"config", &[config_bump_seed]],
+    )?;
+    msg!("PDA creation: {}", config_pda);
+
+    let config = ProgramConfig {
+        discriminator: ProgramConfig::DISCRIMINATOR,
+        version: ProgramConfig::VERSION,
+        admin: *initializer.key,
+        defaults,
+        creation_fee,
+        poll_count: 0,
+        paused: false,
+        paused_at: 0,
+        paused_duration: 0,
+        bump: config_bump_seed,
+    };
+    config.serialize(&mut &mut config_account.data.borrow_mut()[..])?;
+    msg!("Program config initialized");
+
+    Ok(())
+}
+
+
+pub fn update_config(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    admin: Pubkey,
+    defaults: PollDefaults,
+    creation_fee: u64,
+) -> ProgramResult {
+    msg!("Updating program config...");
+    msg!("Admin: {}", admin);
+    msg!("Defaults: {:?}", defaults);
+    msg!("Creation fee: {} lamports", creation_fee);
+
+    if accounts.len() < 2 {
+        msg!("Insufficient accounts provided");
+        return Err(ProgramError::NotEnoughAccountKeys);
+    }
+
+    let account_info_iter = &mut accounts.iter();
+
+    let initializer = next_account_info(account_info_iter)?;
+    let config_account = next_account_info(account_info_iter)?;
+
+    if !initializer.is_signer {
+        msg!("Missing required signature");
+        return Err(ProgramError::MissingRequiredSignature);
+    }
+
+    let mut config = load_program_config(program_id, config_account)?;
+
+    // Only the admin can change the settings
+    if config.admin != *initializer.key {
+        msg!("Only the admin can update the program config");
+        return Err(ProgramError::IllegalOwner);
+    }
+
+    // Handing the config to nobody would lock it forever
+    if admin == Pubkey::default() {
+        msg!("Admin cannot be the default pubkey");
+        return Err(ProgramError::InvalidArgument);
+    }
+
+    check_poll_defaults(&defaults)?;
+
+    config.admin = admin;
+    config.defaults = defaults;
+    config.creation_fee = creation_fee;
+    config.serialize(&mut &mut config_account.data.borrow_mut()[..])?;
+    msg!("Program config updated");
+
+    Ok(())
+}
+
+
+pub fn set_poll_paused(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    paused: bool,
+    extend_deadline: bool,
+) -> ProgramResult {
+    msg!("Setting poll paused: {}", paused);
+
+    if accounts.len() < 3 {
+        msg!("Insufficient accounts provided");
+        return Err(ProgramError::NotEnoughAccountKeys);
+    }
+
+    let account_info_iter = &mut accounts.iter();
+
+    let initializer = next_account_info(account_info_iter)?;
+    let poll_account = next_account_info(account_info_iter)?;
+    let config_account = next_account_info(account_info_iter)?;
+    let proposal_account = next_account_info(account_info_iter).ok();
+
+    if !initializer.is_signer {
+        msg!("Missing required signature");
+        return Err(ProgramError::MissingRequiredSignature);
+    }
+
+    let mut poll_state = load_account::<PollState>(program_id, poll_account)?;
+
+    // Only the current owner or an approved committee proposal can pause
+    let action = ProposalAction::SetPollPaused { paused, extend_deadline };
+    authorize_owner_action(program_id, poll_account, &poll_state, initializer, proposal_account, action)?;
+
+    // Nothing left to protect once the results are in
+    if matches!(poll_state.phase, VotingPhase::Results) {
+        msg!("Can not pause a finished poll");
+        return Err(ProgramError::InvalidAccountData);
+    }
+
+    if poll_state.paused == paused {
+        msg!("Poll is already in this state");
+        return Err(ProgramError::InvalidArgument);
+    }
+
+    let config = load_program_config(program_id, config_account)?;
+    let clock = Clock::get()?;
+
+    // A pause after the deadline would only hold back EndVoting
+    if paused && matches!(poll_state.phase, VotingPhase::Voting) && clock.unix_timestamp > poll_state.voting_ends_at(&config) {
+        msg!("Voting period is finished");
+        return Err(JanecekError::VotingPeriodFinished.into());
+    }
+
+    if paused {
+        poll_state.paused_at = clock.unix_timestamp;
+    } else {
+        // Only the part of the pause that fell into the voting period is given back
+        if extend_deadline && matches!(poll_state.phase, VotingPhase::Voting) {
+            let paused_from = poll_state.paused_at.max(poll_state.voting_start_at);
+            poll_state.paused_duration += clock.unix_timestamp - paused_from;
+            msg!("Voting deadline extended to {}", poll_state.voting_ends_at(&config));
+        }
+        poll_state.paused_at = 0;
+    }
+    poll_state.paused = paused;
+
+    save_poll(&poll_state, poll_account, initializer, None)?;
+    msg!("Poll state updated");
+
+    Ok(())
+}
+
+
+pub fn set_program_paused(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    paused: bool,
+) -> ProgramResult {
+    msg!("Setting program paused: {}", paused);
+
+    if accounts.len() < 2 {
+        msg!("Insufficient accounts provided");
+        return Err(ProgramError::NotEnoughAccountKeys);
+    }
+
+    let account_info_iter = &mut accounts.iter();
+
+    let initializer = next_account_info(account_info_iter)?;
+    let config_account = next_account_info(account_info_iter)?;
+
+    if !initializer.is_signer {
+        msg!("Missing required signature");
+        return Err(ProgramError::MissingRequiredSignature);
+    }
+
+    let mut config = load_program_config(program_id, config_account)?;
+
+    // Only the admin can pause the program
+    if config.admin != *initializer.key {
+        msg!("Only the admin can pause the program");
+        return Err(ProgramError::IllegalOwner);
+    }
+
+    if config.paused == paused {
+        msg!("Program is already in this state");
+        return Err(ProgramError::InvalidArgument);
+    }
+
+    // Polls in voting get the whole pause added to their deadline, see `PollState::voting_ends_at`
+    let clock = Clock::get()?;
+    if paused {
+        config.paused_at = clock.unix_timestamp;
+    } else {
+        config.paused_duration += clock.unix_timestamp - config.paused_at;
+        config.paused_at = 0;
+    }
+    config.paused = paused;
+    config.serialize(&mut &mut config_account.data.borrow_mut()[..])?;
+    msg!("Program config updated");
+
+    Ok(())
+}
+
+
+// ["config"] singleton, must be initialized before polls can be created
+fn load_program_config(program_id: &Pubkey, config_account: &AccountInfo) -> Result<ProgramConfig, ProgramError> {
+    let config = load_account::<ProgramConfig>(program_id, config_account)?;
+    check_pda(program_id, config_account, &[b"config"], config.bump)?;
+
+    Ok(config)
+}
+
+
+// Accounts store their canonical bump so the hot paths skip the search of `find_program_address`,
+// ones written before the bump was stored still search. Returns the bump
+fn check_pda(program_id: &Pubkey, account: &AccountInfo, seeds: &[&[u8]], bump: u8) -> Result<u8, ProgramError> {
+    let (pda, bump) = if bump == 0 {
+        let (pda, bump) = Pubkey::find_program_address(seeds, program_id);
+        (Some(pda), bump)
+    } else {
+        // A bump taken from another account can put the address on the curve, that is a mismatch as well
+        let bump_seed = [bump];
+        (Pubkey::create_program_address(&[seeds, &[&bump_seed[..]]].concat(), program_id).ok(), bump)
+    };
+
+    if pda != Some(*account.key) {
+        msg!("Invalid seeds for PDA");
+        return Err(ProgramError::InvalidArgument);
+    }
+
+    Ok(bump)
+}
+
+
+// Program pause alone, for handlers that move rent or tokens after the results where a poll can not be paused
+fn check_program_not_paused(program_id: &Pubkey, config_account: &AccountInfo) -> Result<ProgramConfig, ProgramError> {
+    let config = load_program_config(program_id, config_account)?;
+    if config.paused {
+        msg!("Program is paused");
+        return Err(JanecekError::ProgramPaused.into());
+    }
+
+    Ok(config)
+}
+
+
+// Circuit breaker, state-changing handlers fail while the program or the poll is paused.
+// Returns the config, its pause time extends the voting deadline
+fn check_not_paused(program_id: &Pubkey, config_account: &AccountInfo, poll_state: &PollState) -> Result<ProgramConfig, ProgramError> {
+    let config = check_program_not_paused(program_id, config_account)?;
+
+    if poll_state.paused {
+        msg!("Poll is paused");
+        return Err(JanecekError::PollPaused.into());
+    }
+
+    Ok(config)
+}
+
+
+// Owner check for the actions a committee can take over
+fn authorize_owner_action(
+    program_id: &Pubkey,
+    poll_account: &AccountInfo,
+    poll_state: &PollState,
+    initializer: &AccountInfo,
+    proposal_account: Option<&AccountInfo>,
+    action: ProposalAction,
+) -> ProgramResult {
+    if poll_state.committee.is_none() {
+        if poll_state.owner != *initializer.key {
+            msg!("Only the current owner can do this");
+            return Err(ProgramError::IllegalOwner);
+        }
+        return Ok(());
+    }
+
+    let proposal_account = proposal_account.ok_or_else(|| {
+        msg!("Committee polls need an approved proposal account");
+        ProgramError::NotEnoughAccountKeys
+    })?;
+    authorize_committee_action(program_id, poll_account, poll_state, initializer, proposal_account, action)
+}
+
+
+// Any member can execute a proposal once it reached the threshold, each proposal runs once
+fn authorize_committee_action(
+    program_id: &Pubkey,
+    poll_account: &AccountInfo,
+    poll_state: &PollState,
+    initializer: &AccountInfo,
+    proposal_account: &AccountInfo,
+    action: ProposalAction,
+) -> ProgramResult {
+    committee_member_index(poll_state, initializer)?;
+    let mut proposal = load_proposal(program_id, poll_account, proposal_account)?;
+
+    if proposal.action != action {
+        msg!("Proposal #{} is for {:?}", proposal.index, proposal.action);
+        return Err(JanecekError::ProposalMismatch.into());
+    }
+
+    let threshold = poll_state.committee.as_ref().map_or(0, |committee| committee.threshold);
+    if proposal.approval_count() < threshold as u32 {
+        msg!("Proposal #{} has {} of {} approvals", proposal.index, proposal.approval_count(), threshold);
+        return Err(JanecekError::ProposalNotApproved.into());
+    }
+
+    proposal.executed = true;
+    proposal.serialize(&mut &mut proposal_account.data.borrow_mut()[..])?;
+    msg!("Proposal #{} executed", proposal.index);
+
+    Ok(())
+}
+
+
+fn committee_member_index(poll_state: &PollState, initializer: &AccountInfo) -> Result<usize, ProgramError> {
+    let Some(committee) = &poll_state.committee else {
+        msg!("Poll has no committee");
+        return Err(JanecekError::InvalidCommittee.into());
+    };
+
+    committee.member_index(initializer.key).ok_or_else(|| {
+        msg!("{} is not a committee member", initializer.key);
+        JanecekError::NotCommitteeMember.into()
+    })
+}
+
+
+// Party account of the given poll
+fn load_party(program_id: &Pubkey, poll_account: &AccountInfo, party_account: &AccountInfo) -> Result<PartyAccount, ProgramError> {
+    let party_state = load_zero_copy::<PartyAccount>(program_id, party_account)?;
+    check_party_poll(poll_account, party_account, &party_state)?;
+
+    Ok(party_state)
+}
+
+
+// Same checks as `load_party`, the counters are then updated in place
+fn load_party_mut<'a>(
+    program_id: &Pubkey,
+    poll_account: &AccountInfo,
+    party_account: &'a AccountInfo,
+    poll_owner: &Pubkey,
+) -> Result<RefMut<'a, [u8]>, ProgramError> {
+    // Parties of the first release did not record a rent payer, the poll owner gets it back
+    let party_data = load_zero_copy_mut::<PartyAccount>(program_id, party_account, poll_owner)?;
+    check_party_poll(poll_account, party_account, PartyAccount::from_bytes(&party_data))?;
+
+    Ok(party_data)
+}
+
+
+fn check_party_poll(poll_account: &AccountInfo, party_account: &AccountInfo, party_state: &PartyAccount) -> ProgramResult {
+    if party_state.poll_id != *poll_account.key {
+        msg!("Party {} does not belong to this poll", party_account.key);
+        return Err(JanecekError::PartyListMismatch.into());
+    }
+
+    Ok(())
+}
+
+
+fn check_rent_payer(account: &AccountInfo, rent_payer: &Pubkey, rent_payer_account: &AccountInfo) -> ProgramResult {
+    // Parties of the first release learn theirs once they are updated or migrated
+    if *rent_payer == Pubkey::default() {
+        msg!("Account {} has no rent payer yet, run MigrateAccount first", account.key);
+        return Err(ProgramError::InvalidAccountData);
+    }
+
+    if *rent_payer != *rent_payer_account.key {
+        msg!("Rent must go back to the rent payer of {}", account.key);
+        return Err(ProgramError::InvalidArgument);
+    }
+
+    Ok(())
+}
+
+
+// Voter account of the given poll and voter, re-derived from its seeds
+fn load_voter(
+    program_id: &Pubkey,
+    poll_account: &AccountInfo,
+    voter: &AccountInfo,
+    voter_account: &AccountInfo,
+) -> Result<VoterAccount, ProgramError> {
+    let voter_state = load_zero_copy::<VoterAccount>(program_id, voter_account)?;
+    check_pda(program_id, voter_account, &[b"voter", poll_account.key.as_ref(), voter.key.as_ref()], voter_state.bump)?;
+    check_voter_keys(poll_account, voter, &voter_state)?;
+
+    Ok(voter_state)
+}
+
+
+// Same checks as `load_voter`, split with `VoterAccount::unpack_mut` to update it in place
+fn load_voter_mut<'a>(
+    program_id: &Pubkey,
+    poll_account: &AccountInfo,
+    voter: &AccountInfo,
+    voter_account: &'a AccountInfo,
+) -> Result<RefMut<'a, [u8]>, ProgramError> {
+    let mut voter_data = load_zero_copy_mut::<VoterAccount>(program_id, voter_account, voter.key)?;
+    let (voter_state, _) = VoterAccount::unpack_mut(&mut voter_data);
+
+    // Voters upgraded from the layout without a bump record it on their next update
+    voter_state.bump = check_pda(program_id, voter_account, &[b"voter", poll_account.key.as_ref(), voter.key.as_ref()], voter_state.bump)?;
+    check_voter_keys(poll_account, voter, voter_state)?;
+
+    Ok(voter_data)
+}
+
+
+fn check_voter_pda(program_id: &Pubkey, poll_account: &AccountInfo, voter: &AccountInfo, voter_account: &AccountInfo) -> ProgramResult {
+    let (voter_pda, _voter_bump_seed) = Pubkey::find_program_address(
+        &[b"voter", poll_account.key.as_ref(), voter.key.as_ref()],
+        program_id,
+    );
+    if voter_pda != *voter_account.key {
+        msg!("Invalid voter account");
+        return Err(ProgramError::InvalidArgument);
+    }
+
+    Ok(())
+}
+
+
+fn check_voter_keys(poll_account: &AccountInfo, voter: &AccountInfo, voter_state: &VoterAccount) -> ProgramResult {
+    if voter_state.poll_key != *poll_account.key || voter_state.voter_key != *voter.key {
+        msg!("Voter account does not belong to this poll and voter");
+        return Err(ProgramError::InvalidAccountData);
+    }
+
+    Ok(())
+}
+
+
+// Pending proposal of this poll
+fn load_proposal(program_id: &Pubkey, poll_account: &AccountInfo, proposal_account: &AccountInfo) -> Result<Proposal, ProgramError> {
+    let proposal = load_account::<Proposal>(program_id, proposal_account)?;
+
+    if proposal.poll_id != *poll_account.key {
+        msg!("Proposal does not belong to this poll");
+        return Err(JanecekError::ProposalMismatch.into());
+    }
+
+    if proposal.executed {
+        msg!("Proposal #{} was already executed", proposal.index);
+        return Err(JanecekError::ProposalAlreadyExecuted.into());
+    }
+
+    Ok(proposal)
+}
+
+
+fn load_eligible_voter(program_id: &Pubkey, poll_account: &AccountInfo, eligible_voter_account: &AccountInfo) -> Result<EligibleVoter, ProgramError> {
+    let eligible_state = load_account::<EligibleVoter>(program_id, eligible_voter_account)?;
+
+    if eligible_state.poll_key != *poll_account.key {
+        msg!("Eligible voter does not belong to this poll");
+        return Err(ProgramError::InvalidArgument);
+    }
+
+    Ok(eligible_state)
+}
+
+
+// Per-creator poll counter, created on the creator's first poll
+fn load_or_create_creator_polls<'a>(
+    program_id: &Pubkey,
+    initializer: &AccountInfo<'a>,
+    creator_account: &AccountInfo<'a>,
+    system_program_account: &AccountInfo<'a>,
+) -> Result<CreatorPolls, ProgramError> {
+    let (creator_pda, creator_bump_seed) = Pubkey::find_program_address(
+        &[b"creator", initializer.key.as_ref()],
+        program_id,
+    );
+    if creator_pda != *creator_account.key {
+        msg!("Invalid seeds for PDA");
+        return Err(ProgramError::InvalidArgument);
+    }
+
+    if !creator_account.data_is_empty() {
+        return load_account::<CreatorPolls>(program_id, creator_account);
+    }
+
+    create_program_account(
+        program_id,
+        initializer,
+        creator_account,
+        system_program_account,
+        CreatorPolls::get_account_size(),
+        &[b"creator", initializer.key.as_ref(), &[creator_bump_seed]],
+    )?;
+    msg!("PDA creation: {}", creator_pda);
+
+    Ok(CreatorPolls {
+        discriminator: CreatorPolls::DISCRIMINATOR,
+        version: CreatorPolls::VERSION,
+        creator: *initializer.key,
+        poll_count: 0,
+    })
+}
+
+
+// Appends the poll to the global registry so all polls can be listed on-chain
+fn register_poll<'a>(
+    program_id: &Pubkey,
+    initializer: &AccountInfo<'a>,
+    poll_account: &AccountInfo<'a>,
+    config: &mut ProgramConfig,
+    registry_entry_account: &AccountInfo<'a>,
+    system_program_account: &AccountInfo<'a>,
+) -> ProgramResult {
+    let index_bytes = config.poll_count.to_le_bytes();
+    let (entry_pda, entry_bump_seed) = Pubkey::find_program_address(&[b"registry", &index_bytes], program_id);
+    if entry_pda != *registry_entry_account.key {
+        msg!("Invalid seeds for PDA");
+        return Err(ProgramError::InvalidArgument);
+    }
+
+    create_program_account(
+        program_id,
+        initializer,
+        registry_entry_account,
+        system_program_account,
+        RegistryEntry::get_account_size(),
+        &[b"registry", &index_bytes, &[entry_bump_seed]],
+    )?;
+    msg!("PDA creation: {}", entry_pda);
+
+    let entry = RegistryEntry {
+        discriminator: RegistryEntry::DISCRIMINATOR,
+        version: RegistryEntry::VERSION,
+        index: config.poll_count,
+        poll: *poll_account.key,
+        creator: *initializer.key,
+    };
+    entry.serialize(&mut &mut registry_entry_account.data.borrow_mut()[..])?;
+
+    config.poll_count += 1;
+    msg!("Poll registered as #{}", entry.index);
+
+    Ok(())
+}
+
+
+// Creates a PDA owned by `owner`, usually this program, rent paid by the payer.
+// Anyone can send lamports to the address first, which would make create_account fail,
+// so a funded address is topped up to rent exemption, allocated and assigned instead
+fn create_program_account<'a>(
+    owner: &Pubkey,
+    payer: &AccountInfo<'a>,
+    account: &AccountInfo<'a>,
+    system_program_account: &AccountInfo<'a>,
+    account_len: usize,
+    seeds: &[&[u8]],
+) -> ProgramResult {
+    let rent = Rent::get()?;
+    let rent_lamports = rent.minimum_balance(account_len);
+    let current_lamports = account.lamports();
+
+    if current_lamports == 0 {
+        return invoke_signed(
+            &system_instruction::create_account(
+                payer.key,
+                account.key,
+                rent_lamports,
+                account_len.try_into().unwrap(),
+                owner,
+            ),
+            &[
+                payer.clone(),
+                account.clone(),
+                system_program_account.clone(),
+            ],
+            &[seeds],
+        );
+    }
+
+    if current_lamports < rent_lamports {
+        invoke(
+            &system_instruction::transfer(payer.key, account.key, rent_lamports - current_lamports),
+            &[
+                payer.clone(),
+                account.clone(),
+                system_program_account.clone(),
+            ],
+        )?;
+    }
+
+    invoke_signed(
+        &system_instruction::allocate(account.key, account_len.try_into().unwrap()),
+        &[account.clone(), system_program_account.clone()],
+        &[seeds],
+    )?;
+
+    invoke_signed(
+        &system_instruction::assign(account.key, owner),
+        &[account.clone(), system_program_account.clone()],
+        &[seeds],
+    )
+}
+
+
+// Poll texts are bounded so accounts have a fixed size and rent
+fn check_poll_texts(title: &str, description: &str) -> ProgramResult {
+    if title.len() > PollState::MAX_TITLE_LEN {
+        msg!("Title is longer than {} bytes", PollState::MAX_TITLE_LEN);
+        return Err(JanecekError::InvalidDataLength.into());
+    }
+
+    if description.len() > PollState::MAX_DESCRIPTION_LEN {
+        msg!("Description is longer than {} bytes", PollState::MAX_DESCRIPTION_LEN);
+        return Err(JanecekError::InvalidDataLength.into());
+    }
+
+    Ok(())
+}
+
+
+// Shared by CreatePoll and the config so defaults can never produce an invalid poll
+fn check_poll_defaults(defaults: &PollDefaults) -> ProgramResult {
+    if !(PollState::MIN_REGISTRATION_DURATION..=PollState::MAX_REGISTRATION_DURATION).contains(&defaults.registration_duration) {
+        msg!("Registration duration must be between {}s and {}s", PollState::MIN_REGISTRATION_DURATION, PollState::MAX_REGISTRATION_DURATION);
+        return Err(JanecekError::InvalidDuration.into());
+    }
+
+    if !(PollState::MIN_VOTING_DURATION..=PollState::MAX_VOTING_DURATION).contains(&defaults.voting_duration) {
+        msg!("Voting duration must be between {}s and {}s", PollState::MIN_VOTING_DURATION, PollState::MAX_VOTING_DURATION);
+        return Err(JanecekError::InvalidDuration.into());
+    }
+
+    // Validate vote budget
+    if !defaults.vote_rules.is_valid() {
+        msg!("Invalid vote rules");
+        return Err(JanecekError::InvalidVoteRules.into());
+    }
+
+    Ok(())
+}
+
+
+// Party texts are bounded so accounts have a fixed size and rent
+fn check_party_metadata(title: &str, description: &str, metadata_uri: &str) -> ProgramResult {
+    if title.len() > PartyAccount::MAX_TITLE_LEN {
+        msg!("Title is longer than {} bytes", PartyAccount::MAX_TITLE_LEN);
+        return Err(JanecekError::InvalidDataLength.into());
+    }
+
+    if description.len() > PartyAccount::MAX_DESCRIPTION_LEN {
+        msg!("Description is longer than {} bytes", PartyAccount::MAX_DESCRIPTION_LEN);
+        return Err(JanecekError::InvalidDataLength.into());
+    }
+
+    if metadata_uri.len() > PartyAccount::MAX_METADATA_URI_LEN {
+        msg!("Metadata URI is longer than {} bytes", PartyAccount::MAX_METADATA_URI_LEN);
+        return Err(JanecekError::InvalidDataLength.into());
+    }
+
+    Ok(())
+}
+
+
+// Shared checks for owner edits of a party during registration
+fn load_party_for_owner(
+    program_id: &Pubkey,
+    initializer: &AccountInfo,
+    poll_account: &AccountInfo,
+    party_account: &AccountInfo,
+    proposal_account: Option<&AccountInfo>,
+    action: ProposalAction,
+) -> Result<(PollState, PartyAccount), ProgramError> {
+    if !initializer.is_signer {
+        msg!("Missing required signature");
+        return Err(ProgramError::MissingRequiredSignature);
+    }
+
+    let poll_state = load_account::<PollState>(program_id, poll_account)?;
+
+    // Only the current owner or an approved committee proposal can manage parties
+    authorize_owner_action(program_id, poll_account, &poll_state, initializer, proposal_account, action)?;
+
+    // Parties are fixed once voting starts
+    if !matches!(poll_state.phase, VotingPhase::Registration) {
+        msg!("Can only manage parties during registration phase");
+        return Err(ProgramError::InvalidAccountData);
+    }
+
+    let party_state = load_party(program_id, poll_account, party_account)?;
+
+    Ok((poll_state, party_state))
+}
+
+
+// Rewrites an account of type `T` in the current layout, returns false for other account types
+fn migrate_layout<'a, T: AccountType>(
+    payer: &AccountInfo<'a>,
+    account: &AccountInfo<'a>,
+    system_program_account: &AccountInfo<'a>,
+    min_len: usize,
+) -> Result<bool, ProgramError> {
+    let version = match account_version::<T>(&account.data.borrow()) {
+        Some(version) => version,
+        None => return Ok(false),
+    };
+
+    if version == T::VERSION {
+        msg!("Account already uses the current layout");
+        return Err(ProgramError::InvalidAccountData);
+    }
+
+    let state = unpack_account::<T>(&account.data.borrow())?;
+
+    // Accounts only grow, old layouts could hold texts beyond today's limits
+    let account_len = account.data_len()
+        .max(min_len)
+        .max(state.try_to_vec()?.len());
+    if account_len > account.data_len() {
+        resize_account(account, payer, system_program_account, account_len)?;
+    }
+
+    state.serialize(&mut &mut account.data.borrow_mut()[..])?;
+    msg!("Account migrated from version {} to {}", version, T::VERSION);
+
+    Ok(true)
+}
+
+
+// Same as `migrate_layout` for the fixed layout accounts, version 0 accounts grow to at least `version_0_len`
+fn migrate_zero_copy<'a, T: ZeroCopyAccount>(
+    payer: &AccountInfo<'a>,
+    account: &AccountInfo<'a>,
+    system_program_account: &AccountInfo<'a>,
+    version_0_len: usize,
+    rent_payer: &Pubkey,
+) -> Result<bool, ProgramError> {
+    let version = match zero_copy_version::<T>(&account.data.borrow()) {
+        Some(version) => version,
+        None => return Ok(false),
+    };
+
+    if version == T::VERSION {
+        msg!("Account already uses the current layout");
+        return Err(ProgramError::InvalidAccountData);
+    }
+
+    // The first release sized accounts exactly for their data
+    let min_len = if version == 0 { version_0_len } else { 0 };
+    upgrade_zero_copy::<T>(account, payer, system_program_account, min_len, rent_payer)?;
+    msg!("Account migrated from version {} to {}", version, T::VERSION);
+
+    Ok(true)
+}
+
+
+// Rewrites a fixed layout account of an older version, growing it to at least `min_len` first.
+// Older layouts are decoded before the resize, some are told apart by their size
+fn upgrade_zero_copy<'a, T: ZeroCopyAccount>(
+    account: &AccountInfo<'a>,
+    payer: &AccountInfo<'a>,
+    system_program_account: &AccountInfo<'a>,
+    min_len: usize,
+    rent_payer: &Pubkey,
+) -> ProgramResult {
+    let upgraded = T::upgrade(&account.data.borrow(), rent_payer)?;
+
+    // Accounts only grow
+    let account_len = account.data_len()
+        .max(min_len)
+        .max(upgraded.len());
+    if account_len > account.data_len() {
+        resize_account(account, payer, system_program_account, account_len)?;
+    }
+
+    let mut data = account.data.borrow_mut();
+    data.fill(0);
+    data[..upgraded.len()].copy_from_slice(&upgraded);
+
+    Ok(())
+}
+
+
+// Saves a poll, one still in an older and smaller layout is grown first with the rent topped up by the payer.
+// Instructions without the system program leave that to MigrateAccount
+fn save_poll<'a>(
+    poll_state: &PollState,
+    poll_account: &AccountInfo<'a>,
+    payer: &AccountInfo<'a>,
+    system_program_account: Option<&AccountInfo<'a>>,
+) -> ProgramResult {
+    if account_version::<PollState>(&poll_account.data.borrow()) != Some(PollState::VERSION) {
+        let account_len = poll_state.try_to_vec()?.len().max(poll_state.get_account_size());
+
+        if account_len > poll_account.data_len() {
+            let system_program_account = system_program_account.ok_or_else(|| {
+                msg!("Poll {} uses an older, smaller layout, run MigrateAccount first", poll_account.key);
+                ProgramError::AccountDataTooSmall
+            })?;
+            resize_account(poll_account, payer, system_program_account, account_len)?;
+        }
+
+    }
+
+    poll_state.serialize(&mut &mut poll_account.data.borrow_mut()[..])?;
+
+    Ok(())
+}
+
+
+// Reallocs an account, topping up rent from the payer or refunding the excess to it
+fn resize_account<'a>(
+    account: &AccountInfo<'a>,
+    payer: &AccountInfo<'a>,
+    system_program_account: &AccountInfo<'a>,
+    account_len: usize,
+) -> ProgramResult {
+    let rent = Rent::get()?;
+    let required_lamports = rent.minimum_balance(account_len);
+    let current_lamports = account.lamports();
+
+    if required_lamports > current_lamports {
+        invoke(
+            &system_instruction::transfer(payer.key, account.key, required_lamports - current_lamports),
+            &[
+                payer.clone(),
+                account.clone(),
+                system_program_account.clone(),
+            ],
+        )?;
+    } else if current_lamports > required_lamports {
+        let payer_lamports = payer.lamports()
+            .checked_add(current_lamports - required_lamports)
+            .ok_or(ProgramError::ArithmeticOverflow)?;
+        **account.lamports.borrow_mut() = required_lamports;
+        **payer.lamports.borrow_mut() = payer_lamports;
+    }
+
+    account.realloc(account_len, false)
+}
+
+
+// Closing is only allowed once results are in and the archive period is over
+fn check_archive_finished(poll_state: &PollState) -> ProgramResult {
+    if !matches!(poll_state.phase, VotingPhase::Results) {
+        msg!("Accounts can only be closed after voting has ended");
+        return Err(JanecekError::VotingPeriodNotFinished.into());
+    }
+
+    let clock = Clock::get()?;
+    if clock.unix_timestamp < poll_state.archive_ends_at() {
+        msg!("Archive period ends at {}", poll_state.archive_ends_at());
+        return Err(JanecekError::ArchivePeriodNotFinished.into());
+    }
+
+    Ok(())
+}
+
+
+// Moves all lamports out and wipes the account so it can not be revived
+fn close_account<'a>(
+    account: &AccountInfo<'a>,
+    destination: &AccountInfo<'a>,
+) -> ProgramResult {
+    let destination_lamports = destination.lamports()
+        .checked_add(account.lamports())
+        .ok_or(ProgramError::ArithmeticOverflow)?;
+    **account.lamports.borrow_mut() = 0;
+    **destination.lamports.borrow_mut() = destination_lamports;
+
+    account.data.borrow_mut().fill(0);
+    account.realloc(0, false)?;
+    account.assign(&system_program::ID);
+
+    Ok(())
+}
+
+
+// Moves the voter's tokens into the poll vault, creating the vault on first use.
+// Returns whether the vault was created, its rent came from `initializer`
+#[allow(clippy::too_many_arguments)]
+fn lock_voter_tokens<'a>(
+    program_id: &Pubkey,
+    initializer: &AccountInfo<'a>,
+    poll_account: &AccountInfo<'a>,
+    token_account: &AccountInfo<'a>,
+    vault_account: &AccountInfo<'a>,
+    mint_account: &AccountInfo<'a>,
+    token_program_account: &AccountInfo<'a>,
+    system_program_account: &AccountInfo<'a>,
+    token_gate: &TokenGate,
+    amount: u64,
+) -> Result<bool, ProgramError> {
+    if *token_program_account.key != spl_token::id() {
+        msg!("Invalid token program");
+        return Err(ProgramError::IncorrectProgramId);
+    }
+
+    if *mint_account.key != token_gate.mint {
+        msg!("Mint does not match poll token gate");
+        return Err(ProgramError::InvalidArgument);
+    }
+
+    // Validate vault PDA
+    let (vault_pda, vault_bump_seed) = Pubkey::find_program_address(
+        &[b"vault", poll_account.key.as_ref()],
+        program_id,
+    );
+    if vault_pda != *vault_account.key {
+        msg!("Invalid seeds for PDA");
+        return Err(ProgramError::InvalidArgument);
+    }
+
+    // Create vault token account owned by the vault PDA itself
+    let vault_created = vault_account.data_is_empty();
+    if vault_created {
+        let seeds = &[
+            b"vault",
+            poll_account.key.as_ref(),
+            &[vault_bump_seed],
+        ];
+
+        create_program_account(
+            token_program_account.key,
+            initializer,
+            vault_account,
+            system_program_account,
+            spl_token::state::Account::LEN,
+            seeds,
+        )?;
+
+        invoke(
+            &spl_token::instruction::initialize_account3(
+                token_program_account.key,
+                vault_account.key,
+                mint_account.key,
+                vault_account.key,
+            )?,
+            &[
+                vault_account.clone(),
+                mint_account.clone(),
+                token_program_account.clone(),
+            ],
+        )?;
+
+        msg!("Vault creation: {}", vault_pda);
+    }
+
+    invoke(
+        &spl_token::instruction::transfer(
+            token_program_account.key,
+            token_account.key,
+            vault_account.key,
+            initializer.key,
+            &[],
+            amount,
+        )?,
+        &[
+            token_account.clone(),
+            vault_account.clone(),
+            initializer.clone(),
+            token_program_account.clone(),
+        ],
+    )?;
+
+    msg!("Locked {} tokens", amount);
+
+    Ok(vault_created)
+}
@@ -1,23 +1,389 @@
+use std::cell::RefMut;
+
 use borsh::{BorshDeserialize, BorshSerialize};
-use solana_program::pubkey::Pubkey;
+use bytemuck::{Pod, Zeroable};
+use solana_program::{account_info::AccountInfo, entrypoint::ProgramResult, hash::hash, msg, program_error::ProgramError, pubkey::Pubkey};
 
-#[derive(BorshSerialize, BorshDeserialize, Debug)]
+use crate::error::JanecekError;
+
+// Every account starts with an 8-byte type tag and a layout version
+pub const ACCOUNT_HEADER_SIZE: usize = 8 + 1;
+
+pub trait AccountType: BorshSerialize + BorshDeserialize {
+    const DISCRIMINATOR: [u8; 8];
+    const VERSION: u8;
+    // Borsh string the account started with before the 8-byte tag, layout version 0
+    const LEGACY_NAME: &'static str;
+
+    // Types whose layout changed override this, only the first release used the string header
+    fn from_older_version(version: u8, _data: &[u8]) -> Result<Self, ProgramError> {
+        msg!("Account version {} is not supported", version);
+        Err(JanecekError::UnsupportedAccountVersion.into())
+    }
+}
+
+// Layout version the data was written with, `None` when it is another account type
+pub fn account_version<T: AccountType>(data: &[u8]) -> Option<u8> {
+    header_version(data, &T::DISCRIMINATOR, T::LEGACY_NAME)
+}
+
+fn header_version(data: &[u8], discriminator: &[u8; 8], legacy_name: &str) -> Option<u8> {
+    if data.len() >= ACCOUNT_HEADER_SIZE && data[..8] == *discriminator {
+        return Some(data[8]);
+    }
+
+    let name = legacy_name.as_bytes();
+    let legacy_header_len = 4 + name.len();
+    if data.len() >= legacy_header_len
+        && data[..4] == (name.len() as u32).to_le_bytes()
+        && &data[4..legacy_header_len] == name
+    {
+        return Some(0);
+    }
+
+    None
+}
+
+// Reads any supported version of the account into the current layout
+pub fn unpack_account<T: AccountType>(data: &[u8]) -> Result<T, ProgramError> {
+    match account_version::<T>(data) {
+        None => Err(JanecekError::AccountTypeMismatch.into()),
+        Some(version) if version == T::VERSION => Ok(T::deserialize(&mut &data[..])?),
+        Some(version) if version < T::VERSION => T::from_older_version(version, data),
+        Some(version) => {
+            msg!("Account version {} is not supported", version);
+            Err(JanecekError::UnsupportedAccountVersion.into())
+        }
+    }
+}
+
+// Owner, type and initialization are checked before any field of the account is trusted
+pub fn load_account<T: AccountType>(program_id: &Pubkey, account: &AccountInfo) -> Result<T, ProgramError> {
+    if account.owner != program_id {
+        msg!("Account {} not owned by program", account.key);
+        return Err(ProgramError::IncorrectProgramId);
+    }
+
+    let data = account.data.borrow();
+    if is_uninitialized(&data) {
+        msg!("Account {} not initialized", account.key);
+        return Err(ProgramError::UninitializedAccount);
+    }
+
+    if account_version::<T>(&data).is_none() {
+        msg!("Account {} has the wrong type", account.key);
+        return Err(JanecekError::AccountTypeMismatch.into());
+    }
+
+    // Older layouts are converted in memory, saving one grows the account first, see `MigrateAccount`
+    unpack_account::<T>(&data)
+}
+
+// Accounts are created with their header, a zero type tag means nothing was written yet
+fn is_uninitialized(data: &[u8]) -> bool {
+    data.len() < ACCOUNT_HEADER_SIZE || data[..8].iter().all(|&b| b == 0)
+}
+
+
+// Little-endian integers without alignment, so zero-copy structs can sit anywhere in account data
+macro_rules! pod_int {
+    ($name:ident, $int:ty) => {
+        #[repr(transparent)]
+        #[derive(Clone, Copy, Default, PartialEq, Eq, Pod, Zeroable)]
+        pub struct $name([u8; std::mem::size_of::<$int>()]);
+
+        impl $name {
+            pub fn get(self) -> $int {
+                <$int>::from_le_bytes(self.0)
+            }
+        }
+
+        impl From<$int> for $name {
+            fn from(value: $int) -> Self {
+                $name(value.to_le_bytes())
+            }
+        }
+
+        impl PartialEq<$int> for $name {
+            fn eq(&self, other: &$int) -> bool {
+                self.get() == *other
+            }
+        }
+
+        impl std::fmt::Debug for $name {
+            fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+                write!(f, "{}", self.get())
+            }
+        }
+    };
+}
+
+pod_int!(PodU16, u16);
+pod_int!(PodU64, u64);
+pod_int!(PodI64, i64);
+
+// Fixed layout accounts used in place, the header is followed by a 1 byte initialized flag
+pub trait ZeroCopyAccount: Pod {
+    const DISCRIMINATOR: [u8; 8];
+    const VERSION: u8;
+    // Borsh string the account started with before the 8-byte tag, layout version 0
+    const LEGACY_NAME: &'static str;
+
+    // Current layout of `old`, a Borsh layout of an older program version.
+    // Layouts that did not store a rent payer record `rent_payer`, the owner of their poll
+    fn upgrade(old: &[u8], rent_payer: &Pubkey) -> Result<Vec<u8>, ProgramError>;
+}
+
+pub fn zero_copy_version<T: ZeroCopyAccount>(data: &[u8]) -> Option<u8> {
+    header_version(data, &T::DISCRIMINATOR, T::LEGACY_NAME)
+}
+
+// Copy of a zero-copy account, older layouts are converted in memory
+pub fn load_zero_copy<T: ZeroCopyAccount>(program_id: &Pubkey, account: &AccountInfo) -> Result<T, ProgramError> {
+    let data = account.data.borrow();
+    let version = check_zero_copy_header::<T>(program_id, account, &data)?;
+
+    if version == T::VERSION {
+        return Ok(*bytemuck::from_bytes(&data[..std::mem::size_of::<T>()]));
+    }
+
+    // A layout without a rent payer is only read here
+    let upgraded = T::upgrade(&data, &Pubkey::default())?;
+    Ok(*bytemuck::from_bytes(&upgraded[..std::mem::size_of::<T>()]))
+}
+
+// Data of an initialized zero-copy account, older layouts are rewritten in place first.
+// Accounts still smaller than their upgraded data have to be grown by the caller or by MigrateAccount
+pub fn load_zero_copy_mut<'a, T: ZeroCopyAccount>(program_id: &Pubkey, account: &'a AccountInfo, rent_payer: &Pubkey) -> Result<RefMut<'a, [u8]>, ProgramError> {
+    let mut data = account.data.borrow_mut();
+    let version = check_zero_copy_header::<T>(program_id, account, &data)?;
+
+    if version != T::VERSION {
+        let upgraded = T::upgrade(&data, rent_payer)?;
+        if upgraded.len() > data.len() {
+            msg!("Account {} uses an older, smaller layout, run MigrateAccount first", account.key);
+            return Err(ProgramError::AccountDataTooSmall);
+        }
+
+        data.fill(0);
+        data[..upgraded.len()].copy_from_slice(&upgraded);
+        msg!("Account {} upgraded from version {}", account.key, version);
+    }
+
+    Ok(RefMut::map(data, |data| &mut **data))
+}
+
+// Overwrites the whole account data, accounts in an older and smaller layout are grown by the caller first
+pub fn store_zero_copy<T: ZeroCopyAccount>(state: &T, data: &mut [u8]) -> ProgramResult {
+    let bytes = bytemuck::bytes_of(state);
+    if data.len() < bytes.len() {
+        msg!("Account is too small for the current layout, use MigrateAccount");
+        return Err(ProgramError::AccountDataTooSmall);
+    }
+
+    data.fill(0);
+    data[..bytes.len()].copy_from_slice(bytes);
+
+    Ok(())
+}
+
+// Returns the layout version, reads only the header instead of scanning the whole account
+fn check_zero_copy_header<T: ZeroCopyAccount>(program_id: &Pubkey, account: &AccountInfo, data: &[u8]) -> Result<u8, ProgramError> {
+    if account.owner != program_id {
+        msg!("Account {} not owned by program", account.key);
+        return Err(ProgramError::IncorrectProgramId);
+    }
+
+    if is_uninitialized(data) {
+        msg!("Account {} not initialized", account.key);
+        return Err(ProgramError::UninitializedAccount);
+    }
+
+    let version = zero_copy_version::<T>(data).ok_or_else(|| {
+        msg!("Account {} has the wrong type", account.key);
+        JanecekError::AccountTypeMismatch
+    })?;
+
+    if version > T::VERSION {
+        msg!("Account version {} is not supported", version);
+        return Err(JanecekError::UnsupportedAccountVersion.into());
+    }
+
+    if version == T::VERSION && (data.len() < std::mem::size_of::<T>() || data[ACCOUNT_HEADER_SIZE] != 1) {
+        msg!("Account {} not initialized", account.key);
+        return Err(ProgramError::UninitializedAccount);
+    }
+
+    Ok(version)
+}
+
+fn fixed_text(bytes: &[u8], len: usize) -> &str {
+    std::str::from_utf8(&bytes[..len.min(bytes.len())]).unwrap_or_default()
+}
+
+fn set_fixed_text(bytes: &mut [u8], text: &str) {
+    bytes.fill(0);
+    bytes[..text.len()].copy_from_slice(text.as_bytes());
+}
+
+#[derive(BorshSerialize, BorshDeserialize, Debug, Clone)]
 pub enum VotingPhase {
     Registration, 
     Voting,
     Results
 }
 
-#[derive(BorshSerialize, BorshDeserialize, Debug)]
+#[derive(BorshSerialize, BorshDeserialize, Debug, Clone, Copy, PartialEq, Eq)]
 pub enum VoteType {
     Positive, 
     Negative
 }
 
-// ["poll", itle, owner]
-#[derive(BorshSerialize, BorshDeserialize, Debug)]
+// One vote of a `CastBallot` instruction
+#[derive(BorshSerialize, BorshDeserialize, Debug, Clone, Copy, PartialEq, Eq)]
+pub struct BallotEntry {
+    pub party: Pubkey,
+    pub vote_type: VoteType,
+}
+
+#[derive(BorshSerialize, BorshDeserialize, Debug, Clone, Copy, PartialEq, Eq)]
+pub enum VotingMode {
+    OnePersonOneVote,
+    // Vote weight is the voter's token balance, locked in the poll vault on the first vote
+    TokenWeighted,
+}
+
+// Per-poll Janeček method budget, e.g. 2+1, 4+2 or 6+3
+#[derive(BorshSerialize, BorshDeserialize, Debug, Clone, Copy, PartialEq, Eq)]
+pub struct VoteRules {
+    pub positive_votes: u8,
+    pub negative_votes: u8,
+    // Positive votes a voter must have cast for each negative vote
+    pub positives_per_negative: u8,
+    pub allow_negative: bool,
+}
+
+impl VoteRules {
+    pub const SIZE: usize = 1 + 1 + 1 + 1;
+    pub const MAX_POSITIVE_VOTES: u8 = 12;
+    pub const MAX_NEGATIVE_VOTES: u8 = 6;
+
+    pub fn is_valid(&self) -> bool {
+        if self.positive_votes == 0 || self.positive_votes > VoteRules::MAX_POSITIVE_VOTES {
+            return false;
+        }
+
+        if !self.allow_negative {
+            return self.negative_votes == 0;
+        }
+
+        // Every negative vote must be reachable with the positive budget
+        self.negative_votes > 0
+            && self.negative_votes <= VoteRules::MAX_NEGATIVE_VOTES
+            && (self.negative_votes as u16) * (self.positives_per_negative as u16) <= self.positive_votes as u16
+    }
+
+    pub fn max_voted_parties(&self) -> usize {
+        self.positive_votes as usize + self.negative_votes as usize
+    }
+
+    // Positive votes a voter must hold to have `negative_count` negative votes
+    pub fn positives_required_for(&self, negative_count: u8) -> u16 {
+        negative_count as u16 * self.positives_per_negative as u16
+    }
+}
+
+impl Default for VoteRules {
+    fn default() -> Self {
+        VoteRules {
+            positive_votes: 2,
+            negative_votes: 1,
+            positives_per_negative: 2,
+            allow_negative: true,
+        }
+    }
+}
+
+// Voters must hold at least `min_balance` tokens of `mint`
+#[derive(BorshSerialize, BorshDeserialize, Debug, Clone, Copy, PartialEq, Eq)]
+pub struct TokenGate {
+    pub mint: Pubkey,
+    pub min_balance: u64,
+}
+
+impl TokenGate {
+    pub const SIZE: usize = 32 + 8;
+}
+
+// Seeds a poll address was derived from, they stay when the texts are edited
+#[derive(BorshSerialize, BorshDeserialize, Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PollSeeds {
+    // ["poll", hash(title), hash(description)], polls created before creator nonces
+    Text {
+        title_seed: [u8; 32],
+        description_seed: [u8; 32],
+    },
+    // ["poll", creator, poll_nonce], nonce taken from the creator's CreatorPolls counter
+    Creator {
+        creator: Pubkey,
+        poll_nonce: u64,
+    },
+}
+
+impl PollSeeds {
+    pub const SIZE: usize = 1 + 32 + 32;
+
+    pub fn find_address(&self, program_id: &Pubkey) -> (Pubkey, u8) {
+        match self {
+            PollSeeds::Text { title_seed, description_seed } => {
+                Pubkey::find_program_address(&[b"poll", title_seed, description_seed], program_id)
+            }
+            PollSeeds::Creator { creator, poll_nonce } => {
+                Pubkey::find_program_address(&[b"poll", creator.as_ref(), &poll_nonce.to_le_bytes()], program_id)
+            }
+        }
+    }
+}
+
+// M-of-N control over the poll, see `ProposalAction` for the gated actions
+#[derive(BorshSerialize, BorshDeserialize, Debug, Clone, PartialEq, Eq)]
+pub struct Committee {
+    pub members: Vec<Pubkey>,
+    pub threshold: u8,
+}
+
+impl Committee {
+    // Approvals are a u16 bitmask over member indexes
+    pub const MAX_MEMBERS: usize = 16;
+
+    pub fn get_size(&self) -> usize {
+        (4 + 32 * self.members.len()) + 1
+    }
+
+    pub fn is_valid(&self) -> bool {
+        if self.members.is_empty() || self.members.len() > Committee::MAX_MEMBERS {
+            return false;
+        }
+
+        if self.threshold == 0 || self.threshold as usize > self.members.len() {
+            return false;
+        }
+
+        // Duplicates would let one key approve twice
+        self.members.iter().enumerate().all(|(index, member)| !self.members[..index].contains(member))
+    }
+
+    pub fn member_index(&self, member: &Pubkey) -> Option<usize> {
+        self.members.iter().position(|key| key == member)
+    }
+}
+
+
+// Address is given by `seeds`, see `PollSeeds::find_address`
+#[derive(BorshSerialize, BorshDeserialize, Debug, Clone)]
 pub struct PollState {
-    pub discriminator: String,
+    pub discriminator: [u8; 8],
+    pub version: u8,
     pub title: String,
     pub description: String,
     pub phase: VotingPhase,
@@ -26,28 +392,472 @@ pub struct PollState {
     pub expected_new_owner: Pubkey,
     pub created_at: i64,
     pub voting_start_at: i64,
+    pub registration_duration: i64,
+    pub voting_duration: i64,
+    pub vote_rules: VoteRules,
+    // Only voters registered by the owner can vote, chosen at creation
+    pub closed: bool,
+    pub eligible_voters: u64,
+    pub token_gate: Option<TokenGate>,
+    pub voting_mode: VotingMode,
+    // Account that paid the poll rent and gets it back on close
+    pub rent_payer: Pubkey,
+    // Voter whose first vote created the token vault, gets its rent back on close
+    pub vault_rent_payer: Pubkey,
+    // Time after the results during which accounts can not be closed yet
+    pub archive_period: i64,
+    pub ended_at: i64,
+    pub seeds: PollSeeds,
+    // Circuit breaker set by the owner, see `SetPollPaused`
+    pub paused: bool,
+    pub paused_at: i64,
+    // Time spent paused during voting that was added to the deadline
+    pub paused_duration: i64,
+    // `ProgramConfig::paused_duration` when voting started, later program pauses extend the deadline
+    pub program_paused_duration: i64,
+    // `expected_new_owner` is Pubkey::default() when no transfer is pending
+    pub owner_transfer_expires_at: i64,
+    // Committee mode, StartVoting, CreateParty and early EndVoting need an approved proposal
+    pub committee: Option<Committee>,
+    pub proposal_counter: u64,
+}
+
+impl AccountType for PollState {
+    const DISCRIMINATOR: [u8; 8] = *b"poll\0\0\0\0";
+    const VERSION: u8 = 1;
+    const LEGACY_NAME: &'static str = "poll";
+
+    // Polls of the first release
+    fn from_older_version(_version: u8, data: &[u8]) -> Result<Self, ProgramError> {
+        Ok(LegacyPollState::deserialize(&mut &data[..])?.into())
+    }
 }
 
 impl PollState {
-    pub const DISCRIMINATOR: &'static str = "poll";
+    pub const MIN_REGISTRATION_DURATION: i64 = 60;
+    pub const MAX_REGISTRATION_DURATION: i64 = 60 * 60 * 24 * 30;
+    pub const MIN_VOTING_DURATION: i64 = 60;
+    pub const MAX_VOTING_DURATION: i64 = 60 * 60 * 24 * 60;
+
+    // Schedule of polls created before durations were stored per poll
+    pub const LEGACY_REGISTRATION_DURATION: i64 = 60 * 60 * 24;
+    pub const LEGACY_VOTING_DURATION: i64 = 60 * 60 * 24 * 7;
 
-    pub fn get_account_size(title: &String, description: &String) -> usize {
-        return (4 + PollState::DISCRIMINATOR.len()) 
-        + (4 + title.len()) 
-        + (4 + description.len())
+    pub const MAX_ARCHIVE_PERIOD: i64 = 60 * 60 * 24 * 365;
+
+    // Nominated owner has this long to accept
+    pub const OWNER_TRANSFER_DURATION: i64 = 60 * 60 * 24 * 7;
+
+    // EndVoting takes every party in one transaction, which has room for about 27 of them
+    pub const MAX_PARTIES: u64 = 24;
+
+    pub const MAX_TITLE_LEN: usize = 64;
+    pub const MAX_DESCRIPTION_LEN: usize = 256;
+
+    // Sized for the longest texts so fields can be edited in place, without a token gate or committee
+    pub const SIZE: usize = ACCOUNT_HEADER_SIZE
+        + (4 + PollState::MAX_TITLE_LEN)
+        + (4 + PollState::MAX_DESCRIPTION_LEN)
         + 1
         + 8
         + 32
         + 32
         + 8
+        + 8
+        + 8
+        + 8
+        + VoteRules::SIZE
+        + 1
+        + 8
+        + 1
+        + 1
+        + 32
+        + 32
+        + 8
+        + 8
+        + PollSeeds::SIZE
+        + 1
+        + 8
+        + 8
+        + 8
+        + 8
+        + 1
         + 8;
+
+    // A token gate and a committee take room only in the polls that have them
+    pub fn get_account_size(&self) -> usize {
+        PollState::SIZE
+            + self.token_gate.map_or(0, |_| TokenGate::SIZE)
+            + self.committee.as_ref().map_or(0, Committee::get_size)
+    }
+
+    // Program pauses during voting count like poll pauses, the config tracks their total
+    pub fn voting_ends_at(&self, config: &ProgramConfig) -> i64 {
+        self.voting_start_at + self.voting_duration + self.paused_duration
+            + (config.paused_duration - self.program_paused_duration)
+    }
+
+    // Accounts of the poll can be closed once results are in and the archive period passed
+    pub fn archive_ends_at(&self) -> i64 {
+        self.ended_at + self.archive_period
     }
 }
 
 
-// ["party", poll_id_pubkey, party_title]
+// PollState layout of the first release, version 0
 #[derive(BorshSerialize, BorshDeserialize, Debug)]
+pub struct LegacyPollState {
+    pub discriminator: String,
+    pub title: String,
+    pub description: String,
+    pub phase: VotingPhase,
+    pub party_counter: u64,
+    pub owner: Pubkey,
+    pub expected_new_owner: Pubkey,
+    pub created_at: i64,
+    pub voting_start_at: i64,
+}
+
+impl From<LegacyPollState> for PollState {
+    fn from(legacy: LegacyPollState) -> Self {
+        // Legacy polls were never edited, so their texts are still the seeds
+        let title_seed = hash(legacy.title.as_bytes()).to_bytes();
+        let description_seed = hash(legacy.description.as_bytes()).to_bytes();
+
+        PollState {
+            discriminator: PollState::DISCRIMINATOR,
+            version: PollState::VERSION,
+            title: legacy.title,
+            description: legacy.description,
+            phase: legacy.phase,
+            party_counter: legacy.party_counter,
+            owner: legacy.owner,
+            // Legacy nominations have no deadline, the owner has to nominate again
+            expected_new_owner: Pubkey::default(),
+            created_at: legacy.created_at,
+            voting_start_at: legacy.voting_start_at,
+            registration_duration: PollState::LEGACY_REGISTRATION_DURATION,
+            voting_duration: PollState::LEGACY_VOTING_DURATION,
+            vote_rules: VoteRules::default(),
+            closed: false,
+            eligible_voters: 0,
+            token_gate: None,
+            voting_mode: VotingMode::OnePersonOneVote,
+            // Legacy polls did not record who paid, their owner gets the rent back
+            rent_payer: legacy.owner,
+            vault_rent_payer: Pubkey::default(),
+            archive_period: 0,
+            ended_at: 0,
+            seeds: PollSeeds::Text { title_seed, description_seed },
+            paused: false,
+            paused_at: 0,
+            paused_duration: 0,
+            program_paused_duration: 0,
+            owner_transfer_expires_at: 0,
+            committee: None,
+            proposal_counter: 0,
+        }
+    }
+}
+
+
+// ["creator", creator_pubkey], polls of a creator are ["poll", creator, 0..poll_count]
+#[derive(BorshSerialize, BorshDeserialize, Debug)]
+pub struct CreatorPolls {
+    pub discriminator: [u8; 8],
+    pub version: u8,
+    pub creator: Pubkey,
+    pub poll_count: u64,
+}
+
+impl AccountType for CreatorPolls {
+    const DISCRIMINATOR: [u8; 8] = *b"creator\0";
+    const VERSION: u8 = 1;
+    const LEGACY_NAME: &'static str = "creator";
+}
+
+impl CreatorPolls {
+    pub fn get_account_size() -> usize {
+        ACCOUNT_HEADER_SIZE
+        + 32
+        + 8
+    }
+}
+
+
+// Settings a poll gets when CreatePoll leaves them out
+#[derive(BorshSerialize, BorshDeserialize, Debug, Clone, Copy, PartialEq, Eq)]
+pub struct PollDefaults {
+    pub registration_duration: i64,
+    pub voting_duration: i64,
+    pub vote_rules: VoteRules,
+}
+
+impl PollDefaults {
+    pub const SIZE: usize = 8 + 8 + VoteRules::SIZE;
+}
+
+impl Default for PollDefaults {
+    fn default() -> Self {
+        PollDefaults {
+            registration_duration: PollState::LEGACY_REGISTRATION_DURATION,
+            voting_duration: PollState::LEGACY_VOTING_DURATION,
+            vote_rules: VoteRules::default(),
+        }
+    }
+}
+
+
+// ["config"], program-wide settings, every poll is listed in a RegistryEntry ["registry", 0..poll_count]
+#[derive(BorshSerialize, BorshDeserialize, Debug)]
+pub struct ProgramConfig {
+    pub discriminator: [u8; 8],
+    pub version: u8,
+    pub admin: Pubkey,
+    pub defaults: PollDefaults,
+    // Lamports paid to the admin for every new poll, 0 disables the fee
+    pub creation_fee: u64,
+    pub poll_count: u64,
+    // Circuit breaker for the whole program, see `SetProgramPaused`
+    pub paused: bool,
+    pub paused_at: i64,
+    // Total time the program spent paused, added to the deadline of polls that were in voting
+    pub paused_duration: i64,
+    // Canonical bump of the config PDA
+    pub bump: u8,
+}
+
+impl AccountType for ProgramConfig {
+    const DISCRIMINATOR: [u8; 8] = *b"config\0\0";
+    const VERSION: u8 = 1;
+    const LEGACY_NAME: &'static str = "config";
+}
+
+impl ProgramConfig {
+    pub fn get_account_size() -> usize {
+        ACCOUNT_HEADER_SIZE
+        + 32
+        + PollDefaults::SIZE
+        + 8
+        + 8
+        + 1
+        + 8
+        + 8
+        + 1
+    }
+}
+
+// ["registry", index]
+#[derive(BorshSerialize, BorshDeserialize, Debug)]
+pub struct RegistryEntry {
+    pub discriminator: [u8; 8],
+    pub version: u8,
+    pub index: u64,
+    pub poll: Pubkey,
+    pub creator: Pubkey,
+}
+
+impl AccountType for RegistryEntry {
+    const DISCRIMINATOR: [u8; 8] = *b"entry\0\0\0";
+    const VERSION: u8 = 1;
+    const LEGACY_NAME: &'static str = "entry";
+}
+
+impl RegistryEntry {
+    pub fn get_account_size() -> usize {
+        ACCOUNT_HEADER_SIZE
+        + 8
+        + 32
+        + 32
+    }
+}
+
+
+// Privileged poll actions a committee votes on, arguments must match the executing call
+#[derive(BorshSerialize, BorshDeserialize, Debug, Clone, PartialEq, Eq)]
+pub enum ProposalAction {
+    StartVoting,
+    CreateParty {
+        title: String,
+        description: String,
+        metadata_uri: String,
+        metadata_hash: [u8; 32],
+    },
+    EndVoting,
+    RemoveParty {
+        party: Pubkey,
+    },
+    UpdatePartyMetadata {
+        party: Pubkey,
+        description: String,
+        metadata_uri: String,
+        metadata_hash: [u8; 32],
+    },
+    UpdatePoll {
+        title: String,
+        description: String,
+    },
+    RegisterVoter {
+        voter: Pubkey,
+    },
+    UnregisterVoter {
+        voter: Pubkey,
+    },
+    SetPollPaused {
+        paused: bool,
+        extend_deadline: bool,
+    },
+}
+
+impl ProposalAction {
+    // Largest variant is CreateParty with the longest party texts
+    pub const SIZE: usize = 1
+        + (4 + PartyAccount::MAX_TITLE_LEN)
+        + (4 + PartyAccount::MAX_DESCRIPTION_LEN)
+        + (4 + PartyAccount::MAX_METADATA_URI_LEN)
+        + 32;
+}
+
+
+// ["proposal", poll_id_pubkey, index]
+#[derive(BorshSerialize, BorshDeserialize, Debug)]
+pub struct Proposal {
+    pub discriminator: [u8; 8],
+    pub version: u8,
+    pub poll_id: Pubkey,
+    pub index: u64,
+    pub proposer: Pubkey,
+    pub action: ProposalAction,
+    // Bit i is set once committee member i approved
+    pub approvals: u16,
+    pub executed: bool,
+}
+
+impl AccountType for Proposal {
+    const DISCRIMINATOR: [u8; 8] = *b"proposal";
+    const VERSION: u8 = 1;
+    const LEGACY_NAME: &'static str = "proposal";
+}
+
+impl Proposal {
+    pub const SIZE: usize = ACCOUNT_HEADER_SIZE
+        + 32
+        + 8
+        + 32
+        + ProposalAction::SIZE
+        + 2
+        + 1;
+
+    pub fn approval_count(&self) -> u32 {
+        self.approvals.count_ones()
+    }
+}
+
+// ["party", poll_id_pubkey, party_title], fixed layout so votes update the counters in place
+#[repr(C)]
+#[derive(Clone, Copy, Debug, Pod, Zeroable)]
 pub struct PartyAccount {
+    pub discriminator: [u8; 8],
+    pub version: u8,
+    pub initialized: u8,
+    pub poll_id: Pubkey,
+    // Texts are stored in fixed buffers, see `title()` and `set_metadata()`
+    pub title_len: u8,
+    pub title_bytes: [u8; PARTY_MAX_TITLE_LEN],
+    pub description_len: PodU16,
+    pub description_bytes: [u8; PARTY_MAX_DESCRIPTION_LEN],
+    pub metadata_uri_len: u8,
+    pub metadata_uri_bytes: [u8; PARTY_MAX_METADATA_URI_LEN],
+    // Off-chain JSON with logo and candidate list, pinned by its hash
+    pub metadata_hash: [u8; 32],
+    pub positive_votes: PodU64,
+    pub negative_votes: PodU64,
+    pub rent_payer: Pubkey,
+}
+
+const PARTY_MAX_TITLE_LEN: usize = 64;
+const PARTY_MAX_DESCRIPTION_LEN: usize = 256;
+const PARTY_MAX_METADATA_URI_LEN: usize = 200;
+
+impl ZeroCopyAccount for PartyAccount {
+    const DISCRIMINATOR: [u8; 8] = *b"party\0\0\0";
+    const VERSION: u8 = 1;
+    const LEGACY_NAME: &'static str = "party";
+
+    fn upgrade(old: &[u8], rent_payer: &Pubkey) -> Result<Vec<u8>, ProgramError> {
+        let legacy = PartyAccountV0::deserialize(&mut &old[..])?;
+
+        let mut party = PartyAccount::new(legacy.poll_id, *rent_payer);
+        party.set_metadata(&legacy.title, "", "", [0u8; 32])?;
+        party.positive_votes = legacy.positive_votes.into();
+        party.negative_votes = legacy.negative_votes.into();
+
+        Ok(bytemuck::bytes_of(&party).to_vec())
+    }
+}
+
+impl PartyAccount {
+    pub const MAX_TITLE_LEN: usize = PARTY_MAX_TITLE_LEN;
+    pub const MAX_DESCRIPTION_LEN: usize = PARTY_MAX_DESCRIPTION_LEN;
+    pub const MAX_METADATA_URI_LEN: usize = PARTY_MAX_METADATA_URI_LEN;
+
+    pub const SIZE: usize = std::mem::size_of::<PartyAccount>();
+
+    pub fn new(poll_id: Pubkey, rent_payer: Pubkey) -> Self {
+        PartyAccount {
+            discriminator: PartyAccount::DISCRIMINATOR,
+            version: PartyAccount::VERSION,
+            initialized: 1,
+            poll_id,
+            rent_payer,
+            ..Zeroable::zeroed()
+        }
+    }
+
+    pub fn from_bytes(data: &[u8]) -> &PartyAccount {
+        bytemuck::from_bytes(&data[..PartyAccount::SIZE])
+    }
+
+    pub fn from_bytes_mut(data: &mut [u8]) -> &mut PartyAccount {
+        bytemuck::from_bytes_mut(&mut data[..PartyAccount::SIZE])
+    }
+
+    pub fn title(&self) -> &str {
+        fixed_text(&self.title_bytes, self.title_len as usize)
+    }
+
+    pub fn description(&self) -> &str {
+        fixed_text(&self.description_bytes, self.description_len.get() as usize)
+    }
+
+    pub fn metadata_uri(&self) -> &str {
+        fixed_text(&self.metadata_uri_bytes, self.metadata_uri_len as usize)
+    }
+
+    pub fn set_metadata(&mut self, title: &str, description: &str, metadata_uri: &str, metadata_hash: [u8; 32]) -> ProgramResult {
+        if title.len() > PartyAccount::MAX_TITLE_LEN
+            || description.len() > PartyAccount::MAX_DESCRIPTION_LEN
+            || metadata_uri.len() > PartyAccount::MAX_METADATA_URI_LEN
+        {
+            return Err(JanecekError::InvalidDataLength.into());
+        }
+
+        self.title_len = title.len() as u8;
+        set_fixed_text(&mut self.title_bytes, title);
+        self.description_len = (description.len() as u16).into();
+        set_fixed_text(&mut self.description_bytes, description);
+        self.metadata_uri_len = metadata_uri.len() as u8;
+        set_fixed_text(&mut self.metadata_uri_bytes, metadata_uri);
+        self.metadata_hash = metadata_hash;
+
+        Ok(())
+    }
+}
+
+
+// PartyAccount layout of the first release, version 0
+#[derive(BorshSerialize, BorshDeserialize, Debug)]
+pub struct PartyAccountV0 {
     pub discriminator: String,
     pub poll_id: Pubkey,
     pub title: String,
@@ -55,22 +865,157 @@ pub struct PartyAccount {
     pub negative_votes: u64,
 }
 
-impl PartyAccount {
-    pub const DISCRIMINATOR: &'static str = "party";
 
-    pub fn get_account_size(title: &String) -> usize {
-        return (4 + PartyAccount::DISCRIMINATOR.len()) 
-            + 32
-            + (4 + title.len())
-            + 8
-            + 8;
+// ["voter", poll_id_pubkey, voter_pubkey], fixed header followed by one `VotedParty` slot per vote the rules allow
+#[repr(C)]
+#[derive(Clone, Copy, Debug, Pod, Zeroable)]
+pub struct VoterAccount {
+    pub discriminator: [u8; 8],
+    pub version: u8,
+    pub initialized: u8,
+    // Canonical bump of the voter PDA, 0 for voters upgraded from the first release
+    pub bump: u8,
+    pub poll_key: Pubkey,
+    pub voter_key: Pubkey,
+    pub positive_used: u8,
+    pub negative_used: u8,
+    // Weight added to a party per vote, snapshotted on the first vote
+    pub vote_weight: PodU64,
+    // Tokens held in the poll vault until the voter unlocks them
+    pub locked_tokens: PodU64,
+    // Slots in use, the ones after them are zeroed
+    pub voted_count: u8,
+}
+
+impl ZeroCopyAccount for VoterAccount {
+    const DISCRIMINATOR: [u8; 8] = *b"voter\0\0\0";
+    const VERSION: u8 = 1;
+    const LEGACY_NAME: &'static str = "voter";
+
+    fn upgrade(old: &[u8], _rent_payer: &Pubkey) -> Result<Vec<u8>, ProgramError> {
+        let legacy = VoterAccountV0::deserialize(&mut &old[..])?;
+
+        let mut voter = VoterAccount::new(legacy.poll_key, legacy.voter_key, 1, 0, 0);
+        voter.positive_used = legacy.positive_used;
+        voter.negative_used = legacy.negative_used;
+
+        // Room for the votes cast so far, the caller keeps the account's own slot count
+        let mut data = vec![0u8; VoterAccount::SIZE + VotedParty::SIZE * legacy.voted_parties.len()];
+        store_zero_copy(&voter, &mut data)?;
+        let (voter, slots) = VoterAccount::unpack_mut(&mut data);
+        for (index, party) in legacy.voted_parties.into_iter().enumerate() {
+            // The first release only took the negative vote after both positive ones
+            let vote_type = if index < legacy.positive_used as usize { VoteType::Positive } else { VoteType::Negative };
+            voter.push_vote(slots, VotedParty::new(party, vote_type, 0, 0))?;
+        }
+
+        Ok(data)
+    }
+}
+
+impl VoterAccount {
+    pub const SIZE: usize = std::mem::size_of::<VoterAccount>();
+
+    pub fn new(poll_key: Pubkey, voter_key: Pubkey, vote_weight: u64, locked_tokens: u64, bump: u8) -> Self {
+        VoterAccount {
+            discriminator: VoterAccount::DISCRIMINATOR,
+            version: VoterAccount::VERSION,
+            initialized: 1,
+            bump,
+            poll_key,
+            voter_key,
+            vote_weight: vote_weight.into(),
+            locked_tokens: locked_tokens.into(),
+            ..Zeroable::zeroed()
+        }
+    }
+
+    pub fn get_account_size(vote_rules: &VoteRules) -> usize {
+        VoterAccount::SIZE + (VotedParty::SIZE * vote_rules.max_voted_parties())
+    }
+
+    // Header and the votes cast so far
+    pub fn unpack(data: &[u8]) -> (&VoterAccount, &[VotedParty]) {
+        let (header, slots) = data.split_at(VoterAccount::SIZE);
+        let voter: &VoterAccount = bytemuck::from_bytes(header);
+        let slots: &[VotedParty] = bytemuck::cast_slice(&slots[..slots.len() - slots.len() % VotedParty::SIZE]);
+
+        (voter, &slots[..(voter.voted_count as usize).min(slots.len())])
+    }
+
+    // Header and every vote slot, used or not
+    pub fn unpack_mut(data: &mut [u8]) -> (&mut VoterAccount, &mut [VotedParty]) {
+        let (header, slots) = data.split_at_mut(VoterAccount::SIZE);
+        let slots_len = slots.len() - slots.len() % VotedParty::SIZE;
+
+        (bytemuck::from_bytes_mut(header), bytemuck::cast_slice_mut(&mut slots[..slots_len]))
+    }
+
+    pub fn find_vote(&self, slots: &[VotedParty], party: &Pubkey) -> Option<usize> {
+        slots[..self.voted_count as usize].iter().position(|voted| voted.party == *party)
+    }
+
+    pub fn push_vote(&mut self, slots: &mut [VotedParty], voted: VotedParty) -> ProgramResult {
+        let count = self.voted_count as usize;
+        if count >= slots.len() {
+            return Err(ProgramError::AccountDataTooSmall);
+        }
+
+        slots[count] = voted;
+        self.voted_count += 1;
+
+        Ok(())
+    }
+
+    // Keeps the order of the remaining votes
+    pub fn remove_vote(&mut self, slots: &mut [VotedParty], index: usize) -> VotedParty {
+        let count = self.voted_count as usize;
+        let removed = slots[index];
+
+        slots.copy_within(index + 1..count, index);
+        slots[count - 1] = Zeroable::zeroed();
+        self.voted_count -= 1;
+
+        removed
+    }
+}
+
+
+#[repr(C)]
+#[derive(Clone, Copy, Debug, Pod, Zeroable)]
+pub struct VotedParty {
+    pub party: Pubkey,
+    // `VoteType` discriminant, see `vote_type()`
+    pub vote_type: u8,
+    pub voted_at: PodI64,
+    pub slot: PodU64,
+}
+
+impl VotedParty {
+    pub const SIZE: usize = std::mem::size_of::<VotedParty>();
+
+    pub fn new(party: Pubkey, vote_type: VoteType, voted_at: i64, slot: u64) -> Self {
+        VotedParty {
+            party,
+            vote_type: vote_type as u8,
+            voted_at: voted_at.into(),
+            slot: slot.into(),
+        }
+    }
+
+    pub fn vote_type(&self) -> VoteType {
+        if self.vote_type == VoteType::Negative as u8 {
+            VoteType::Negative
+        } else {
+            VoteType::Positive
+        }
     }
 }
 
 
-// ["voter", poll_id_pubkey, voter_pubkey]
+// VoterAccount layout of the first release, version 0
 #[derive(BorshSerialize, BorshDeserialize, Debug)]
-pub struct VoterAccount {
+pub struct VoterAccountV0 {
     pub discriminator: String,
     pub poll_key: Pubkey,
     pub voter_key: Pubkey,
@@ -79,16 +1024,73 @@ pub struct VoterAccount {
     pub voted_parties: Vec<Pubkey>,
 }
 
-impl VoterAccount {
-    pub const DISCRIMINATOR: &'static str = "voter";
-    pub const MAX_VOTED_PARTIES: usize = 3;
 
+// ["eligible", poll_id_pubkey, voter_pubkey]
+#[derive(BorshSerialize, BorshDeserialize, Debug)]
+pub struct EligibleVoter {
+    pub discriminator: [u8; 8],
+    pub version: u8,
+    pub poll_key: Pubkey,
+    pub voter_key: Pubkey,
+    // Owner that registered the voter, gets the rent back on close
+    pub rent_payer: Pubkey,
+    // Canonical bump of the eligibility PDA
+    pub bump: u8,
+}
+
+impl AccountType for EligibleVoter {
+    const DISCRIMINATOR: [u8; 8] = *b"eligible";
+    const VERSION: u8 = 1;
+    const LEGACY_NAME: &'static str = "eligible";
+}
+
+impl EligibleVoter {
     pub fn get_account_size() -> usize {
-        return (4 + VoterAccount::DISCRIMINATOR.len())
+        ACCOUNT_HEADER_SIZE
+        + 32
         + 32
         + 32
         + 1
-        + 1
-        + 4 + (32 * VoterAccount::MAX_VOTED_PARTIES);
     }
-}
\ No newline at end of file
+}
+
+#[derive(BorshSerialize, BorshDeserialize, Debug, Clone)]
+pub struct PartyResult {
+    pub party: Pubkey,
+    pub positive_votes: u64,
+    pub negative_votes: u64,
+    pub net_votes: i128,
+}
+
+impl PartyResult {
+    pub const SIZE: usize = 32 + 8 + 8 + 16;
+}
+
+
+// ["results", poll_id_pubkey]
+#[derive(BorshSerialize, BorshDeserialize, Debug)]
+pub struct PollResults {
+    pub discriminator: [u8; 8],
+    pub version: u8,
+    pub poll_id: Pubkey,
+    pub finalized_at: i64,
+    // Account that ran EndVoting, gets the rent back when the poll is closed
+    pub rent_payer: Pubkey,
+    pub parties: Vec<PartyResult>,
+}
+
+impl AccountType for PollResults {
+    const DISCRIMINATOR: [u8; 8] = *b"results\0";
+    const VERSION: u8 = 1;
+    const LEGACY_NAME: &'static str = "results";
+}
+
+impl PollResults {
+    pub fn get_account_size(party_count: usize) -> usize {
+        ACCOUNT_HEADER_SIZE
+        + 32
+        + 8
+        + 32
+        + 4 + (PartyResult::SIZE * party_count)
+    }
+}
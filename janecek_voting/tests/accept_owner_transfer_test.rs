@@ -7,7 +7,7 @@ use helpers::{
 };
 use janecek_voting::state::{PollState, VotingPhase};
 use borsh::{BorshDeserialize};
-use solana_sdk::{signature::Keypair, signer::Signer, system_instruction, transaction::Transaction};
+use solana_sdk::{pubkey::Pubkey, signature::Keypair, signer::Signer, system_instruction, transaction::Transaction};
 
 #[tokio::test]
 async fn test_accept_owner_transfer_success() {
@@ -20,7 +20,7 @@ async fn test_accept_owner_transfer_success() {
 
     let poll_pda = create_poll(
         &mut ctx.banks_client,
-        &payer,
+        payer,
         &recent_blockhash,
         &program_id,
         &poll_title,
@@ -44,7 +44,7 @@ async fn test_accept_owner_transfer_success() {
 
     initiate_owner_transfer(
         &mut ctx.banks_client,
-        &payer,
+        payer,
         &recent_blockhash,
         &program_id,
         &new_owner.pubkey(),
@@ -59,8 +59,9 @@ async fn test_accept_owner_transfer_success() {
         &poll_pda,
     ).await;
 
-    let poll_state = PollState::try_from_slice(&ctx.banks_client.get_account(poll_pda).await.unwrap().unwrap().data).unwrap();
+    let poll_state = PollState::deserialize(&mut &ctx.banks_client.get_account(poll_pda).await.unwrap().unwrap().data[..]).unwrap();
     assert_eq!(poll_state.phase as u8, VotingPhase::Registration as u8);
     assert_eq!(poll_state.owner, new_owner.pubkey());
-    assert_eq!(poll_state.expected_new_owner, new_owner.pubkey());
+    assert_eq!(poll_state.expected_new_owner, Pubkey::default());
+    assert_eq!(poll_state.owner_transfer_expires_at, 0);
 }
\ No newline at end of file
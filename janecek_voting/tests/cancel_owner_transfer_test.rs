@@ -0,0 +1,211 @@
+mod helpers;
+use helpers::{
+    setup_test_env,
+    assert_custom_error,
+    create_poll,
+    initiate_owner_transfer,
+    accept_owner_transfer,
+    accept_owner_transfer_expect_fail,
+    cancel_owner_transfer,
+    cancel_owner_transfer_expect_fail,
+};
+use janecek_voting::{error::JanecekError, state::PollState};
+use borsh::{BorshDeserialize};
+use solana_sdk::{clock::Clock, pubkey::Pubkey, signature::Keypair, signer::Signer, system_instruction, transaction::{Transaction, TransactionError}, instruction::InstructionError};
+
+
+#[tokio::test]
+async fn test_cancel_owner_transfer_success() {
+    let (mut ctx, program_id) = setup_test_env().await;
+
+    let title = "Presidential Election".to_string();
+    let description = "Vote for the next president".to_string();
+    let new_owner = Keypair::new();
+
+    // transfer SOL to new owner
+    let transfer_ix = system_instruction::transfer(
+        &ctx.payer.pubkey(),
+        &new_owner.pubkey(),
+        1_000_000_000, // 1 SOL
+    );
+    let tx = Transaction::new_signed_with_payer(
+        &[transfer_ix],
+        Some(&ctx.payer.pubkey()),
+        &[&ctx.payer],
+        ctx.last_blockhash,
+    );
+    ctx.banks_client.process_transaction(tx).await.unwrap();
+
+    // create poll
+    let poll_pda = create_poll(
+        &mut ctx.banks_client,
+        &ctx.payer,
+        &ctx.last_blockhash,
+        &program_id,
+        &title,
+        &description,
+    ).await;
+
+    // expect error when there is nothing to cancel
+    let err = cancel_owner_transfer_expect_fail(
+        &mut ctx.banks_client,
+        &ctx.payer,
+        &ctx.last_blockhash,
+        &program_id,
+        &poll_pda,
+    ).await.unwrap();
+    assert_custom_error(err, JanecekError::NoPendingOwnerTransfer);
+
+    // owner nominates and then withdraws the nomination
+    initiate_owner_transfer(
+        &mut ctx.banks_client,
+        &ctx.payer,
+        &ctx.last_blockhash,
+        &program_id,
+        &new_owner.pubkey(),
+        &poll_pda,
+    ).await;
+
+    // expect error when the nominee cancels
+    let err = cancel_owner_transfer_expect_fail(
+        &mut ctx.banks_client,
+        &new_owner,
+        &ctx.last_blockhash,
+        &program_id,
+        &poll_pda,
+    ).await.unwrap();
+
+    match err {
+        TransactionError::InstructionError(_, InstructionError::IllegalOwner) => {}
+        other => panic!("Unexpected transport error type: {:?}", other),
+    }
+
+    // update blockhash
+    ctx.last_blockhash = ctx.get_new_latest_blockhash().await.unwrap();
+
+    cancel_owner_transfer(
+        &mut ctx.banks_client,
+        &ctx.payer,
+        &ctx.last_blockhash,
+        &program_id,
+        &poll_pda,
+    ).await;
+
+    let poll_state = PollState::deserialize(&mut &ctx.banks_client.get_account(poll_pda).await.unwrap().unwrap().data[..]).unwrap();
+    assert_eq!(poll_state.owner, ctx.payer.pubkey());
+    assert_eq!(poll_state.expected_new_owner, Pubkey::default());
+
+    // expect error when accepting a cancelled nomination
+    let err = accept_owner_transfer_expect_fail(
+        &mut ctx.banks_client,
+        &new_owner,
+        &ctx.last_blockhash,
+        &program_id,
+        &poll_pda,
+    ).await.unwrap();
+
+    match err {
+        TransactionError::InstructionError(_, InstructionError::IllegalOwner) => {}
+        other => panic!("Unexpected transport error type: {:?}", other),
+    }
+}
+
+
+#[tokio::test]
+async fn test_owner_transfer_expiry() {
+    let (mut ctx, program_id) = setup_test_env().await;
+
+    let title = "Presidential Election".to_string();
+    let description = "Vote for the next president".to_string();
+    let new_owner = Keypair::new();
+
+    // transfer SOL to new owner
+    let transfer_ix = system_instruction::transfer(
+        &ctx.payer.pubkey(),
+        &new_owner.pubkey(),
+        1_000_000_000, // 1 SOL
+    );
+    let tx = Transaction::new_signed_with_payer(
+        &[transfer_ix],
+        Some(&ctx.payer.pubkey()),
+        &[&ctx.payer],
+        ctx.last_blockhash,
+    );
+    ctx.banks_client.process_transaction(tx).await.unwrap();
+
+    // create poll
+    let poll_pda = create_poll(
+        &mut ctx.banks_client,
+        &ctx.payer,
+        &ctx.last_blockhash,
+        &program_id,
+        &title,
+        &description,
+    ).await;
+
+    initiate_owner_transfer(
+        &mut ctx.banks_client,
+        &ctx.payer,
+        &ctx.last_blockhash,
+        &program_id,
+        &new_owner.pubkey(),
+        &poll_pda,
+    ).await;
+
+    let clock: Clock = ctx.banks_client.get_sysvar().await.unwrap();
+    let poll_state = PollState::deserialize(&mut &ctx.banks_client.get_account(poll_pda).await.unwrap().unwrap().data[..]).unwrap();
+    assert_eq!(poll_state.owner_transfer_expires_at, clock.unix_timestamp + PollState::OWNER_TRANSFER_DURATION);
+
+    // update timestamp past the transfer deadline
+    let mut clock: Clock = ctx.banks_client.get_sysvar().await.unwrap();
+    clock.unix_timestamp += PollState::OWNER_TRANSFER_DURATION + 60;
+    ctx.set_sysvar(&clock);
+
+    // expect error when accepting an expired nomination
+    let err = accept_owner_transfer_expect_fail(
+        &mut ctx.banks_client,
+        &new_owner,
+        &ctx.last_blockhash,
+        &program_id,
+        &poll_pda,
+    ).await.unwrap();
+    assert_custom_error(err, JanecekError::OwnerTransferExpired);
+
+    // update blockhash
+    ctx.last_blockhash = ctx.get_new_latest_blockhash().await.unwrap();
+
+    // owner nominates again and the new owner accepts in time
+    initiate_owner_transfer(
+        &mut ctx.banks_client,
+        &ctx.payer,
+        &ctx.last_blockhash,
+        &program_id,
+        &new_owner.pubkey(),
+        &poll_pda,
+    ).await;
+
+    accept_owner_transfer(
+        &mut ctx.banks_client,
+        &new_owner,
+        &ctx.last_blockhash,
+        &program_id,
+        &poll_pda,
+    ).await;
+
+    // update blockhash
+    ctx.last_blockhash = ctx.get_new_latest_blockhash().await.unwrap();
+
+    // expect error when the same nomination is accepted twice
+    let err = accept_owner_transfer_expect_fail(
+        &mut ctx.banks_client,
+        &new_owner,
+        &ctx.last_blockhash,
+        &program_id,
+        &poll_pda,
+    ).await.unwrap();
+
+    match err {
+        TransactionError::InstructionError(_, InstructionError::IllegalOwner) => {}
+        other => panic!("Unexpected transport error type: {:?}", other),
+    }
+}
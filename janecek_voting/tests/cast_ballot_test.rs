@@ -0,0 +1,143 @@
+mod helpers;
+use helpers::{setup_test_env, setup_voting_poll, assert_custom_error, vote, cast_ballot, cast_ballot_expect_fail, cast_ballot_with_parties_expect_fail};
+use janecek_voting::{error::JanecekError, state::{BallotEntry, PartyAccount, VoteType, VoterAccount}};
+use solana_sdk::pubkey::Pubkey;
+
+
+fn entry(party: Pubkey, vote_type: VoteType) -> BallotEntry {
+    BallotEntry { party, vote_type }
+}
+
+
+#[tokio::test]
+async fn test_cast_ballot_success() {
+    let (mut ctx, program_id) = setup_test_env().await;
+    let (poll_pda, party_pdas) = setup_voting_poll(&mut ctx, &program_id, 4).await;
+
+    // -C +A +B, the negative vote comes first but is covered by the positives of the same ballot
+    let voter_pda = cast_ballot(
+        &mut ctx.banks_client,
+        &ctx.payer,
+        &ctx.last_blockhash,
+        &program_id,
+        &poll_pda,
+        &[
+            entry(party_pdas[2], VoteType::Negative),
+            entry(party_pdas[0], VoteType::Positive),
+            entry(party_pdas[1], VoteType::Positive),
+        ],
+    ).await;
+
+    for (party_pda, positive, negative) in [(party_pdas[0], 1, 0), (party_pdas[1], 1, 0), (party_pdas[2], 0, 1), (party_pdas[3], 0, 0)] {
+        let party_state = *PartyAccount::from_bytes(&ctx.banks_client.get_account(party_pda).await.unwrap().unwrap().data);
+        assert_eq!(party_state.positive_votes, positive);
+        assert_eq!(party_state.negative_votes, negative);
+    }
+
+    let voter_account = ctx.banks_client.get_account(voter_pda).await.unwrap().unwrap();
+    let (voter_state, voted_parties) = VoterAccount::unpack(&voter_account.data);
+
+    assert_eq!(voter_state.positive_used, 2);
+    assert_eq!(voter_state.negative_used, 1);
+    assert_eq!(voted_parties.len(), 3);
+    assert_eq!(voted_parties[0].party, party_pdas[2]);
+    assert_eq!(voted_parties[0].vote_type(), VoteType::Negative);
+}
+
+
+#[tokio::test]
+async fn test_cast_ballot_fail() {
+    let (mut ctx, program_id) = setup_test_env().await;
+    let (poll_pda, party_pdas) = setup_voting_poll(&mut ctx, &program_id, 4).await;
+
+    // expect error when the negative vote is not covered by the positives of the ballot
+    let err = cast_ballot_expect_fail(
+        &mut ctx.banks_client,
+        &ctx.payer,
+        &ctx.last_blockhash,
+        &program_id,
+        &poll_pda,
+        &[entry(party_pdas[0], VoteType::Positive), entry(party_pdas[2], VoteType::Negative)],
+    ).await.unwrap();
+    assert_custom_error(err, JanecekError::MustUseAllPositiveVoices);
+
+    // expect error when the ballot uses more positive votes than the rules allow
+    let err = cast_ballot_expect_fail(
+        &mut ctx.banks_client,
+        &ctx.payer,
+        &ctx.last_blockhash,
+        &program_id,
+        &poll_pda,
+        &[
+            entry(party_pdas[0], VoteType::Positive),
+            entry(party_pdas[1], VoteType::Positive),
+            entry(party_pdas[2], VoteType::Positive),
+        ],
+    ).await.unwrap();
+    assert_custom_error(err, JanecekError::NoPositiveVoice);
+
+    // expect error when a party appears twice
+    let err = cast_ballot_expect_fail(
+        &mut ctx.banks_client,
+        &ctx.payer,
+        &ctx.last_blockhash,
+        &program_id,
+        &poll_pda,
+        &[entry(party_pdas[0], VoteType::Positive), entry(party_pdas[0], VoteType::Positive)],
+    ).await.unwrap();
+    assert_custom_error(err, JanecekError::AlreadyVoted);
+
+    // expect error when the party accounts do not follow the ballot
+    let err = cast_ballot_with_parties_expect_fail(
+        &mut ctx.banks_client,
+        &ctx.payer,
+        &ctx.last_blockhash,
+        &program_id,
+        &poll_pda,
+        &[entry(party_pdas[0], VoteType::Positive), entry(party_pdas[1], VoteType::Positive)],
+        &[party_pdas[1], party_pdas[0]],
+    ).await.unwrap();
+    assert_custom_error(err, JanecekError::PartyListMismatch);
+
+    // rejected ballots leave no votes behind
+    for party_pda in &party_pdas {
+        let party_state = *PartyAccount::from_bytes(&ctx.banks_client.get_account(*party_pda).await.unwrap().unwrap().data);
+        assert_eq!(party_state.positive_votes, 0);
+        assert_eq!(party_state.negative_votes, 0);
+    }
+
+    // +A
+    vote(
+        &mut ctx.banks_client,
+        &ctx.payer,
+        &ctx.last_blockhash,
+        &program_id,
+        &poll_pda,
+        &party_pdas[0],
+        VoteType::Positive,
+    ).await;
+
+    // expect error when the ballot repeats a party voted for earlier
+    let err = cast_ballot_expect_fail(
+        &mut ctx.banks_client,
+        &ctx.payer,
+        &ctx.last_blockhash,
+        &program_id,
+        &poll_pda,
+        &[entry(party_pdas[1], VoteType::Positive), entry(party_pdas[0], VoteType::Positive)],
+    ).await.unwrap();
+    assert_custom_error(err, JanecekError::AlreadyVoted);
+
+    // +B -C completes the ballot started with the single vote
+    cast_ballot(
+        &mut ctx.banks_client,
+        &ctx.payer,
+        &ctx.last_blockhash,
+        &program_id,
+        &poll_pda,
+        &[entry(party_pdas[2], VoteType::Negative), entry(party_pdas[1], VoteType::Positive)],
+    ).await;
+
+    let party_state_c = *PartyAccount::from_bytes(&ctx.banks_client.get_account(party_pdas[2]).await.unwrap().unwrap().data);
+    assert_eq!(party_state_c.negative_votes, 1);
+}
@@ -0,0 +1,290 @@
+mod helpers;
+use helpers::{
+    setup_test_env,
+    assert_custom_error,
+    create_poll_with_params,
+    create_party,
+    start_voting,
+    vote,
+    end_voting,
+    close_poll,
+    close_poll_expect_fail,
+    close_party,
+    close_party_expect_fail,
+    close_voter,
+    close_eligible_voter,
+    register_voter,
+    unregister_voter,
+    PollParams,
+};
+use janecek_voting::{error::JanecekError, state::{PollResults, PollState, VoteType}};
+use borsh::{BorshDeserialize};
+use solana_sdk::{clock::Clock, signature::Keypair, signer::Signer};
+
+
+#[tokio::test]
+async fn test_close_accounts_after_archive_period() {
+    let (mut ctx, program_id) = setup_test_env().await;
+
+    let title = "Presidential Election".to_string();
+    let description = "Vote for the next president".to_string();
+
+    // create poll with a one day archive period
+    let poll_pda = create_poll_with_params(
+        &mut ctx.banks_client,
+        &ctx.payer,
+        &ctx.last_blockhash,
+        &program_id,
+        &title,
+        &description,
+        PollParams {
+            archive_period: 60 * 60 * 24,
+            ..PollParams::default()
+        },
+    ).await;
+
+    // create parties
+    let mut party_pdas = Vec::new();
+    for party_title in ["Party A", "Party B"] {
+        let party_pda = create_party(
+            &mut ctx.banks_client,
+            &ctx.payer,
+            &ctx.last_blockhash,
+            &program_id,
+            party_title,
+            &poll_pda,
+        ).await;
+        party_pdas.push(party_pda);
+    }
+
+    // update timestamp to 24 hours and 1 minute
+    let mut clock: Clock = ctx.banks_client.get_sysvar().await.unwrap();
+    clock.unix_timestamp += (60 * 60 * 24) + 60;
+    ctx.set_sysvar(&clock);
+
+    // start voting
+    start_voting(
+        &mut ctx.banks_client,
+        &ctx.payer,
+        &ctx.last_blockhash,
+        &program_id,
+        &poll_pda,
+    ).await;
+
+    // +A
+    let voter_pda = vote(
+        &mut ctx.banks_client,
+        &ctx.payer,
+        &ctx.last_blockhash,
+        &program_id,
+        &poll_pda,
+        &party_pdas[0],
+        VoteType::Positive,
+    ).await;
+
+    // expect error when closing a party before the results
+    let err = close_party_expect_fail(
+        &mut ctx.banks_client,
+        &ctx.payer,
+        &ctx.last_blockhash,
+        &program_id,
+        &poll_pda,
+        &party_pdas[0],
+    ).await.unwrap();
+    assert_custom_error(err, JanecekError::VotingPeriodNotFinished);
+
+    // owner ends voting
+    let results_pda = end_voting(
+        &mut ctx.banks_client,
+        &ctx.payer,
+        &ctx.last_blockhash,
+        &program_id,
+        &poll_pda,
+        &party_pdas,
+    ).await;
+
+    let poll_state = PollState::deserialize(&mut &ctx.banks_client.get_account(poll_pda).await.unwrap().unwrap().data[..]).unwrap();
+    assert_eq!(poll_state.ended_at, clock.unix_timestamp);
+    assert_eq!(poll_state.rent_payer, ctx.payer.pubkey());
+
+    let results_state = PollResults::try_from_slice(&ctx.banks_client.get_account(results_pda).await.unwrap().unwrap().data).unwrap();
+    assert_eq!(results_state.rent_payer, ctx.payer.pubkey());
+
+    // update blockhash
+    ctx.last_blockhash = ctx.get_new_latest_blockhash().await.unwrap();
+
+    // expect error when closing a party during the archive period
+    let err = close_party_expect_fail(
+        &mut ctx.banks_client,
+        &ctx.payer,
+        &ctx.last_blockhash,
+        &program_id,
+        &poll_pda,
+        &party_pdas[0],
+    ).await.unwrap();
+    assert_custom_error(err, JanecekError::ArchivePeriodNotFinished);
+
+    // update timestamp past the archive period
+    let mut clock: Clock = ctx.banks_client.get_sysvar().await.unwrap();
+    clock.unix_timestamp += (60 * 60 * 24) + 60;
+    ctx.set_sysvar(&clock);
+
+    // expect error when closing the poll while parties still point at it
+    let err = close_poll_expect_fail(
+        &mut ctx.banks_client,
+        &ctx.payer,
+        &ctx.last_blockhash,
+        &program_id,
+        &poll_pda,
+    ).await.unwrap();
+    assert_custom_error(err, JanecekError::PartiesNotClosed);
+
+    // update blockhash
+    ctx.last_blockhash = ctx.get_new_latest_blockhash().await.unwrap();
+
+    // close parties, voter and poll
+    for party_pda in &party_pdas {
+        close_party(
+            &mut ctx.banks_client,
+            &ctx.payer,
+            &ctx.last_blockhash,
+            &program_id,
+            &poll_pda,
+            party_pda,
+        ).await;
+    }
+
+    close_voter(
+        &mut ctx.banks_client,
+        &ctx.payer,
+        &ctx.last_blockhash,
+        &program_id,
+        &poll_pda,
+    ).await;
+
+    let balance_before = ctx.banks_client.get_balance(ctx.payer.pubkey()).await.unwrap();
+    let poll_lamports = ctx.banks_client.get_balance(poll_pda).await.unwrap()
+        + ctx.banks_client.get_balance(results_pda).await.unwrap();
+
+    close_poll(
+        &mut ctx.banks_client,
+        &ctx.payer,
+        &ctx.last_blockhash,
+        &program_id,
+        &poll_pda,
+    ).await;
+
+    // rent of the poll and its results went back to the payer (minus the transaction fee)
+    let balance_after = ctx.banks_client.get_balance(ctx.payer.pubkey()).await.unwrap();
+    assert!(balance_after > balance_before + poll_lamports - 10_000);
+
+    for closed_pda in [poll_pda, results_pda, party_pdas[0], party_pdas[1], voter_pda] {
+        assert!(ctx.banks_client.get_account(closed_pda).await.unwrap().is_none());
+    }
+}
+
+
+#[tokio::test]
+async fn test_close_eligible_voters() {
+    let (mut ctx, program_id) = setup_test_env().await;
+    let members = [Keypair::new(), Keypair::new()];
+
+    // create closed poll
+    let poll_pda = create_poll_with_params(
+        &mut ctx.banks_client,
+        &ctx.payer,
+        &ctx.last_blockhash,
+        &program_id,
+        "Board Election",
+        "Internal vote of the team",
+        PollParams { closed: true, ..PollParams::default() },
+    ).await;
+
+    let mut eligible_pdas = Vec::new();
+    for member in &members {
+        eligible_pdas.push(register_voter(
+            &mut ctx.banks_client,
+            &ctx.payer,
+            &ctx.last_blockhash,
+            &program_id,
+            &poll_pda,
+            &member.pubkey(),
+        ).await);
+    }
+
+    // unregistering refunds the owner right away
+    unregister_voter(
+        &mut ctx.banks_client,
+        &ctx.payer,
+        &ctx.last_blockhash,
+        &program_id,
+        &poll_pda,
+        &members[1].pubkey(),
+    ).await;
+
+    // update timestamp to 24 hours and 1 minute
+    let mut clock: Clock = ctx.banks_client.get_sysvar().await.unwrap();
+    clock.unix_timestamp += (60 * 60 * 24) + 60;
+    ctx.set_sysvar(&clock);
+
+    start_voting(
+        &mut ctx.banks_client,
+        &ctx.payer,
+        &ctx.last_blockhash,
+        &program_id,
+        &poll_pda,
+    ).await;
+
+    end_voting(
+        &mut ctx.banks_client,
+        &ctx.payer,
+        &ctx.last_blockhash,
+        &program_id,
+        &poll_pda,
+        &[],
+    ).await;
+
+    // expect error when closing the poll while registered voters still point at it
+    let err = close_poll_expect_fail(
+        &mut ctx.banks_client,
+        &ctx.payer,
+        &ctx.last_blockhash,
+        &program_id,
+        &poll_pda,
+    ).await.unwrap();
+    assert_custom_error(err, JanecekError::EligibleVotersNotClosed);
+
+    let balance_before = ctx.banks_client.get_balance(ctx.payer.pubkey()).await.unwrap();
+    let eligible_lamports = ctx.banks_client.get_balance(eligible_pdas[0]).await.unwrap();
+
+    close_eligible_voter(
+        &mut ctx.banks_client,
+        &ctx.payer,
+        &ctx.last_blockhash,
+        &program_id,
+        &poll_pda,
+        &members[0].pubkey(),
+    ).await;
+
+    // rent went back to the owner (minus the transaction fees)
+    let balance_after = ctx.banks_client.get_balance(ctx.payer.pubkey()).await.unwrap();
+    assert!(balance_after > balance_before + eligible_lamports - 20_000);
+
+    let poll_state = PollState::deserialize(&mut &ctx.banks_client.get_account(poll_pda).await.unwrap().unwrap().data[..]).unwrap();
+    assert_eq!(poll_state.eligible_voters, 0);
+
+    // update blockhash
+    ctx.last_blockhash = ctx.get_new_latest_blockhash().await.unwrap();
+
+    close_poll(
+        &mut ctx.banks_client,
+        &ctx.payer,
+        &ctx.last_blockhash,
+        &program_id,
+        &poll_pda,
+    ).await;
+
+    for closed_pda in [poll_pda, eligible_pdas[0], eligible_pdas[1]] {
+        assert!(ctx.banks_client.get_account(closed_pda).await.unwrap().is_none());
+    }
+}
@@ -0,0 +1,592 @@
+mod helpers;
+use helpers::{
+    setup_test_env,
+    assert_custom_error,
+    create_poll,
+    create_poll_with_params,
+    create_party_expect_fail,
+    update_poll_expect_fail,
+    register_voter_expect_fail,
+    set_poll_paused_expect_fail,
+    remove_party_expect_fail,
+    set_committee,
+    set_committee_expect_fail,
+    propose_action,
+    approve_proposal,
+    approve_proposal_expect_fail,
+    execute_with_proposal,
+    close_proposal,
+    close_proposal_expect_fail,
+    PartyMetadata,
+    PollParams,
+};
+use janecek_voting::{error::JanecekError, state::{Committee, PartyAccount, PollState, Proposal, ProposalAction, VotingPhase}};
+use borsh::{BorshDeserialize};
+use solana_program::hash::hash;
+use solana_program_test::ProgramTestContext;
+use solana_sdk::{clock::Clock, pubkey::Pubkey, signature::Keypair, signer::Signer, system_instruction, transaction::{Transaction, TransactionError}, instruction::InstructionError};
+
+
+fn assert_not_enough_keys(err: TransactionError) {
+    match err {
+        TransactionError::InstructionError(_, InstructionError::NotEnoughAccountKeys) => {}
+        other => panic!("Unexpected transport error type: {:?}", other),
+    }
+}
+
+// Alice proposes, the payer approves and executes
+async fn run_proposal(ctx: &mut ProgramTestContext, program_id: &Pubkey, poll_pda: &Pubkey, alice: &Keypair, action: ProposalAction) {
+    let proposal_pda = propose_action(
+        &mut ctx.banks_client,
+        alice,
+        &ctx.last_blockhash,
+        program_id,
+        poll_pda,
+        action.clone(),
+    ).await;
+
+    approve_proposal(
+        &mut ctx.banks_client,
+        &ctx.payer,
+        &ctx.last_blockhash,
+        program_id,
+        poll_pda,
+        &proposal_pda,
+    ).await;
+
+    execute_with_proposal(
+        &mut ctx.banks_client,
+        &ctx.payer,
+        &ctx.last_blockhash,
+        program_id,
+        poll_pda,
+        &proposal_pda,
+        &action,
+        &[],
+    ).await.unwrap();
+}
+
+
+#[tokio::test]
+async fn test_set_committee() {
+    let (mut ctx, program_id) = setup_test_env().await;
+
+    let title = "Presidential Election".to_string();
+    let description = "Vote for the next president".to_string();
+    let outsider = Keypair::new();
+
+    // transfer SOL to outsider
+    let transfer_ix = system_instruction::transfer(
+        &ctx.payer.pubkey(),
+        &outsider.pubkey(),
+        1_000_000_000, // 1 SOL
+    );
+    let tx = Transaction::new_signed_with_payer(
+        &[transfer_ix],
+        Some(&ctx.payer.pubkey()),
+        &[&ctx.payer],
+        ctx.last_blockhash,
+    );
+    ctx.banks_client.process_transaction(tx).await.unwrap();
+
+    // create poll
+    let poll_pda = create_poll(
+        &mut ctx.banks_client,
+        &ctx.payer,
+        &ctx.last_blockhash,
+        &program_id,
+        &title,
+        &description,
+    ).await;
+
+    let committee = Committee {
+        members: vec![ctx.payer.pubkey(), outsider.pubkey()],
+        threshold: 2,
+    };
+
+    // expect error when someone other than the owner sets the committee
+    let err = set_committee_expect_fail(
+        &mut ctx.banks_client,
+        &outsider,
+        &ctx.last_blockhash,
+        &program_id,
+        &poll_pda,
+        committee.clone(),
+    ).await.unwrap();
+
+    match err {
+        TransactionError::InstructionError(_, InstructionError::IllegalOwner) => {}
+        other => panic!("Unexpected transport error type: {:?}", other),
+    }
+
+    // expect error for unreachable thresholds and duplicate members
+    for invalid_committee in [
+        Committee { members: vec![ctx.payer.pubkey()], threshold: 2 },
+        Committee { members: vec![ctx.payer.pubkey(), ctx.payer.pubkey()], threshold: 2 },
+    ] {
+        let err = set_committee_expect_fail(
+            &mut ctx.banks_client,
+            &ctx.payer,
+            &ctx.last_blockhash,
+            &program_id,
+            &poll_pda,
+            invalid_committee,
+        ).await.unwrap();
+        assert_custom_error(err, JanecekError::InvalidCommittee);
+    }
+
+    set_committee(
+        &mut ctx.banks_client,
+        &ctx.payer,
+        &ctx.last_blockhash,
+        &program_id,
+        &poll_pda,
+        committee.clone(),
+    ).await;
+
+    let poll_account = ctx.banks_client.get_account(poll_pda).await.unwrap().unwrap();
+    let poll_state = PollState::deserialize(&mut &poll_account.data[..]).unwrap();
+    assert_eq!(poll_state.committee, Some(committee.clone()));
+
+    // the poll grew by the committee's room only
+    let rent = ctx.banks_client.get_rent().await.unwrap();
+    assert_eq!(poll_account.data.len(), PollState::SIZE + committee.get_size());
+    assert_eq!(poll_account.lamports, rent.minimum_balance(poll_account.data.len()));
+
+    // expect error when the owner tries to replace the committee alone
+    let err = set_committee_expect_fail(
+        &mut ctx.banks_client,
+        &ctx.payer,
+        &ctx.last_blockhash,
+        &program_id,
+        &poll_pda,
+        Committee { members: vec![ctx.payer.pubkey()], threshold: 1 },
+    ).await.unwrap();
+    assert_custom_error(err, JanecekError::InvalidCommittee);
+}
+
+
+#[tokio::test]
+async fn test_committee_proposals() {
+    let (mut ctx, program_id) = setup_test_env().await;
+
+    let title = "Presidential Election".to_string();
+    let description = "Vote for the next president".to_string();
+    let alice = Keypair::new();
+    let bob = Keypair::new();
+    let outsider = Keypair::new();
+
+    // transfer SOL to committee members and outsider
+    for account in [&alice, &bob, &outsider] {
+        let transfer_ix = system_instruction::transfer(
+            &ctx.payer.pubkey(),
+            &account.pubkey(),
+            1_000_000_000, // 1 SOL
+        );
+        let tx = Transaction::new_signed_with_payer(
+            &[transfer_ix],
+            Some(&ctx.payer.pubkey()),
+            &[&ctx.payer],
+            ctx.last_blockhash,
+        );
+        ctx.banks_client.process_transaction(tx).await.unwrap();
+    }
+
+    // create poll controlled by a 2-of-3 committee
+    let poll_pda = create_poll(
+        &mut ctx.banks_client,
+        &ctx.payer,
+        &ctx.last_blockhash,
+        &program_id,
+        &title,
+        &description,
+    ).await;
+
+    set_committee(
+        &mut ctx.banks_client,
+        &ctx.payer,
+        &ctx.last_blockhash,
+        &program_id,
+        &poll_pda,
+        Committee {
+            members: vec![ctx.payer.pubkey(), alice.pubkey(), bob.pubkey()],
+            threshold: 2,
+        },
+    ).await;
+
+    // expect error when the owner creates a party without a proposal
+    let err = create_party_expect_fail(
+        &mut ctx.banks_client,
+        &ctx.payer,
+        &ctx.last_blockhash,
+        &program_id,
+        "Party A",
+        &poll_pda,
+        PartyMetadata::default(),
+    ).await.unwrap();
+
+    match err {
+        TransactionError::InstructionError(_, InstructionError::NotEnoughAccountKeys) => {}
+        other => panic!("Unexpected transport error type: {:?}", other),
+    }
+
+    // alice proposes a party
+    let create_party_action = ProposalAction::CreateParty {
+        title: "Party A".to_string(),
+        description: String::new(),
+        metadata_uri: String::new(),
+        metadata_hash: [0u8; 32],
+    };
+    let party_proposal_pda = propose_action(
+        &mut ctx.banks_client,
+        &alice,
+        &ctx.last_blockhash,
+        &program_id,
+        &poll_pda,
+        create_party_action.clone(),
+    ).await;
+
+    // expect error when executing with only the proposer's approval
+    let err = execute_with_proposal(
+        &mut ctx.banks_client,
+        &alice,
+        &ctx.last_blockhash,
+        &program_id,
+        &poll_pda,
+        &party_proposal_pda,
+        &create_party_action,
+        &[],
+    ).await.unwrap_err().unwrap();
+    assert_custom_error(err, JanecekError::ProposalNotApproved);
+
+    // expect error when approving twice or from outside the committee
+    let err = approve_proposal_expect_fail(
+        &mut ctx.banks_client,
+        &alice,
+        &ctx.last_blockhash,
+        &program_id,
+        &poll_pda,
+        &party_proposal_pda,
+    ).await.unwrap();
+    assert_custom_error(err, JanecekError::AlreadyApproved);
+
+    let err = approve_proposal_expect_fail(
+        &mut ctx.banks_client,
+        &outsider,
+        &ctx.last_blockhash,
+        &program_id,
+        &poll_pda,
+        &party_proposal_pda,
+    ).await.unwrap();
+    assert_custom_error(err, JanecekError::NotCommitteeMember);
+
+    // bob approves in a separate transaction
+    approve_proposal(
+        &mut ctx.banks_client,
+        &bob,
+        &ctx.last_blockhash,
+        &program_id,
+        &poll_pda,
+        &party_proposal_pda,
+    ).await;
+
+    let proposal = Proposal::deserialize(&mut &ctx.banks_client.get_account(party_proposal_pda).await.unwrap().unwrap().data[..]).unwrap();
+    assert_eq!(proposal.approval_count(), 2);
+
+    // expect error when the proposal is used for another action
+    let err = execute_with_proposal(
+        &mut ctx.banks_client,
+        &ctx.payer,
+        &ctx.last_blockhash,
+        &program_id,
+        &poll_pda,
+        &party_proposal_pda,
+        &ProposalAction::StartVoting,
+        &[],
+    ).await.unwrap_err().unwrap();
+    assert_custom_error(err, JanecekError::ProposalMismatch);
+
+    // update blockhash
+    ctx.last_blockhash = ctx.get_new_latest_blockhash().await.unwrap();
+
+    execute_with_proposal(
+        &mut ctx.banks_client,
+        &alice,
+        &ctx.last_blockhash,
+        &program_id,
+        &poll_pda,
+        &party_proposal_pda,
+        &create_party_action,
+        &[],
+    ).await.unwrap();
+
+    let poll_state = PollState::deserialize(&mut &ctx.banks_client.get_account(poll_pda).await.unwrap().unwrap().data[..]).unwrap();
+    assert_eq!(poll_state.party_counter, 1);
+
+    // expect error when the same proposal runs twice
+    let err = execute_with_proposal(
+        &mut ctx.banks_client,
+        &bob,
+        &ctx.last_blockhash,
+        &program_id,
+        &poll_pda,
+        &party_proposal_pda,
+        &create_party_action,
+        &[],
+    ).await.unwrap_err().unwrap();
+    assert_custom_error(err, JanecekError::ProposalAlreadyExecuted);
+
+    // expect error when someone other than the proposer closes the proposal
+    let err = close_proposal_expect_fail(
+        &mut ctx.banks_client,
+        &bob,
+        &ctx.last_blockhash,
+        &program_id,
+        &poll_pda,
+        &party_proposal_pda,
+    ).await.unwrap();
+
+    match err {
+        TransactionError::InstructionError(_, InstructionError::IllegalOwner) => {}
+        other => panic!("Unexpected transport error type: {:?}", other),
+    }
+
+    // executed proposals can go right away, the proposer gets the rent back
+    let alice_balance = ctx.banks_client.get_balance(alice.pubkey()).await.unwrap();
+    let proposal_lamports = ctx.banks_client.get_balance(party_proposal_pda).await.unwrap();
+
+    close_proposal(
+        &mut ctx.banks_client,
+        &alice,
+        &ctx.last_blockhash,
+        &program_id,
+        &poll_pda,
+        &party_proposal_pda,
+    ).await;
+
+    assert!(ctx.banks_client.get_account(party_proposal_pda).await.unwrap().is_none());
+    assert!(ctx.banks_client.get_balance(alice.pubkey()).await.unwrap() > alice_balance + proposal_lamports - 10_000);
+
+    // update timestamp to 24 hours and 1 minute
+    let mut clock: Clock = ctx.banks_client.get_sysvar().await.unwrap();
+    clock.unix_timestamp += (60 * 60 * 24) + 60;
+    ctx.set_sysvar(&clock);
+
+    // party rent was paid by the executing member
+    let (party_pda, _bump_seeds) = Pubkey::find_program_address(
+        &[b"party", poll_pda.as_ref(), hash(b"Party A").as_ref()],
+        &program_id,
+    );
+    let party_pdas = vec![party_pda];
+    let party_state = *PartyAccount::from_bytes(&ctx.banks_client.get_account(party_pdas[0]).await.unwrap().unwrap().data);
+    assert_eq!(party_state.rent_payer, alice.pubkey());
+
+    // committee starts voting and ends it early
+    let mut proposal_pdas = Vec::new();
+    for action in [ProposalAction::StartVoting, ProposalAction::EndVoting] {
+        let proposal_pda = propose_action(
+            &mut ctx.banks_client,
+            &ctx.payer,
+            &ctx.last_blockhash,
+            &program_id,
+            &poll_pda,
+            action.clone(),
+        ).await;
+        proposal_pdas.push(proposal_pda);
+
+        // expect error when closing a pending proposal of a running poll
+        let err = close_proposal_expect_fail(
+            &mut ctx.banks_client,
+            &ctx.payer,
+            &ctx.last_blockhash,
+            &program_id,
+            &poll_pda,
+            &proposal_pda,
+        ).await.unwrap();
+        assert_custom_error(err, JanecekError::VotingPeriodNotFinished);
+
+        approve_proposal(
+            &mut ctx.banks_client,
+            &bob,
+            &ctx.last_blockhash,
+            &program_id,
+            &poll_pda,
+            &proposal_pda,
+        ).await;
+
+        execute_with_proposal(
+            &mut ctx.banks_client,
+            &bob,
+            &ctx.last_blockhash,
+            &program_id,
+            &poll_pda,
+            &proposal_pda,
+            &action,
+            &party_pdas,
+        ).await.unwrap();
+    }
+
+    let poll_state = PollState::deserialize(&mut &ctx.banks_client.get_account(poll_pda).await.unwrap().unwrap().data[..]).unwrap();
+    assert!(matches!(poll_state.phase, VotingPhase::Results));
+    assert_eq!(poll_state.proposal_counter, 3);
+
+    // update blockhash
+    ctx.last_blockhash = ctx.get_new_latest_blockhash().await.unwrap();
+
+    for proposal_pda in &proposal_pdas {
+        close_proposal(
+            &mut ctx.banks_client,
+            &ctx.payer,
+            &ctx.last_blockhash,
+            &program_id,
+            &poll_pda,
+            proposal_pda,
+        ).await;
+        assert!(ctx.banks_client.get_account(*proposal_pda).await.unwrap().is_none());
+    }
+}
+
+
+#[tokio::test]
+async fn test_committee_owner_actions() {
+    let (mut ctx, program_id) = setup_test_env().await;
+    let alice = Keypair::new();
+    let voter = Keypair::new();
+
+    // transfer SOL to alice
+    let transfer_ix = system_instruction::transfer(
+        &ctx.payer.pubkey(),
+        &alice.pubkey(),
+        1_000_000_000, // 1 SOL
+    );
+    let tx = Transaction::new_signed_with_payer(
+        &[transfer_ix],
+        Some(&ctx.payer.pubkey()),
+        &[&ctx.payer],
+        ctx.last_blockhash,
+    );
+    ctx.banks_client.process_transaction(tx).await.unwrap();
+
+    // closed poll controlled by a 2-of-2 committee
+    let poll_pda = create_poll_with_params(
+        &mut ctx.banks_client,
+        &ctx.payer,
+        &ctx.last_blockhash,
+        &program_id,
+        "Presidential Election",
+        "Vote for the next president",
+        PollParams {
+            closed: true,
+            ..PollParams::default()
+        },
+    ).await;
+
+    set_committee(
+        &mut ctx.banks_client,
+        &ctx.payer,
+        &ctx.last_blockhash,
+        &program_id,
+        &poll_pda,
+        Committee {
+            members: vec![ctx.payer.pubkey(), alice.pubkey()],
+            threshold: 2,
+        },
+    ).await;
+
+    run_proposal(&mut ctx, &program_id, &poll_pda, &alice, ProposalAction::CreateParty {
+        title: "Party A".to_string(),
+        description: String::new(),
+        metadata_uri: String::new(),
+        metadata_hash: [0u8; 32],
+    }).await;
+
+    let (party_pda, _bump_seeds) = Pubkey::find_program_address(
+        &[b"party", poll_pda.as_ref(), hash(b"Party A").as_ref()],
+        &program_id,
+    );
+
+    // expect error when the owner skips the committee
+    let err = update_poll_expect_fail(
+        &mut ctx.banks_client,
+        &ctx.payer,
+        &ctx.last_blockhash,
+        &program_id,
+        &poll_pda,
+        "Snap Election",
+        "Vote for the next president",
+    ).await.unwrap();
+    assert_not_enough_keys(err);
+
+    let err = register_voter_expect_fail(
+        &mut ctx.banks_client,
+        &ctx.payer,
+        &ctx.last_blockhash,
+        &program_id,
+        &poll_pda,
+        &voter.pubkey(),
+    ).await.unwrap();
+    assert_not_enough_keys(err);
+
+    let err = set_poll_paused_expect_fail(
+        &mut ctx.banks_client,
+        &ctx.payer,
+        &ctx.last_blockhash,
+        &program_id,
+        &poll_pda,
+        true,
+        false,
+    ).await.unwrap();
+    assert_not_enough_keys(err);
+
+    let err = remove_party_expect_fail(
+        &mut ctx.banks_client,
+        &ctx.payer,
+        &ctx.last_blockhash,
+        &program_id,
+        &poll_pda,
+        &party_pda,
+    ).await.unwrap();
+    assert_not_enough_keys(err);
+
+    // committee runs the same actions through proposals
+    run_proposal(&mut ctx, &program_id, &poll_pda, &alice, ProposalAction::UpdatePartyMetadata {
+        party: party_pda,
+        description: "Fiscal conservatives".to_string(),
+        metadata_uri: String::new(),
+        metadata_hash: [0u8; 32],
+    }).await;
+
+    let party_state = *PartyAccount::from_bytes(&ctx.banks_client.get_account(party_pda).await.unwrap().unwrap().data);
+    assert_eq!(party_state.description(), "Fiscal conservatives");
+
+    run_proposal(&mut ctx, &program_id, &poll_pda, &alice, ProposalAction::UpdatePoll {
+        title: "Snap Election".to_string(),
+        description: "Vote for the next president".to_string(),
+    }).await;
+
+    let poll_state = PollState::deserialize(&mut &ctx.banks_client.get_account(poll_pda).await.unwrap().unwrap().data[..]).unwrap();
+    assert_eq!(poll_state.title, "Snap Election");
+
+    run_proposal(&mut ctx, &program_id, &poll_pda, &alice, ProposalAction::RegisterVoter { voter: voter.pubkey() }).await;
+
+    let poll_state = PollState::deserialize(&mut &ctx.banks_client.get_account(poll_pda).await.unwrap().unwrap().data[..]).unwrap();
+    assert_eq!(poll_state.eligible_voters, 1);
+
+    run_proposal(&mut ctx, &program_id, &poll_pda, &alice, ProposalAction::UnregisterVoter { voter: voter.pubkey() }).await;
+
+    let poll_state = PollState::deserialize(&mut &ctx.banks_client.get_account(poll_pda).await.unwrap().unwrap().data[..]).unwrap();
+    assert_eq!(poll_state.eligible_voters, 0);
+
+    for paused in [true, false] {
+        run_proposal(&mut ctx, &program_id, &poll_pda, &alice, ProposalAction::SetPollPaused { paused, extend_deadline: false }).await;
+
+        let poll_state = PollState::deserialize(&mut &ctx.banks_client.get_account(poll_pda).await.unwrap().unwrap().data[..]).unwrap();
+        assert_eq!(poll_state.paused, paused);
+    }
+
+    run_proposal(&mut ctx, &program_id, &poll_pda, &alice, ProposalAction::RemoveParty { party: party_pda }).await;
+
+    assert!(ctx.banks_client.get_account(party_pda).await.unwrap().is_none());
+    let poll_state = PollState::deserialize(&mut &ctx.banks_client.get_account(poll_pda).await.unwrap().unwrap().data[..]).unwrap();
+    assert_eq!(poll_state.party_counter, 0);
+}
@@ -0,0 +1,137 @@
+// Native runs do not meter the program and report about the same units for every instruction,
+// the numbers are only compared under `cargo test-sbf`
+mod helpers;
+use helpers::{setup_test_env, create_poll, create_party, start_voting, vote, vote_instruction, retract_vote_instruction, simulate_compute_units};
+use janecek_voting::state::{PartyAccount, PartyAccountV0, VoterAccount, VoterAccountV0, VoteType};
+use borsh::BorshSerialize;
+use solana_program_test::ProgramTestContext;
+use solana_sdk::{account::Account, clock::Clock, pubkey::Pubkey};
+
+
+// Zeroes the stored PDA bump of the voter, as for voters upgraded from the first release.
+// Returns the account as it was
+async fn forget_voter_bump(ctx: &mut ProgramTestContext, voter_pda: &Pubkey) -> Account {
+    let voter_account = ctx.banks_client.get_account(*voter_pda).await.unwrap().unwrap();
+    let mut account = voter_account.clone();
+    VoterAccount::unpack_mut(&mut account.data).0.bump = 0;
+    ctx.set_account(voter_pda, &account.into());
+
+    voter_account
+}
+
+// Rewrites the voter and a party in the Borsh layouts of the first release, the path every vote took before
+// the fixed layout. Accounts keep their size, so the vote converts them in place instead of growing them
+async fn downgrade_to_first_release(ctx: &mut ProgramTestContext, voter_pda: &Pubkey, party_pda: &Pubkey) {
+    let mut account = ctx.banks_client.get_account(*voter_pda).await.unwrap().unwrap();
+    let (voter, voted_parties) = VoterAccount::unpack(&account.data);
+    let mut data = VoterAccountV0 {
+        discriminator: "voter".to_string(),
+        poll_key: voter.poll_key,
+        voter_key: voter.voter_key,
+        positive_used: voter.positive_used,
+        negative_used: voter.negative_used,
+        voted_parties: voted_parties.iter().map(|voted| voted.party).collect(),
+    }.try_to_vec().unwrap();
+    data.resize(account.data.len(), 0);
+    account.data = data;
+    ctx.set_account(voter_pda, &account.into());
+
+    let mut account = ctx.banks_client.get_account(*party_pda).await.unwrap().unwrap();
+    let party = *PartyAccount::from_bytes(&account.data);
+    let mut data = PartyAccountV0 {
+        discriminator: "party".to_string(),
+        poll_id: party.poll_id,
+        title: party.title().to_string(),
+        positive_votes: party.positive_votes.get(),
+        negative_votes: party.negative_votes.get(),
+    }.try_to_vec().unwrap();
+    data.resize(account.data.len(), 0);
+    account.data = data;
+    ctx.set_account(party_pda, &account.into());
+}
+
+
+#[tokio::test]
+async fn test_vote_compute_units() {
+    let (mut ctx, program_id) = setup_test_env().await;
+
+    // create poll
+    let poll_pda = create_poll(
+        &mut ctx.banks_client,
+        &ctx.payer,
+        &ctx.last_blockhash,
+        &program_id,
+        "Presidential Election",
+        "Vote for the next president",
+    ).await;
+
+    // create parties
+    let mut party_pdas = Vec::new();
+    for title in ["Party A", "Party B", "Party C"] {
+        party_pdas.push(create_party(
+            &mut ctx.banks_client,
+            &ctx.payer,
+            &ctx.last_blockhash,
+            &program_id,
+            title,
+            &poll_pda,
+        ).await);
+    }
+
+    // update timestamp to 24 hours and 1 minute
+    let mut clock: Clock = ctx.banks_client.get_sysvar().await.unwrap();
+    clock.unix_timestamp += (60 * 60 * 24) + 60;
+    ctx.set_sysvar(&clock);
+
+    start_voting(
+        &mut ctx.banks_client,
+        &ctx.payer,
+        &ctx.last_blockhash,
+        &program_id,
+        &poll_pda,
+    ).await;
+
+    // first vote also creates the voter account
+    let (first_vote, _voter_pda) = vote_instruction(&ctx.payer, &program_id, &poll_pda, &party_pdas[0], VoteType::Positive, &[]);
+    let first_vote_units = simulate_compute_units(&mut ctx.banks_client, &ctx.payer, &ctx.last_blockhash, &[first_vote]).await;
+
+    vote(
+        &mut ctx.banks_client,
+        &ctx.payer,
+        &ctx.last_blockhash,
+        &program_id,
+        &poll_pda,
+        &party_pdas[0],
+        VoteType::Positive,
+    ).await;
+
+    // later votes only update the counters in place
+    let (next_vote, voter_pda) = vote_instruction(&ctx.payer, &program_id, &poll_pda, &party_pdas[1], VoteType::Positive, &[]);
+    let next_vote_units = simulate_compute_units(&mut ctx.banks_client, &ctx.payer, &ctx.last_blockhash, std::slice::from_ref(&next_vote)).await;
+
+    // the same vote searching the voter bump instead of reading it from the account
+    let voter_account = forget_voter_bump(&mut ctx, &voter_pda).await;
+    ctx.last_blockhash = ctx.get_new_latest_blockhash().await.unwrap();
+    let searched_bump_units = simulate_compute_units(&mut ctx.banks_client, &ctx.payer, &ctx.last_blockhash, std::slice::from_ref(&next_vote)).await;
+    ctx.set_account(&voter_pda, &voter_account.into());
+
+    let retract = retract_vote_instruction(&ctx.payer, &program_id, &poll_pda, &party_pdas[0]);
+    let retract_units = simulate_compute_units(&mut ctx.banks_client, &ctx.payer, &ctx.last_blockhash, &[retract]).await;
+
+    // the same vote with voter and party decoded from Borsh and rewritten, the poll is decoded on both paths
+    downgrade_to_first_release(&mut ctx, &voter_pda, &party_pdas[1]).await;
+    ctx.last_blockhash = ctx.get_new_latest_blockhash().await.unwrap();
+    let borsh_vote_units = simulate_compute_units(&mut ctx.banks_client, &ctx.payer, &ctx.last_blockhash, &[next_vote]).await;
+
+    println!("first vote:                {} CU", first_vote_units);
+    println!("next vote, fixed layout:   {} CU", next_vote_units);
+    println!("next vote, searched bump:  {} CU", searched_bump_units);
+    println!("next vote, Borsh layouts:  {} CU", borsh_vote_units);
+    println!("retract vote:              {} CU", retract_units);
+
+    if cfg!(feature = "test-sbf") {
+        assert!(next_vote_units < first_vote_units);
+        assert!(next_vote_units < searched_bump_units);
+        assert!(next_vote_units < borsh_vote_units);
+    }
+}
@@ -0,0 +1,272 @@
+mod helpers;
+use helpers::{
+    setup_test_env,
+    start_test_env,
+    initialize_config,
+    create_poll,
+    create_poll_with_params,
+    get_config,
+    initialize_config_expect_fail,
+    update_config,
+    update_config_expect_fail,
+    program_data_pda,
+    assert_custom_error,
+    PollParams,
+};
+use janecek_voting::{error::JanecekError, state::{PollDefaults, PollState, VoteRules}};
+use borsh::{BorshDeserialize};
+use solana_program::bpf_loader_upgradeable;
+use solana_sdk::{account::Account, pubkey::Pubkey, rent::Rent, signature::Keypair, signer::Signer, system_instruction, transaction::{Transaction, TransactionError}, instruction::InstructionError};
+
+
+#[tokio::test]
+async fn test_config_admin_only() {
+    let (mut ctx, program_id) = setup_test_env().await;
+    let outsider = Keypair::new();
+
+    // transfer SOL to outsider
+    let transfer_ix = system_instruction::transfer(
+        &ctx.payer.pubkey(),
+        &outsider.pubkey(),
+        1_000_000_000, // 1 SOL
+    );
+    let tx = Transaction::new_signed_with_payer(
+        &[transfer_ix],
+        Some(&ctx.payer.pubkey()),
+        &[&ctx.payer],
+        ctx.last_blockhash,
+    );
+    ctx.banks_client.process_transaction(tx).await.unwrap();
+
+    let config = get_config(&mut ctx.banks_client, &program_id).await;
+    assert_eq!(config.admin, ctx.payer.pubkey());
+    assert_eq!(config.defaults, PollDefaults::default());
+    assert_eq!(config.creation_fee, 0);
+    assert_eq!(config.poll_count, 0);
+
+    // expect error when the config is initialized a second time
+    ctx.last_blockhash = ctx.get_new_latest_blockhash().await.unwrap();
+    let err = initialize_config_expect_fail(
+        &mut ctx.banks_client,
+        &ctx.payer,
+        &ctx.last_blockhash,
+        &program_id,
+        PollDefaults::default(),
+        0,
+    ).await.unwrap();
+
+    match err {
+        TransactionError::InstructionError(_, InstructionError::AccountAlreadyInitialized) => {}
+        other => panic!("Unexpected transport error type: {:?}", other),
+    }
+
+    // expect error when someone other than the admin updates the config
+    let err = update_config_expect_fail(
+        &mut ctx.banks_client,
+        &outsider,
+        &ctx.last_blockhash,
+        &program_id,
+        &outsider.pubkey(),
+        PollDefaults::default(),
+        0,
+    ).await.unwrap();
+
+    match err {
+        TransactionError::InstructionError(_, InstructionError::IllegalOwner) => {}
+        other => panic!("Unexpected transport error type: {:?}", other),
+    }
+
+    // expect error when the defaults would create invalid polls
+    let err = update_config_expect_fail(
+        &mut ctx.banks_client,
+        &ctx.payer,
+        &ctx.last_blockhash,
+        &program_id,
+        &ctx.payer.pubkey(),
+        PollDefaults {
+            voting_duration: PollState::MIN_VOTING_DURATION - 1,
+            ..PollDefaults::default()
+        },
+        0,
+    ).await.unwrap();
+
+    match err {
+        TransactionError::InstructionError(_, InstructionError::Custom(code)) => {
+            assert_eq!(code, JanecekError::InvalidDuration as u32, "Expected InvalidDuration error");
+        }
+        other => panic!("Unexpected transport error type: {:?}", other),
+    }
+
+    // expect error when the config is handed to the default pubkey
+    let err = update_config_expect_fail(
+        &mut ctx.banks_client,
+        &ctx.payer,
+        &ctx.last_blockhash,
+        &program_id,
+        &Pubkey::default(),
+        PollDefaults::default(),
+        0,
+    ).await.unwrap();
+
+    match err {
+        TransactionError::InstructionError(_, InstructionError::InvalidArgument) => {}
+        other => panic!("Unexpected transport error type: {:?}", other),
+    }
+
+    // admin hands the config over
+    update_config(
+        &mut ctx.banks_client,
+        &ctx.payer,
+        &ctx.last_blockhash,
+        &program_id,
+        &outsider.pubkey(),
+        PollDefaults::default(),
+        0,
+    ).await;
+
+    let config = get_config(&mut ctx.banks_client, &program_id).await;
+    assert_eq!(config.admin, outsider.pubkey());
+}
+
+
+#[tokio::test]
+async fn test_config_upgrade_authority_only() {
+    let (mut ctx, program_id) = start_test_env().await;
+    let outsider = Keypair::new();
+
+    // transfer SOL to outsider
+    let transfer_ix = system_instruction::transfer(
+        &ctx.payer.pubkey(),
+        &outsider.pubkey(),
+        1_000_000_000, // 1 SOL
+    );
+    let tx = Transaction::new_signed_with_payer(
+        &[transfer_ix],
+        Some(&ctx.payer.pubkey()),
+        &[&ctx.payer],
+        ctx.last_blockhash,
+    );
+    ctx.banks_client.process_transaction(tx).await.unwrap();
+
+    // expect error when someone other than the upgrade authority grabs the fresh config
+    let err = initialize_config_expect_fail(
+        &mut ctx.banks_client,
+        &outsider,
+        &ctx.last_blockhash,
+        &program_id,
+        PollDefaults::default(),
+        0,
+    ).await.unwrap();
+
+    match err {
+        TransactionError::InstructionError(_, InstructionError::IllegalOwner) => {}
+        other => panic!("Unexpected transport error type: {:?}", other),
+    }
+
+    initialize_config(
+        &mut ctx.banks_client,
+        &ctx.payer,
+        &ctx.last_blockhash,
+        &program_id,
+        PollDefaults::default(),
+        0,
+    ).await;
+
+    let config = get_config(&mut ctx.banks_client, &program_id).await;
+    assert_eq!(config.admin, ctx.payer.pubkey());
+}
+
+
+#[tokio::test]
+async fn test_config_immutable_program() {
+    let (mut ctx, program_id) = start_test_env().await;
+
+    // program data of a program whose upgrade authority was removed
+    let mut data = 3u32.to_le_bytes().to_vec(); // ProgramData variant
+    data.extend(0u64.to_le_bytes()); // slot
+    data.push(0); // no upgrade authority
+    data.extend([0u8; 32]);
+    ctx.set_account(&program_data_pda(&program_id), &Account {
+        lamports: Rent::default().minimum_balance(data.len()),
+        data,
+        owner: bpf_loader_upgradeable::id(),
+        executable: false,
+        rent_epoch: 0,
+    }.into());
+
+    // expect error, nobody can become the admin of an immutable program
+    let err = initialize_config_expect_fail(
+        &mut ctx.banks_client,
+        &ctx.payer,
+        &ctx.last_blockhash,
+        &program_id,
+        PollDefaults::default(),
+        0,
+    ).await.unwrap();
+    assert_custom_error(err, JanecekError::ProgramImmutable);
+}
+
+
+#[tokio::test]
+async fn test_config_defaults_and_fee() {
+    let (mut ctx, program_id) = setup_test_env().await;
+    let admin = Keypair::new();
+
+    let defaults = PollDefaults {
+        registration_duration: 60 * 5,
+        voting_duration: 60 * 30,
+        vote_rules: VoteRules { positive_votes: 3, negative_votes: 1, positives_per_negative: 2, allow_negative: true },
+    };
+    let creation_fee = 10_000_000;
+
+    // new defaults and a fee paid to a separate admin
+    update_config(
+        &mut ctx.banks_client,
+        &ctx.payer,
+        &ctx.last_blockhash,
+        &program_id,
+        &admin.pubkey(),
+        defaults,
+        creation_fee,
+    ).await;
+
+    // poll without a schedule takes the config defaults
+    let poll_pda = create_poll(
+        &mut ctx.banks_client,
+        &ctx.payer,
+        &ctx.last_blockhash,
+        &program_id,
+        "Lunch Straw Poll",
+        "Where do we eat today",
+    ).await;
+
+    let poll_state = PollState::deserialize(&mut &ctx.banks_client.get_account(poll_pda).await.unwrap().unwrap().data[..]).unwrap();
+    assert_eq!(poll_state.registration_duration, defaults.registration_duration);
+    assert_eq!(poll_state.voting_duration, defaults.voting_duration);
+    assert_eq!(poll_state.vote_rules, defaults.vote_rules);
+
+    assert_eq!(ctx.banks_client.get_balance(admin.pubkey()).await.unwrap(), creation_fee);
+
+    // explicit values still win over the defaults
+    let poll_pda = create_poll_with_params(
+        &mut ctx.banks_client,
+        &ctx.payer,
+        &ctx.last_blockhash,
+        &program_id,
+        "Presidential Election",
+        "Vote for the next president",
+        PollParams {
+            voting_duration: Some(60 * 60 * 24 * 7),
+            ..PollParams::default()
+        },
+    ).await;
+
+    let poll_state = PollState::deserialize(&mut &ctx.banks_client.get_account(poll_pda).await.unwrap().unwrap().data[..]).unwrap();
+    assert_eq!(poll_state.registration_duration, defaults.registration_duration);
+    assert_eq!(poll_state.voting_duration, 60 * 60 * 24 * 7);
+
+    assert_eq!(ctx.banks_client.get_balance(admin.pubkey()).await.unwrap(), creation_fee * 2);
+
+    let config = get_config(&mut ctx.banks_client, &program_id).await;
+    assert_eq!(config.poll_count, 2);
+}
@@ -1,7 +1,8 @@
 mod helpers;
-use helpers::{setup_test_env, create_poll, create_party};
-use janecek_voting::state::{PollState, PartyAccount};
+use helpers::{setup_test_env, create_poll, create_party, create_party_with_metadata, create_party_expect_fail, PartyMetadata};
+use janecek_voting::{error::JanecekError, state::{PollState, PartyAccount}};
 use borsh::{BorshDeserialize};
+use solana_sdk::{transaction::TransactionError, instruction::InstructionError};
 
 
 #[tokio::test]
@@ -18,7 +19,7 @@ async fn test_create_party_success() {
 
     let poll_pda = create_poll(
         &mut ctx.banks_client,
-        &payer,
+        payer,
         &recent_blockhash,
         &program_id,
         &poll_title,
@@ -27,18 +28,78 @@ async fn test_create_party_success() {
 
     let party_pda = create_party(
         &mut ctx.banks_client,
-        &payer,
+        payer,
         &recent_blockhash,
         &program_id,
         &party_title,
         &poll_pda,
     ).await;
 
-    let poll_state = PollState::try_from_slice(&ctx.banks_client.get_account(poll_pda).await.unwrap().unwrap().data).unwrap();
-    let party_state = PartyAccount::try_from_slice(&ctx.banks_client.get_account(party_pda).await.unwrap().unwrap().data).unwrap();
+    let poll_state = PollState::deserialize(&mut &ctx.banks_client.get_account(poll_pda).await.unwrap().unwrap().data[..]).unwrap();
+    let party_state = *PartyAccount::from_bytes(&ctx.banks_client.get_account(party_pda).await.unwrap().unwrap().data);
 
     assert_eq!(poll_state.party_counter, 1);
-    assert_eq!(party_state.title, party_title);
+    assert_eq!(party_state.title(), party_title);
     assert_eq!(party_state.positive_votes, 0);
     assert_eq!(party_state.negative_votes, 0);
-}
\ No newline at end of file
+}
+
+#[tokio::test]
+async fn test_create_party_with_metadata() {
+    let (mut ctx, program_id) = setup_test_env().await;
+
+    let poll_title = "Presidential Election".to_string();
+    let poll_description = "Vote for the next president".to_string();
+    let metadata = PartyMetadata {
+        description: "Party of the green future".to_string(),
+        metadata_uri: "https://example.com/party-a.json".to_string(),
+        metadata_hash: [7u8; 32],
+    };
+
+    let poll_pda = create_poll(
+        &mut ctx.banks_client,
+        &ctx.payer,
+        &ctx.last_blockhash,
+        &program_id,
+        &poll_title,
+        &poll_description,
+    ).await;
+
+    // expect error when the description is too long
+    let err = create_party_expect_fail(
+        &mut ctx.banks_client,
+        &ctx.payer,
+        &ctx.last_blockhash,
+        &program_id,
+        "Party A",
+        &poll_pda,
+        PartyMetadata {
+            description: "a".repeat(PartyAccount::MAX_DESCRIPTION_LEN + 1),
+            ..metadata.clone()
+        },
+    ).await.unwrap();
+
+    match err {
+        TransactionError::InstructionError(_, InstructionError::Custom(code)) => {
+            assert_eq!(code, JanecekError::InvalidDataLength as u32, "Expected InvalidDataLength error");
+        }
+        other => panic!("Unexpected transport error type: {:?}", other),
+    }
+
+    let party_pda = create_party_with_metadata(
+        &mut ctx.banks_client,
+        &ctx.payer,
+        &ctx.last_blockhash,
+        &program_id,
+        "Party A",
+        &poll_pda,
+        metadata.clone(),
+    ).await;
+
+    let party_state = *PartyAccount::from_bytes(&ctx.banks_client.get_account(party_pda).await.unwrap().unwrap().data);
+
+    assert_eq!(party_state.title(), "Party A");
+    assert_eq!(party_state.description(), metadata.description);
+    assert_eq!(party_state.metadata_uri(), metadata.metadata_uri);
+    assert_eq!(party_state.metadata_hash, metadata.metadata_hash);
+}
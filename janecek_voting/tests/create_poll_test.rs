@@ -1,8 +1,8 @@
 mod helpers;
-use helpers::{setup_test_env, create_poll};
-use janecek_voting::state::{PollState, VotingPhase};
+use helpers::{setup_test_env, create_poll, create_poll_with_params, create_poll_expect_fail, get_config, PollParams};
+use janecek_voting::{error::JanecekError, state::{CreatorPolls, PollSeeds, PollState, RegistryEntry, VoteRules, VotingPhase}};
 use borsh::{BorshDeserialize};
-use solana_sdk::{signer::Signer};
+use solana_sdk::{pubkey::Pubkey, signer::Signer, transaction::TransactionError, instruction::InstructionError};
 
 
 #[tokio::test]
@@ -16,17 +16,216 @@ async fn test_create_poll_success() {
 
     let poll_pda = create_poll(
         &mut ctx.banks_client, 
-        &payer, 
+        payer, 
         &recent_blockhash, 
         &program_id, &title, 
         &description
     ).await;
 
-    let poll_state = PollState::try_from_slice(&ctx.banks_client.get_account(poll_pda).await.unwrap().unwrap().data).unwrap();
+    let poll_state = PollState::deserialize(&mut &ctx.banks_client.get_account(poll_pda).await.unwrap().unwrap().data[..]).unwrap();
 
     assert_eq!(poll_state.title, title);
     assert_eq!(poll_state.description, description);
     assert_eq!(poll_state.phase as u8, VotingPhase::Registration as u8);
     assert_eq!(poll_state.owner, payer.pubkey());
     assert_eq!(poll_state.party_counter, 0);
-}
\ No newline at end of file
+    assert_eq!(poll_state.vote_rules, VoteRules::default());
+}
+
+#[tokio::test]
+async fn test_create_poll_with_custom_schedule() {
+    let (mut ctx, program_id) = setup_test_env().await;
+    let payer = &ctx.payer;
+    let recent_blockhash = ctx.last_blockhash;
+
+    let title = "Lunch Straw Poll".to_string();
+    let description = "Where do we eat today".to_string();
+
+    let poll_pda = create_poll_with_params(
+        &mut ctx.banks_client,
+        payer,
+        &recent_blockhash,
+        &program_id,
+        &title,
+        &description,
+        PollParams {
+            registration_duration: Some(60 * 5),
+            voting_duration: Some(60 * 30),
+            ..PollParams::default()
+        },
+    ).await;
+
+    let poll_state = PollState::deserialize(&mut &ctx.banks_client.get_account(poll_pda).await.unwrap().unwrap().data[..]).unwrap();
+
+    assert_eq!(poll_state.registration_duration, 60 * 5);
+    assert_eq!(poll_state.voting_duration, 60 * 30);
+}
+
+
+#[tokio::test]
+async fn test_create_poll_invalid_duration() {
+    let (mut ctx, program_id) = setup_test_env().await;
+    let payer = &ctx.payer;
+    let recent_blockhash = ctx.last_blockhash;
+
+    let title = "Presidential Election".to_string();
+    let description = "Vote for the next president".to_string();
+
+    // voting period shorter than the minimum, registration longer than the maximum
+    for (registration_duration, voting_duration) in [
+        (60 * 60 * 24, PollState::MIN_VOTING_DURATION - 1),
+        (PollState::MAX_REGISTRATION_DURATION + 1, 60 * 60 * 24 * 7),
+    ] {
+        let err = create_poll_expect_fail(
+            &mut ctx.banks_client,
+            payer,
+            &recent_blockhash,
+            &program_id,
+            &title,
+            &description,
+            PollParams {
+                registration_duration: Some(registration_duration),
+                voting_duration: Some(voting_duration),
+                ..PollParams::default()
+            },
+        ).await.unwrap();
+
+        match err {
+            TransactionError::InstructionError(_, InstructionError::Custom(code)) => {
+                assert_eq!(
+                    code,
+                    JanecekError::InvalidDuration as u32,
+                    "Expected InvalidDuration error"
+                );
+            }
+            other => panic!("Unexpected transport error type: {:?}", other),
+        }
+    }
+}
+
+
+#[tokio::test]
+async fn test_create_poll_invalid_vote_rules() {
+    let (mut ctx, program_id) = setup_test_env().await;
+    let payer = &ctx.payer;
+    let recent_blockhash = ctx.last_blockhash;
+
+    let title = "Presidential Election".to_string();
+    let description = "Vote for the next president".to_string();
+
+    let invalid_rules = [
+        // no positive votes
+        VoteRules { positive_votes: 0, negative_votes: 0, positives_per_negative: 0, allow_negative: false },
+        // negative votes while negatives are disabled
+        VoteRules { positive_votes: 2, negative_votes: 1, positives_per_negative: 2, allow_negative: false },
+        // second negative vote can never be reached
+        VoteRules { positive_votes: 3, negative_votes: 2, positives_per_negative: 2, allow_negative: true },
+    ];
+
+    for vote_rules in invalid_rules {
+        let err = create_poll_expect_fail(
+            &mut ctx.banks_client,
+            payer,
+            &recent_blockhash,
+            &program_id,
+            &title,
+            &description,
+            PollParams {
+                vote_rules: Some(vote_rules),
+                ..PollParams::default()
+            },
+        ).await.unwrap();
+
+        match err {
+            TransactionError::InstructionError(_, InstructionError::Custom(code)) => {
+                assert_eq!(
+                    code,
+                    JanecekError::InvalidVoteRules as u32,
+                    "Expected InvalidVoteRules error"
+                );
+            }
+            other => panic!("Unexpected transport error type: {:?}", other),
+        }
+    }
+}
+
+
+#[tokio::test]
+async fn test_create_poll_too_long_text() {
+    let (mut ctx, program_id) = setup_test_env().await;
+    let payer = &ctx.payer;
+    let recent_blockhash = ctx.last_blockhash;
+
+    let too_long_texts = [
+        ("a".repeat(PollState::MAX_TITLE_LEN + 1), "Vote for the next president".to_string()),
+        ("Presidential Election".to_string(), "a".repeat(PollState::MAX_DESCRIPTION_LEN + 1)),
+    ];
+
+    for (title, description) in too_long_texts {
+        let err = create_poll_expect_fail(
+            &mut ctx.banks_client,
+            payer,
+            &recent_blockhash,
+            &program_id,
+            &title,
+            &description,
+            PollParams::default(),
+        ).await.unwrap();
+
+        match err {
+            TransactionError::InstructionError(_, InstructionError::Custom(code)) => {
+                assert_eq!(
+                    code,
+                    JanecekError::InvalidDataLength as u32,
+                    "Expected InvalidDataLength error"
+                );
+            }
+            other => panic!("Unexpected transport error type: {:?}", other),
+        }
+    }
+}
+
+
+#[tokio::test]
+async fn test_create_poll_registry() {
+    let (mut ctx, program_id) = setup_test_env().await;
+
+    let title = "Presidential Election".to_string();
+    let description = "Vote for the next president".to_string();
+
+    // same wording twice no longer collides
+    let mut poll_pdas = Vec::new();
+    for _ in 0..2 {
+        let poll_pda = create_poll(
+            &mut ctx.banks_client,
+            &ctx.payer,
+            &ctx.last_blockhash,
+            &program_id,
+            &title,
+            &description,
+        ).await;
+        poll_pdas.push(poll_pda);
+    }
+    assert_ne!(poll_pdas[0], poll_pdas[1]);
+
+    // polls of a creator are enumerated by nonce
+    let (creator_pda, _bump_seeds) = Pubkey::find_program_address(&[b"creator", ctx.payer.pubkey().as_ref()], &program_id);
+    let creator_polls = CreatorPolls::try_from_slice(&ctx.banks_client.get_account(creator_pda).await.unwrap().unwrap().data).unwrap();
+    assert_eq!(creator_polls.poll_count, 2);
+
+    for (poll_nonce, poll_pda) in poll_pdas.iter().enumerate() {
+        let seeds = PollSeeds::Creator { creator: ctx.payer.pubkey(), poll_nonce: poll_nonce as u64 };
+        assert_eq!(seeds.find_address(&program_id).0, *poll_pda);
+    }
+
+    // all polls are enumerated through the global registry
+    let config = get_config(&mut ctx.banks_client, &program_id).await;
+    assert_eq!(config.poll_count, 2);
+
+    for (index, poll_pda) in poll_pdas.iter().enumerate() {
+        let (entry_pda, _bump_seeds) = Pubkey::find_program_address(&[b"registry", &(index as u64).to_le_bytes()], &program_id);
+        let entry = RegistryEntry::try_from_slice(&ctx.banks_client.get_account(entry_pda).await.unwrap().unwrap().data).unwrap();
+        assert_eq!(entry.poll, *poll_pda);
+        assert_eq!(entry.creator, ctx.payer.pubkey());
+    }
+}
@@ -0,0 +1,310 @@
+mod helpers;
+use helpers::{setup_test_env, create_poll, create_party, create_party_expect_fail, start_voting, vote, end_voting, end_voting_instruction, end_voting_expect_fail, PartyMetadata};
+use janecek_voting::{error::JanecekError, state::{PollState, PollResults, VoteType, VotingPhase}};
+use borsh::{BorshDeserialize};
+use solana_sdk::{clock::Clock, packet::PACKET_DATA_SIZE, pubkey::Pubkey, rent::Rent, signature::Keypair, signer::Signer, system_instruction, transaction::{Transaction, TransactionError}, instruction::InstructionError};
+
+#[tokio::test]
+async fn test_end_voting_by_owner_success() {
+    let (mut ctx, program_id) = setup_test_env().await;
+
+    let title = "Presidential Election".to_string();
+    let description = "Vote for the next president".to_string();
+
+    // create poll
+    let poll_pda = create_poll(
+        &mut ctx.banks_client,
+        &ctx.payer,
+        &ctx.last_blockhash,
+        &program_id,
+        &title,
+        &description,
+    ).await;
+
+    // create parties
+    let mut party_pdas = Vec::new();
+    for party_title in ["Party A", "Party B", "Party C"] {
+        let party_pda = create_party(
+            &mut ctx.banks_client,
+            &ctx.payer,
+            &ctx.last_blockhash,
+            &program_id,
+            party_title,
+            &poll_pda,
+        ).await;
+        party_pdas.push(party_pda);
+    }
+
+    // update timestamp to 24 hours and 1 minute
+    let mut clock: Clock = ctx.banks_client.get_sysvar().await.unwrap();
+    clock.unix_timestamp += (60 * 60 * 24) + 60;
+    ctx.set_sysvar(&clock);
+
+    // start voting
+    start_voting(
+        &mut ctx.banks_client,
+        &ctx.payer,
+        &ctx.last_blockhash,
+        &program_id,
+        &poll_pda,
+    ).await;
+
+    // +A, +C, -B
+    for (party_pda, vote_type) in [
+        (party_pdas[0], VoteType::Positive),
+        (party_pdas[2], VoteType::Positive),
+        (party_pdas[1], VoteType::Negative),
+    ] {
+        vote(
+            &mut ctx.banks_client,
+            &ctx.payer,
+            &ctx.last_blockhash,
+            &program_id,
+            &poll_pda,
+            &party_pda,
+            vote_type,
+        ).await;
+    }
+
+    // owner closes the poll before the deadline
+    let results_pda = end_voting(
+        &mut ctx.banks_client,
+        &ctx.payer,
+        &ctx.last_blockhash,
+        &program_id,
+        &poll_pda,
+        &party_pdas,
+    ).await;
+
+    let poll_state = PollState::deserialize(&mut &ctx.banks_client.get_account(poll_pda).await.unwrap().unwrap().data[..]).unwrap();
+    let results_state = PollResults::try_from_slice(&ctx.banks_client.get_account(results_pda).await.unwrap().unwrap().data).unwrap();
+
+    assert_eq!(poll_state.phase as u8, VotingPhase::Results as u8);
+
+    assert_eq!(results_state.poll_id, poll_pda);
+    assert_eq!(results_state.parties.len(), 3);
+    assert_eq!(results_state.parties[0].party, party_pdas[0]);
+    assert_eq!(results_state.parties[0].net_votes, 1);
+    assert_eq!(results_state.parties[1].party, party_pdas[2]);
+    assert_eq!(results_state.parties[1].net_votes, 1);
+    assert_eq!(results_state.parties[2].party, party_pdas[1]);
+    assert_eq!(results_state.parties[2].positive_votes, 0);
+    assert_eq!(results_state.parties[2].negative_votes, 1);
+    assert_eq!(results_state.parties[2].net_votes, -1);
+}
+
+#[tokio::test]
+async fn test_end_voting_by_anyone_after_deadline() {
+    let (mut ctx, program_id) = setup_test_env().await;
+
+    let title = "Presidential Election".to_string();
+    let description = "Vote for the next president".to_string();
+    let title_a = "Party A".to_string();
+    let cranker = Keypair::new();
+
+    // transfer SOL to cranker
+    let transfer_ix = system_instruction::transfer(
+        &ctx.payer.pubkey(),
+        &cranker.pubkey(),
+        1_000_000_000, // 1 SOL
+    );
+
+    let tx = Transaction::new_signed_with_payer(
+        &[transfer_ix],
+        Some(&ctx.payer.pubkey()),
+        &[&ctx.payer],
+        ctx.last_blockhash,
+    );
+    ctx.banks_client.process_transaction(tx).await.unwrap();
+
+    // create poll
+    let poll_pda = create_poll(
+        &mut ctx.banks_client,
+        &ctx.payer,
+        &ctx.last_blockhash,
+        &program_id,
+        &title,
+        &description,
+    ).await;
+
+    // create party
+    let party_pda_a = create_party(
+        &mut ctx.banks_client,
+        &ctx.payer,
+        &ctx.last_blockhash,
+        &program_id,
+        &title_a,
+        &poll_pda,
+    ).await;
+
+    // update timestamp to 24 hours and 1 minute
+    let mut clock: Clock = ctx.banks_client.get_sysvar().await.unwrap();
+    clock.unix_timestamp += (60 * 60 * 24) + 60;
+    ctx.set_sysvar(&clock);
+
+    // start voting
+    start_voting(
+        &mut ctx.banks_client,
+        &ctx.payer,
+        &ctx.last_blockhash,
+        &program_id,
+        &poll_pda,
+    ).await;
+
+    // expect error when non-owner tries to end voting before the deadline
+    let err = end_voting_expect_fail(
+        &mut ctx.banks_client,
+        &cranker,
+        &ctx.last_blockhash,
+        &program_id,
+        &poll_pda,
+        &[party_pda_a],
+    ).await.unwrap();
+
+    match err {
+        TransactionError::InstructionError(_, InstructionError::Custom(code)) => {
+            assert_eq!(
+                code,
+                JanecekError::VotingPeriodNotFinished as u32,
+                "Expected VotingPeriodNotFinished error"
+            );
+        }
+        other => panic!("Unexpected transport error type: {:?}", other),
+    }
+
+    // update timestamp to 7 days and 1 minute
+    let mut clock: Clock = ctx.banks_client.get_sysvar().await.unwrap();
+    clock.unix_timestamp += (60 * 60 * 24 * 7) + 60;
+    ctx.set_sysvar(&clock);
+
+    // update blockhash
+    ctx.last_blockhash = ctx.get_new_latest_blockhash().await.unwrap();
+
+    // expect error when a party is missing from the tally
+    let err = end_voting_expect_fail(
+        &mut ctx.banks_client,
+        &cranker,
+        &ctx.last_blockhash,
+        &program_id,
+        &poll_pda,
+        &[],
+    ).await.unwrap();
+
+    match err {
+        TransactionError::InstructionError(_, InstructionError::Custom(code)) => {
+            assert_eq!(
+                code,
+                JanecekError::PartyListMismatch as u32,
+                "Expected PartyListMismatch error"
+            );
+        }
+        other => panic!("Unexpected transport error type: {:?}", other),
+    }
+
+    // someone funds the results address first, which must not block the tally
+    let (results_pda, _bump_seed) = Pubkey::find_program_address(&[b"results", poll_pda.as_ref()], &program_id);
+    let transfer_ix = system_instruction::transfer(&cranker.pubkey(), &results_pda, 1_000_000);
+    let tx = Transaction::new_signed_with_payer(&[transfer_ix], Some(&cranker.pubkey()), &[&cranker], ctx.last_blockhash);
+    ctx.banks_client.process_transaction(tx).await.unwrap();
+
+    // anyone can end voting after the deadline
+    end_voting(
+        &mut ctx.banks_client,
+        &cranker,
+        &ctx.last_blockhash,
+        &program_id,
+        &poll_pda,
+        &[party_pda_a],
+    ).await;
+
+    let rent: Rent = ctx.banks_client.get_sysvar().await.unwrap();
+    let poll_state = PollState::deserialize(&mut &ctx.banks_client.get_account(poll_pda).await.unwrap().unwrap().data[..]).unwrap();
+    let results_account = ctx.banks_client.get_account(results_pda).await.unwrap().unwrap();
+    let results_state = PollResults::try_from_slice(&results_account.data).unwrap();
+
+    assert_eq!(results_account.owner, program_id);
+    assert!(results_account.lamports >= rent.minimum_balance(results_account.data.len()));
+    assert_eq!(poll_state.phase as u8, VotingPhase::Results as u8);
+    assert_eq!(results_state.parties.len(), 1);
+    assert_eq!(results_state.parties[0].party, party_pda_a);
+    assert_eq!(results_state.parties[0].net_votes, 0);
+}
+
+
+#[tokio::test]
+async fn test_end_voting_with_max_parties() {
+    let (mut ctx, program_id) = setup_test_env().await;
+
+    // create poll
+    let poll_pda = create_poll(
+        &mut ctx.banks_client,
+        &ctx.payer,
+        &ctx.last_blockhash,
+        &program_id,
+        "Presidential Election",
+        "Vote for the next president",
+    ).await;
+
+    // create as many parties as a poll can have
+    let mut party_pdas = Vec::new();
+    for index in 0..PollState::MAX_PARTIES {
+        party_pdas.push(create_party(
+            &mut ctx.banks_client,
+            &ctx.payer,
+            &ctx.last_blockhash,
+            &program_id,
+            &format!("Party {}", index),
+            &poll_pda,
+        ).await);
+    }
+
+    // expect error when the poll already has the maximum number of parties
+    let err = create_party_expect_fail(
+        &mut ctx.banks_client,
+        &ctx.payer,
+        &ctx.last_blockhash,
+        &program_id,
+        "One party too many",
+        &poll_pda,
+        PartyMetadata::default(),
+    ).await.unwrap();
+
+    match err {
+        TransactionError::InstructionError(_, InstructionError::Custom(code)) => {
+            assert_eq!(code, JanecekError::TooManyParties as u32, "Expected TooManyParties error");
+        }
+        other => panic!("Unexpected transport error type: {:?}", other),
+    }
+
+    // update timestamp to 24 hours and 1 minute
+    let mut clock: Clock = ctx.banks_client.get_sysvar().await.unwrap();
+    clock.unix_timestamp += (60 * 60 * 24) + 60;
+    ctx.set_sysvar(&clock);
+
+    start_voting(
+        &mut ctx.banks_client,
+        &ctx.payer,
+        &ctx.last_blockhash,
+        &program_id,
+        &poll_pda,
+    ).await;
+
+    // every party still fits in the single EndVoting transaction, with room for a committee proposal
+    let (ix, _results_pda) = end_voting_instruction(&ctx.payer, &program_id, &poll_pda, &party_pdas);
+    let tx = Transaction::new_signed_with_payer(&[ix], Some(&ctx.payer.pubkey()), &[&ctx.payer], ctx.last_blockhash);
+    let tx_len = 1 + 64 * tx.signatures.len() + tx.message_data().len();
+    let proposal_len = 32 + 1;
+    assert!(tx_len + proposal_len <= PACKET_DATA_SIZE, "EndVoting transaction is {} bytes", tx_len);
+
+    let results_pda = end_voting(
+        &mut ctx.banks_client,
+        &ctx.payer,
+        &ctx.last_blockhash,
+        &program_id,
+        &poll_pda,
+        &party_pdas,
+    ).await;
+
+    let results_state = PollResults::try_from_slice(&ctx.banks_client.get_account(results_pda).await.unwrap().unwrap().data).unwrap();
+    assert_eq!(results_state.parties.len(), party_pdas.len());
+}
@@ -1,18 +1,29 @@
+#![allow(dead_code)]
+
 use solana_program_test::*;
 use solana_sdk::{
-    instruction::{AccountMeta, Instruction},
+    account::Account,
+    clock::Clock,
+    instruction::{AccountMeta, Instruction, InstructionError},
+    program_option::COption,
+    program_pack::Pack,
     pubkey::Pubkey,
     signature::Keypair,
     signer::Signer,
-    transaction::Transaction,
+    transaction::{Transaction, TransactionError},
     hash::Hash,
 };
-use solana_program::{system_program, hash::hash};
-use borsh::BorshSerialize;
-use janecek_voting::{processor::process_instruction, state::VoteType};
+use solana_program::{bpf_loader_upgradeable, rent::Rent, system_program, hash::hash};
+use borsh::{BorshDeserialize, BorshSerialize};
+use janecek_voting::{error::JanecekError, processor::process_instruction, state::{BallotEntry, Committee, CreatorPolls, PollDefaults, PollSeeds, PollState, ProgramConfig, ProposalAction, TokenGate, VoteRules, VoteType, VotingMode}};
 
 
-pub async fn setup_test_env() -> (ProgramTestContext, Pubkey) {
+pub fn program_data_pda(program_id: &Pubkey) -> Pubkey {
+    Pubkey::find_program_address(&[program_id.as_ref()], &bpf_loader_upgradeable::id()).0
+}
+
+// Program without a config yet, payer is its upgrade authority
+pub async fn start_test_env() -> (ProgramTestContext, Pubkey) {
     let program_id = Pubkey::new_unique();
     let program_test = ProgramTest::new(
         "janecek_voting",
@@ -20,39 +31,181 @@ pub async fn setup_test_env() -> (ProgramTestContext, Pubkey) {
         processor!(process_instruction)
     );
 
-    let context = program_test.start_with_context().await;
+    let mut context = program_test.start_with_context().await;
+
+    // native processors are not deployed through the upgradeable loader, so fake its ProgramData account
+    let mut data = 3u32.to_le_bytes().to_vec(); // ProgramData variant
+    data.extend(0u64.to_le_bytes()); // slot
+    data.push(1); // upgrade authority is set
+    data.extend(context.payer.pubkey().to_bytes());
+    let program_data = Account {
+        lamports: Rent::default().minimum_balance(data.len()),
+        data,
+        owner: bpf_loader_upgradeable::id(),
+        executable: false,
+        rent_epoch: 0,
+    };
+    context.set_account(&program_data_pda(&program_id), &program_data.into());
+
+    (context, program_id)
+}
+
+pub async fn setup_test_env() -> (ProgramTestContext, Pubkey) {
+    let (mut context, program_id) = start_test_env().await;
+
+    // polls can only be created once the config exists, payer is the admin
+    initialize_config(
+        &mut context.banks_client,
+        &context.payer,
+        &context.last_blockhash,
+        &program_id,
+        PollDefaults::default(),
+        0,
+    ).await;
+
     (context, program_id)
     // let (banks_client, payer, recent_blockhash) = program_test.start().await;
     // (banks_client, payer, recent_blockhash, program_id)
 }
 
 
+// Poll with `party_count` parties whose voting just started
+pub async fn setup_voting_poll(ctx: &mut ProgramTestContext, program_id: &Pubkey, party_count: usize) -> (Pubkey, Vec<Pubkey>) {
+    // create poll
+    let poll_pda = create_poll(
+        &mut ctx.banks_client,
+        &ctx.payer,
+        &ctx.last_blockhash,
+        program_id,
+        "Presidential Election",
+        "Vote for the next president",
+    ).await;
+
+    // create parties
+    let mut party_pdas = Vec::new();
+    for index in 0..party_count {
+        let party_pda = create_party(
+            &mut ctx.banks_client,
+            &ctx.payer,
+            &ctx.last_blockhash,
+            program_id,
+            &format!("Party {}", index),
+            &poll_pda,
+        ).await;
+        party_pdas.push(party_pda);
+    }
+
+    // update timestamp to 24 hours and 1 minute
+    let mut clock: Clock = ctx.banks_client.get_sysvar().await.unwrap();
+    clock.unix_timestamp += (60 * 60 * 24) + 60;
+    ctx.set_sysvar(&clock);
+
+    // start voting
+    start_voting(
+        &mut ctx.banks_client,
+        &ctx.payer,
+        &ctx.last_blockhash,
+        program_id,
+        &poll_pda,
+    ).await;
+
+    (poll_pda, party_pdas)
+}
+
+pub fn assert_custom_error(err: TransactionError, expected: JanecekError) {
+    match err {
+        TransactionError::InstructionError(_, InstructionError::Custom(code)) => {
+            assert_eq!(code, expected as u32, "Expected {:?} error", expected);
+        }
+        other => panic!("Unexpected transport error type: {:?}", other),
+    }
+}
+
+
+// CreatePoll settings besides title and description
+#[derive(BorshSerialize, Clone, Copy)]
+pub struct PollParams {
+    // None takes the default from the program config
+    pub registration_duration: Option<i64>,
+    pub voting_duration: Option<i64>,
+    pub vote_rules: Option<VoteRules>,
+    pub token_gate: Option<TokenGate>,
+    pub voting_mode: VotingMode,
+    pub archive_period: i64,
+    pub closed: bool,
+}
+
+impl Default for PollParams {
+    fn default() -> Self {
+        PollParams {
+            registration_duration: None,
+            voting_duration: None,
+            vote_rules: None,
+            token_gate: None,
+            voting_mode: VotingMode::OnePersonOneVote,
+            archive_period: 0,
+            closed: false,
+        }
+    }
+}
+
 #[derive(BorshSerialize)]
 struct CreatePollPayload {
     title: String,
     description: String,
+    params: PollParams,
 }
 
-pub async fn create_poll(
+pub fn config_pda(program_id: &Pubkey) -> Pubkey {
+    Pubkey::find_program_address(&[b"config"], program_id).0
+}
+
+pub async fn get_config(banks_client: &mut BanksClient, program_id: &Pubkey) -> ProgramConfig {
+    let account = banks_client.get_account(config_pda(program_id)).await.unwrap().unwrap();
+    ProgramConfig::try_from_slice(&account.data).unwrap()
+}
+
+// Reads the creator counter and config to find the next poll and registry entry
+pub async fn next_poll_addresses(
     banks_client: &mut BanksClient,
-    payer: &Keypair,
-    recent_blockhash: &Hash,
     program_id: &Pubkey,
-    title: &str,
-    description: &str,
-) -> Pubkey {
-    let title_hash = hash(title.as_bytes());
-    let description_hash = hash(description.as_bytes());
+    creator: &Pubkey,
+) -> (Pubkey, Pubkey, Pubkey, Pubkey) {
+    let (creator_pda, _bump_seeds) = Pubkey::find_program_address(&[b"creator", creator.as_ref()], program_id);
+    let config_pda = config_pda(program_id);
 
-    let (poll_pda, _bump_seeds) = Pubkey::find_program_address(
-        &[b"poll", title_hash.as_ref(), description_hash.as_ref()],
+    let poll_nonce = match banks_client.get_account(creator_pda).await.unwrap() {
+        Some(account) => CreatorPolls::try_from_slice(&account.data).unwrap().poll_count,
+        None => 0,
+    };
+    let registry_index = get_config(banks_client, program_id).await.poll_count;
+
+    let (poll_pda, _bump_seeds) = PollSeeds::Creator { creator: *creator, poll_nonce }.find_address(program_id);
+    let (registry_entry_pda, _bump_seeds) = Pubkey::find_program_address(
+        &[b"registry", &registry_index.to_le_bytes()],
         program_id
     );
 
+    (poll_pda, creator_pda, config_pda, registry_entry_pda)
+}
+
+async fn create_poll_instruction(
+    banks_client: &mut BanksClient,
+    payer: &Keypair,
+    program_id: &Pubkey,
+    title: &str,
+    description: &str,
+    params: PollParams,
+) -> (Instruction, Pubkey) {
+    let (poll_pda, creator_pda, config_pda, registry_entry_pda) =
+        next_poll_addresses(banks_client, program_id, &payer.pubkey()).await;
+    let admin = get_config(banks_client, program_id).await.admin;
+
     let mut data = vec![0u8]; // discriminator = 0 (CreatePoll)
     let payload = CreatePollPayload {
         title: title.to_string(),
         description: description.to_string(),
+        params,
     };
     data.extend(payload.try_to_vec().expect("borsh serialize"));
 
@@ -61,220 +214,1831 @@ pub async fn create_poll(
         accounts: vec![
             AccountMeta::new(payer.pubkey(), true),
             AccountMeta::new(poll_pda, false),
+            AccountMeta::new(creator_pda, false),
+            AccountMeta::new(config_pda, false),
+            AccountMeta::new(registry_entry_pda, false),
+            AccountMeta::new(admin, false),
             AccountMeta::new_readonly(system_program::id(), false),
         ],
-        data: data,
+        data,
     };
 
-    let tx = Transaction::new_signed_with_payer(&[ix], Some(&payer.pubkey()), &[&payer], *recent_blockhash);
-    banks_client.process_transaction(tx).await.unwrap();
+    (ix, poll_pda)
+}
 
-    poll_pda
+pub async fn create_poll(
+    banks_client: &mut BanksClient,
+    payer: &Keypair,
+    recent_blockhash: &Hash,
+    program_id: &Pubkey,
+    title: &str,
+    description: &str,
+) -> Pubkey {
+    create_poll_with_params(
+        banks_client,
+        payer,
+        recent_blockhash,
+        program_id,
+        title,
+        description,
+        PollParams::default(),
+    ).await
 }
 
+pub async fn create_poll_with_params(
+    banks_client: &mut BanksClient,
+    payer: &Keypair,
+    recent_blockhash: &Hash,
+    program_id: &Pubkey,
+    title: &str,
+    description: &str,
+    params: PollParams,
+) -> Pubkey {
+    let (ix, poll_pda) = create_poll_instruction(banks_client, payer, program_id, title, description, params).await;
+    let tx = Transaction::new_signed_with_payer(&[ix], Some(&payer.pubkey()), &[payer], *recent_blockhash);
+    banks_client.process_transaction(tx).await.unwrap();
 
-#[derive(BorshSerialize)]
-struct CreatePartyPayload {
-    title: String,
+    poll_pda
 }
 
-pub async fn create_party(
+pub async fn create_poll_expect_fail(
     banks_client: &mut BanksClient,
     payer: &Keypair,
     recent_blockhash: &Hash,
     program_id: &Pubkey,
     title: &str,
-    poll_id: &Pubkey,
-) -> Pubkey {
-    let title_hash = hash(title.as_bytes());
-    let (party_pda, _bump_seeds) = Pubkey::find_program_address(
-        &[b"party", poll_id.as_ref(), title_hash.as_ref()],
-        program_id
-    );
+    description: &str,
+    params: PollParams,
+) -> solana_program_test::BanksClientError {
+    let (ix, _poll_pda) = create_poll_instruction(banks_client, payer, program_id, title, description, params).await;
+    let tx = Transaction::new_signed_with_payer(&[ix], Some(&payer.pubkey()), &[payer], *recent_blockhash);
 
-    let mut data = vec![1u8]; // discriminator = 1 (CreateParty)
-    let payload = CreatePartyPayload {
-        title: title.to_string(),
-    };
+    banks_client.process_transaction(tx).await.unwrap_err()
+}
+
+
+#[derive(BorshSerialize)]
+struct InitializeConfigPayload {
+    defaults: PollDefaults,
+    creation_fee: u64,
+}
+
+fn initialize_config_instruction(
+    payer: &Keypair,
+    program_id: &Pubkey,
+    defaults: PollDefaults,
+    creation_fee: u64,
+) -> Instruction {
+    let mut data = vec![17u8]; // discriminator = 17 (InitializeConfig)
+    let payload = InitializeConfigPayload { defaults, creation_fee };
     data.extend(payload.try_to_vec().expect("borsh serialize"));
 
-    let ix = Instruction {
+    Instruction {
         program_id: *program_id,
         accounts: vec![
             AccountMeta::new(payer.pubkey(), true),
-            AccountMeta::new(*poll_id, false),
-            AccountMeta::new(party_pda, false),
+            AccountMeta::new(config_pda(program_id), false),
             AccountMeta::new_readonly(system_program::id(), false),
+            AccountMeta::new_readonly(program_data_pda(program_id), false),
         ],
-        data: data,
-    };
+        data,
+    }
+}
 
-    let tx = Transaction::new_signed_with_payer(&[ix], Some(&payer.pubkey()), &[&payer], *recent_blockhash);
+pub async fn initialize_config(
+    banks_client: &mut BanksClient,
+    payer: &Keypair,
+    recent_blockhash: &Hash,
+    program_id: &Pubkey,
+    defaults: PollDefaults,
+    creation_fee: u64,
+) {
+    let ix = initialize_config_instruction(payer, program_id, defaults, creation_fee);
+    let tx = Transaction::new_signed_with_payer(&[ix], Some(&payer.pubkey()), &[payer], *recent_blockhash);
     banks_client.process_transaction(tx).await.unwrap();
-
-    party_pda
 }
 
+pub async fn initialize_config_expect_fail(
+    banks_client: &mut BanksClient,
+    payer: &Keypair,
+    recent_blockhash: &Hash,
+    program_id: &Pubkey,
+    defaults: PollDefaults,
+    creation_fee: u64,
+) -> solana_program_test::BanksClientError {
+    let ix = initialize_config_instruction(payer, program_id, defaults, creation_fee);
+    let tx = Transaction::new_signed_with_payer(&[ix], Some(&payer.pubkey()), &[payer], *recent_blockhash);
+    banks_client.process_transaction(tx).await.unwrap_err()
+}
 
 #[derive(BorshSerialize)]
-struct InitiateOwnerTransferPayload {
-    new_owner: Pubkey,
+struct UpdateConfigPayload {
+    admin: Pubkey,
+    defaults: PollDefaults,
+    creation_fee: u64,
 }
 
-pub async fn initiate_owner_transfer(
-    banks_client: &mut BanksClient,
+fn update_config_instruction(
     payer: &Keypair,
-    recent_blockhash: &Hash,
     program_id: &Pubkey,
-    new_owner: &Pubkey,
-    poll_id: &Pubkey,
-) -> Pubkey {
-    let mut data = vec![2u8]; // discriminator = 2 (InitiateOwnerTransfer)
-    let payload = InitiateOwnerTransferPayload {
-        new_owner: *new_owner,
-    };
+    admin: &Pubkey,
+    defaults: PollDefaults,
+    creation_fee: u64,
+) -> Instruction {
+    let mut data = vec![18u8]; // discriminator = 18 (UpdateConfig)
+    let payload = UpdateConfigPayload { admin: *admin, defaults, creation_fee };
     data.extend(payload.try_to_vec().expect("borsh serialize"));
 
-    let ix = Instruction {
+    Instruction {
         program_id: *program_id,
         accounts: vec![
             AccountMeta::new(payer.pubkey(), true),
-            AccountMeta::new(*poll_id, false),
+            AccountMeta::new(config_pda(program_id), false),
         ],
-        data: data,
-    };
-    let tx = Transaction::new_signed_with_payer(&[ix], Some(&payer.pubkey()), &[&payer], *recent_blockhash);
-    banks_client.process_transaction(tx).await.unwrap();
-
-    *poll_id
+        data,
+    }
 }
 
-
-pub async fn accept_owner_transfer(
+pub async fn update_config(
     banks_client: &mut BanksClient,
     payer: &Keypair,
     recent_blockhash: &Hash,
     program_id: &Pubkey,
-    poll_id: &Pubkey,
-) -> Pubkey {
-    let data = vec![3u8]; // discriminator = 3 (AcceptOwnerTransfer)
-    let ix = Instruction {
-        program_id: *program_id,
-        accounts: vec![
-            AccountMeta::new(payer.pubkey(), true),
-            AccountMeta::new(*poll_id, false),
-        ],
-        data: data,
-    };
-    let tx = Transaction::new_signed_with_payer(&[ix], Some(&payer.pubkey()), &[&payer], *recent_blockhash);
+    admin: &Pubkey,
+    defaults: PollDefaults,
+    creation_fee: u64,
+) {
+    let ix = update_config_instruction(payer, program_id, admin, defaults, creation_fee);
+    let tx = Transaction::new_signed_with_payer(&[ix], Some(&payer.pubkey()), &[payer], *recent_blockhash);
     banks_client.process_transaction(tx).await.unwrap();
+}
 
-    *poll_id
+pub async fn update_config_expect_fail(
+    banks_client: &mut BanksClient,
+    payer: &Keypair,
+    recent_blockhash: &Hash,
+    program_id: &Pubkey,
+    admin: &Pubkey,
+    defaults: PollDefaults,
+    creation_fee: u64,
+) -> solana_program_test::BanksClientError {
+    let ix = update_config_instruction(payer, program_id, admin, defaults, creation_fee);
+    let tx = Transaction::new_signed_with_payer(&[ix], Some(&payer.pubkey()), &[payer], *recent_blockhash);
+    banks_client.process_transaction(tx).await.unwrap_err()
 }
 
 
-pub async fn start_voting(
+#[derive(BorshSerialize)]
+struct SetProgramPausedPayload {
+    paused: bool,
+}
+
+pub async fn set_program_paused(
     banks_client: &mut BanksClient,
     payer: &Keypair,
     recent_blockhash: &Hash,
     program_id: &Pubkey,
-    poll_id: &Pubkey,
-) -> Pubkey {
-    let data = vec![4u8]; // discriminator = 4 (StartVoting)
+    paused: bool,
+) {
+    let mut data = vec![20u8]; // discriminator = 20 (SetProgramPaused)
+    let payload = SetProgramPausedPayload { paused };
+    data.extend(payload.try_to_vec().expect("borsh serialize"));
 
     let ix = Instruction {
         program_id: *program_id,
         accounts: vec![
             AccountMeta::new(payer.pubkey(), true),
-            AccountMeta::new(*poll_id, false),
+            AccountMeta::new(config_pda(program_id), false),
         ],
-        data: data,
+        data,
     };
-    let tx = Transaction::new_signed_with_payer(&[ix], Some(&payer.pubkey()), &[&payer], *recent_blockhash);
+    let tx = Transaction::new_signed_with_payer(&[ix], Some(&payer.pubkey()), &[payer], *recent_blockhash);
     banks_client.process_transaction(tx).await.unwrap();
-
-    *poll_id
 }
 
 
 #[derive(BorshSerialize)]
-struct VotePayload {
-    vote_type: VoteType,
+struct SetPollPausedPayload {
+    paused: bool,
+    extend_deadline: bool,
 }
 
-pub async fn vote(
-    banks_client: &mut BanksClient,
+fn set_poll_paused_instruction(
     payer: &Keypair,
-    recent_blockhash: &Hash,
     program_id: &Pubkey,
     poll_pda: &Pubkey,
-    party_pda: &Pubkey,
-    vote_type: VoteType,
-) -> Pubkey {
-    let (voter_pda, _bump_seeds) = Pubkey::find_program_address(
-        &[b"voter", poll_pda.as_ref(), payer.pubkey().as_ref()],
-        program_id
-    );
-    
-    let mut data = vec![5u8]; // discriminator = 5 (Vote)
-    let payload = VotePayload {
-        vote_type: vote_type,
-    };
+    paused: bool,
+    extend_deadline: bool,
+) -> Instruction {
+    let mut data = vec![19u8]; // discriminator = 19 (SetPollPaused)
+    let payload = SetPollPausedPayload { paused, extend_deadline };
     data.extend(payload.try_to_vec().expect("borsh serialize"));
 
-    let ix = Instruction {
+    Instruction {
         program_id: *program_id,
         accounts: vec![
             AccountMeta::new(payer.pubkey(), true),
             AccountMeta::new(*poll_pda, false),
-            AccountMeta::new(*party_pda, false),
-            AccountMeta::new(voter_pda, false),
-            AccountMeta::new_readonly(system_program::id(), false),
+            AccountMeta::new_readonly(config_pda(program_id), false),
         ],
-        data: data,
-    };
-    let tx = Transaction::new_signed_with_payer(&[ix], Some(&payer.pubkey()), &[&payer], *recent_blockhash);
-    banks_client.process_transaction(tx).await.unwrap();
+        data,
+    }
+}
 
-    voter_pda
+pub async fn set_poll_paused(
+    banks_client: &mut BanksClient,
+    payer: &Keypair,
+    recent_blockhash: &Hash,
+    program_id: &Pubkey,
+    poll_pda: &Pubkey,
+    paused: bool,
+    extend_deadline: bool,
+) {
+    let ix = set_poll_paused_instruction(payer, program_id, poll_pda, paused, extend_deadline);
+    let tx = Transaction::new_signed_with_payer(&[ix], Some(&payer.pubkey()), &[payer], *recent_blockhash);
+    banks_client.process_transaction(tx).await.unwrap();
 }
 
-pub async fn vote_expect_fail(
+pub async fn set_poll_paused_expect_fail(
     banks_client: &mut BanksClient,
     payer: &Keypair,
     recent_blockhash: &Hash,
     program_id: &Pubkey,
     poll_pda: &Pubkey,
-    party_pda: &Pubkey,
-    vote_type: VoteType,
+    paused: bool,
+    extend_deadline: bool,
 ) -> solana_program_test::BanksClientError {
-    let (voter_pda, _) = Pubkey::find_program_address(
-        &[b"voter", poll_pda.as_ref(), payer.pubkey().as_ref()],
+    let ix = set_poll_paused_instruction(payer, program_id, poll_pda, paused, extend_deadline);
+    let tx = Transaction::new_signed_with_payer(&[ix], Some(&payer.pubkey()), &[payer], *recent_blockhash);
+
+    banks_client.process_transaction(tx).await.unwrap_err()
+}
+
+
+// CreateParty and UpdatePartyMetadata fields besides the title
+#[derive(BorshSerialize, Clone, Default)]
+pub struct PartyMetadata {
+    pub description: String,
+    pub metadata_uri: String,
+    pub metadata_hash: [u8; 32],
+}
+
+#[derive(BorshSerialize)]
+struct CreatePartyPayload {
+    title: String,
+    metadata: PartyMetadata,
+}
+
+fn create_party_instruction(
+    payer: &Keypair,
+    program_id: &Pubkey,
+    title: &str,
+    poll_id: &Pubkey,
+    metadata: PartyMetadata,
+) -> (Instruction, Pubkey) {
+    let title_hash = hash(title.as_bytes());
+    let (party_pda, _bump_seeds) = Pubkey::find_program_address(
+        &[b"party", poll_id.as_ref(), title_hash.as_ref()],
         program_id
     );
 
-    let mut data = vec![5u8];
-    let payload = VotePayload { vote_type };
-    data.extend(payload.try_to_vec().unwrap());
+    let mut data = vec![1u8]; // discriminator = 1 (CreateParty)
+    let payload = CreatePartyPayload {
+        title: title.to_string(),
+        metadata,
+    };
+    data.extend(payload.try_to_vec().expect("borsh serialize"));
 
     let ix = Instruction {
         program_id: *program_id,
         accounts: vec![
             AccountMeta::new(payer.pubkey(), true),
-            AccountMeta::new(*poll_pda, false),
-            AccountMeta::new(*party_pda, false),
-            AccountMeta::new(voter_pda, false),
+            AccountMeta::new(*poll_id, false),
+            AccountMeta::new_readonly(config_pda(program_id), false),
+            AccountMeta::new(party_pda, false),
             AccountMeta::new_readonly(system_program::id(), false),
         ],
         data,
     };
 
-    let tx = Transaction::new_signed_with_payer(
-        &[ix],
-        Some(&payer.pubkey()),
-        &[payer],
-        *recent_blockhash,
-    );
+    (ix, party_pda)
+}
 
-    banks_client.process_transaction(tx).await.unwrap_err()
+pub async fn create_party(
+    banks_client: &mut BanksClient,
+    payer: &Keypair,
+    recent_blockhash: &Hash,
+    program_id: &Pubkey,
+    title: &str,
+    poll_id: &Pubkey,
+) -> Pubkey {
+    create_party_with_metadata(
+        banks_client,
+        payer,
+        recent_blockhash,
+        program_id,
+        title,
+        poll_id,
+        PartyMetadata::default(),
+    ).await
+}
+
+pub async fn create_party_with_metadata(
+    banks_client: &mut BanksClient,
+    payer: &Keypair,
+    recent_blockhash: &Hash,
+    program_id: &Pubkey,
+    title: &str,
+    poll_id: &Pubkey,
+    metadata: PartyMetadata,
+) -> Pubkey {
+    let (ix, party_pda) = create_party_instruction(payer, program_id, title, poll_id, metadata);
+    let tx = Transaction::new_signed_with_payer(&[ix], Some(&payer.pubkey()), &[&payer], *recent_blockhash);
+    banks_client.process_transaction(tx).await.unwrap();
+
+    party_pda
+}
+
+pub async fn create_party_expect_fail(
+    banks_client: &mut BanksClient,
+    payer: &Keypair,
+    recent_blockhash: &Hash,
+    program_id: &Pubkey,
+    title: &str,
+    poll_id: &Pubkey,
+    metadata: PartyMetadata,
+) -> solana_program_test::BanksClientError {
+    let (ix, _party_pda) = create_party_instruction(payer, program_id, title, poll_id, metadata);
+    let tx = Transaction::new_signed_with_payer(&[ix], Some(&payer.pubkey()), &[&payer], *recent_blockhash);
+
+    banks_client.process_transaction(tx).await.unwrap_err()
+}
+
+
+fn remove_party_instruction(
+    payer: &Keypair,
+    program_id: &Pubkey,
+    poll_pda: &Pubkey,
+    party_pda: &Pubkey,
+) -> Instruction {
+    let data = vec![14u8]; // discriminator = 14 (RemoveParty)
+
+    Instruction {
+        program_id: *program_id,
+        accounts: vec![
+            AccountMeta::new(payer.pubkey(), true),
+            AccountMeta::new(*poll_pda, false),
+            AccountMeta::new_readonly(config_pda(program_id), false),
+            AccountMeta::new(*party_pda, false),
+            AccountMeta::new(payer.pubkey(), false),
+        ],
+        data,
+    }
+}
+
+pub async fn remove_party(
+    banks_client: &mut BanksClient,
+    payer: &Keypair,
+    recent_blockhash: &Hash,
+    program_id: &Pubkey,
+    poll_pda: &Pubkey,
+    party_pda: &Pubkey,
+) {
+    let ix = remove_party_instruction(payer, program_id, poll_pda, party_pda);
+    let tx = Transaction::new_signed_with_payer(&[ix], Some(&payer.pubkey()), &[payer], *recent_blockhash);
+    banks_client.process_transaction(tx).await.unwrap();
+}
+
+pub async fn remove_party_expect_fail(
+    banks_client: &mut BanksClient,
+    payer: &Keypair,
+    recent_blockhash: &Hash,
+    program_id: &Pubkey,
+    poll_pda: &Pubkey,
+    party_pda: &Pubkey,
+) -> solana_program_test::BanksClientError {
+    let ix = remove_party_instruction(payer, program_id, poll_pda, party_pda);
+    let tx = Transaction::new_signed_with_payer(&[ix], Some(&payer.pubkey()), &[payer], *recent_blockhash);
+
+    banks_client.process_transaction(tx).await.unwrap_err()
+}
+
+
+#[derive(BorshSerialize)]
+struct UpdatePartyMetadataPayload {
+    metadata: PartyMetadata,
+}
+
+fn update_party_metadata_instruction(
+    payer: &Keypair,
+    program_id: &Pubkey,
+    poll_pda: &Pubkey,
+    party_pda: &Pubkey,
+    metadata: PartyMetadata,
+) -> Instruction {
+    let mut data = vec![15u8]; // discriminator = 15 (UpdatePartyMetadata)
+    let payload = UpdatePartyMetadataPayload { metadata };
+    data.extend(payload.try_to_vec().expect("borsh serialize"));
+
+    Instruction {
+        program_id: *program_id,
+        accounts: vec![
+            AccountMeta::new(payer.pubkey(), true),
+            AccountMeta::new_readonly(*poll_pda, false),
+            AccountMeta::new_readonly(config_pda(program_id), false),
+            AccountMeta::new(*party_pda, false),
+        ],
+        data,
+    }
+}
+
+pub async fn update_party_metadata(
+    banks_client: &mut BanksClient,
+    payer: &Keypair,
+    recent_blockhash: &Hash,
+    program_id: &Pubkey,
+    poll_pda: &Pubkey,
+    party_pda: &Pubkey,
+    metadata: PartyMetadata,
+) {
+    let ix = update_party_metadata_instruction(payer, program_id, poll_pda, party_pda, metadata);
+    let tx = Transaction::new_signed_with_payer(&[ix], Some(&payer.pubkey()), &[payer], *recent_blockhash);
+    banks_client.process_transaction(tx).await.unwrap();
+}
+
+pub async fn update_party_metadata_expect_fail(
+    banks_client: &mut BanksClient,
+    payer: &Keypair,
+    recent_blockhash: &Hash,
+    program_id: &Pubkey,
+    poll_pda: &Pubkey,
+    party_pda: &Pubkey,
+    metadata: PartyMetadata,
+) -> solana_program_test::BanksClientError {
+    let ix = update_party_metadata_instruction(payer, program_id, poll_pda, party_pda, metadata);
+    let tx = Transaction::new_signed_with_payer(&[ix], Some(&payer.pubkey()), &[payer], *recent_blockhash);
+
+    banks_client.process_transaction(tx).await.unwrap_err()
+}
+
+
+#[derive(BorshSerialize)]
+struct UpdatePollPayload {
+    title: String,
+    description: String,
+}
+
+fn update_poll_instruction(
+    payer: &Keypair,
+    program_id: &Pubkey,
+    poll_pda: &Pubkey,
+    title: &str,
+    description: &str,
+) -> Instruction {
+    let mut data = vec![16u8]; // discriminator = 16 (UpdatePoll)
+    let payload = UpdatePollPayload {
+        title: title.to_string(),
+        description: description.to_string(),
+    };
+    data.extend(payload.try_to_vec().expect("borsh serialize"));
+
+    Instruction {
+        program_id: *program_id,
+        accounts: vec![
+            AccountMeta::new(payer.pubkey(), true),
+            AccountMeta::new(*poll_pda, false),
+            AccountMeta::new_readonly(config_pda(program_id), false),
+            AccountMeta::new_readonly(system_program::id(), false),
+        ],
+        data,
+    }
+}
+
+pub async fn update_poll(
+    banks_client: &mut BanksClient,
+    payer: &Keypair,
+    recent_blockhash: &Hash,
+    program_id: &Pubkey,
+    poll_pda: &Pubkey,
+    title: &str,
+    description: &str,
+) {
+    let ix = update_poll_instruction(payer, program_id, poll_pda, title, description);
+    let tx = Transaction::new_signed_with_payer(&[ix], Some(&payer.pubkey()), &[payer], *recent_blockhash);
+    banks_client.process_transaction(tx).await.unwrap();
+}
+
+pub async fn update_poll_expect_fail(
+    banks_client: &mut BanksClient,
+    payer: &Keypair,
+    recent_blockhash: &Hash,
+    program_id: &Pubkey,
+    poll_pda: &Pubkey,
+    title: &str,
+    description: &str,
+) -> solana_program_test::BanksClientError {
+    let ix = update_poll_instruction(payer, program_id, poll_pda, title, description);
+    let tx = Transaction::new_signed_with_payer(&[ix], Some(&payer.pubkey()), &[payer], *recent_blockhash);
+
+    banks_client.process_transaction(tx).await.unwrap_err()
+}
+
+
+#[derive(BorshSerialize)]
+struct InitiateOwnerTransferPayload {
+    new_owner: Pubkey,
+}
+
+pub async fn initiate_owner_transfer(
+    banks_client: &mut BanksClient,
+    payer: &Keypair,
+    recent_blockhash: &Hash,
+    program_id: &Pubkey,
+    new_owner: &Pubkey,
+    poll_id: &Pubkey,
+) -> Pubkey {
+    let mut data = vec![2u8]; // discriminator = 2 (InitiateOwnerTransfer)
+    let payload = InitiateOwnerTransferPayload {
+        new_owner: *new_owner,
+    };
+    data.extend(payload.try_to_vec().expect("borsh serialize"));
+
+    let ix = Instruction {
+        program_id: *program_id,
+        accounts: vec![
+            AccountMeta::new(payer.pubkey(), true),
+            AccountMeta::new(*poll_id, false),
+            AccountMeta::new_readonly(config_pda(program_id), false),
+        ],
+        data,
+    };
+    let tx = Transaction::new_signed_with_payer(&[ix], Some(&payer.pubkey()), &[&payer], *recent_blockhash);
+    banks_client.process_transaction(tx).await.unwrap();
+
+    *poll_id
+}
+
+
+fn accept_owner_transfer_instruction(
+    payer: &Keypair,
+    program_id: &Pubkey,
+    poll_id: &Pubkey,
+) -> Instruction {
+    let data = vec![3u8]; // discriminator = 3 (AcceptOwnerTransfer)
+
+    Instruction {
+        program_id: *program_id,
+        accounts: vec![
+            AccountMeta::new(payer.pubkey(), true),
+            AccountMeta::new(*poll_id, false),
+            AccountMeta::new_readonly(config_pda(program_id), false),
+        ],
+        data,
+    }
+}
+
+pub async fn accept_owner_transfer(
+    banks_client: &mut BanksClient,
+    payer: &Keypair,
+    recent_blockhash: &Hash,
+    program_id: &Pubkey,
+    poll_id: &Pubkey,
+) -> Pubkey {
+    let ix = accept_owner_transfer_instruction(payer, program_id, poll_id);
+    let tx = Transaction::new_signed_with_payer(&[ix], Some(&payer.pubkey()), &[&payer], *recent_blockhash);
+    banks_client.process_transaction(tx).await.unwrap();
+
+    *poll_id
+}
+
+pub async fn accept_owner_transfer_expect_fail(
+    banks_client: &mut BanksClient,
+    payer: &Keypair,
+    recent_blockhash: &Hash,
+    program_id: &Pubkey,
+    poll_id: &Pubkey,
+) -> solana_program_test::BanksClientError {
+    let ix = accept_owner_transfer_instruction(payer, program_id, poll_id);
+    let tx = Transaction::new_signed_with_payer(&[ix], Some(&payer.pubkey()), &[payer], *recent_blockhash);
+
+    banks_client.process_transaction(tx).await.unwrap_err()
+}
+
+
+fn cancel_owner_transfer_instruction(
+    payer: &Keypair,
+    program_id: &Pubkey,
+    poll_id: &Pubkey,
+) -> Instruction {
+    let data = vec![21u8]; // discriminator = 21 (CancelOwnerTransfer)
+
+    Instruction {
+        program_id: *program_id,
+        accounts: vec![
+            AccountMeta::new(payer.pubkey(), true),
+            AccountMeta::new(*poll_id, false),
+        ],
+        data,
+    }
+}
+
+pub async fn cancel_owner_transfer(
+    banks_client: &mut BanksClient,
+    payer: &Keypair,
+    recent_blockhash: &Hash,
+    program_id: &Pubkey,
+    poll_id: &Pubkey,
+) {
+    let ix = cancel_owner_transfer_instruction(payer, program_id, poll_id);
+    let tx = Transaction::new_signed_with_payer(&[ix], Some(&payer.pubkey()), &[payer], *recent_blockhash);
+    banks_client.process_transaction(tx).await.unwrap();
+}
+
+pub async fn cancel_owner_transfer_expect_fail(
+    banks_client: &mut BanksClient,
+    payer: &Keypair,
+    recent_blockhash: &Hash,
+    program_id: &Pubkey,
+    poll_id: &Pubkey,
+) -> solana_program_test::BanksClientError {
+    let ix = cancel_owner_transfer_instruction(payer, program_id, poll_id);
+    let tx = Transaction::new_signed_with_payer(&[ix], Some(&payer.pubkey()), &[payer], *recent_blockhash);
+
+    banks_client.process_transaction(tx).await.unwrap_err()
+}
+
+
+pub async fn start_voting(
+    banks_client: &mut BanksClient,
+    payer: &Keypair,
+    recent_blockhash: &Hash,
+    program_id: &Pubkey,
+    poll_id: &Pubkey,
+) -> Pubkey {
+    let data = vec![4u8]; // discriminator = 4 (StartVoting)
+
+    let ix = Instruction {
+        program_id: *program_id,
+        accounts: vec![
+            AccountMeta::new(payer.pubkey(), true),
+            AccountMeta::new(*poll_id, false),
+            AccountMeta::new_readonly(config_pda(program_id), false),
+        ],
+        data,
+    };
+    let tx = Transaction::new_signed_with_payer(&[ix], Some(&payer.pubkey()), &[&payer], *recent_blockhash);
+    banks_client.process_transaction(tx).await.unwrap();
+
+    *poll_id
+}
+
+pub async fn start_voting_expect_fail(
+    banks_client: &mut BanksClient,
+    payer: &Keypair,
+    recent_blockhash: &Hash,
+    program_id: &Pubkey,
+    poll_id: &Pubkey,
+) -> solana_program_test::BanksClientError {
+    let data = vec![4u8]; // discriminator = 4 (StartVoting)
+
+    let ix = Instruction {
+        program_id: *program_id,
+        accounts: vec![
+            AccountMeta::new(payer.pubkey(), true),
+            AccountMeta::new(*poll_id, false),
+            AccountMeta::new_readonly(config_pda(program_id), false),
+        ],
+        data,
+    };
+    let tx = Transaction::new_signed_with_payer(&[ix], Some(&payer.pubkey()), &[payer], *recent_blockhash);
+
+    banks_client.process_transaction(tx).await.unwrap_err()
+}
+
+
+#[derive(BorshSerialize)]
+struct VotePayload {
+    vote_type: VoteType,
+}
+
+pub fn vote_instruction(
+    payer: &Keypair,
+    program_id: &Pubkey,
+    poll_pda: &Pubkey,
+    party_pda: &Pubkey,
+    vote_type: VoteType,
+    extra_accounts: &[AccountMeta],
+) -> (Instruction, Pubkey) {
+    let (voter_pda, _bump_seeds) = Pubkey::find_program_address(
+        &[b"voter", poll_pda.as_ref(), payer.pubkey().as_ref()],
+        program_id
+    );
+
+    let mut data = vec![5u8]; // discriminator = 5 (Vote)
+    let payload = VotePayload { vote_type };
+    data.extend(payload.try_to_vec().expect("borsh serialize"));
+
+    let mut accounts = vec![
+        AccountMeta::new(payer.pubkey(), true),
+        AccountMeta::new(*poll_pda, false),
+        AccountMeta::new_readonly(config_pda(program_id), false),
+        AccountMeta::new(*party_pda, false),
+        AccountMeta::new(voter_pda, false),
+        AccountMeta::new_readonly(system_program::id(), false),
+    ];
+    accounts.extend_from_slice(extra_accounts);
+
+    let ix = Instruction {
+        program_id: *program_id,
+        accounts,
+        data,
+    };
+
+    (ix, voter_pda)
+}
+
+pub async fn vote(
+    banks_client: &mut BanksClient,
+    payer: &Keypair,
+    recent_blockhash: &Hash,
+    program_id: &Pubkey,
+    poll_pda: &Pubkey,
+    party_pda: &Pubkey,
+    vote_type: VoteType,
+) -> Pubkey {
+    vote_with_accounts(banks_client, payer, recent_blockhash, program_id, poll_pda, party_pda, vote_type, &[]).await
+}
+
+pub async fn vote_expect_fail(
+    banks_client: &mut BanksClient,
+    payer: &Keypair,
+    recent_blockhash: &Hash,
+    program_id: &Pubkey,
+    poll_pda: &Pubkey,
+    party_pda: &Pubkey,
+    vote_type: VoteType,
+) -> solana_program_test::BanksClientError {
+    vote_with_accounts_expect_fail(banks_client, payer, recent_blockhash, program_id, poll_pda, party_pda, vote_type, &[]).await
+}
+
+// Vote with the optional eligibility accounts required by closed polls
+#[allow(clippy::too_many_arguments)]
+pub async fn vote_with_accounts(
+    banks_client: &mut BanksClient,
+    payer: &Keypair,
+    recent_blockhash: &Hash,
+    program_id: &Pubkey,
+    poll_pda: &Pubkey,
+    party_pda: &Pubkey,
+    vote_type: VoteType,
+    extra_accounts: &[AccountMeta],
+) -> Pubkey {
+    let (ix, voter_pda) = vote_instruction(payer, program_id, poll_pda, party_pda, vote_type, extra_accounts);
+    let tx = Transaction::new_signed_with_payer(&[ix], Some(&payer.pubkey()), &[payer], *recent_blockhash);
+    banks_client.process_transaction(tx).await.unwrap();
+
+    voter_pda
+}
+
+#[allow(clippy::too_many_arguments)]
+pub async fn vote_with_accounts_expect_fail(
+    banks_client: &mut BanksClient,
+    payer: &Keypair,
+    recent_blockhash: &Hash,
+    program_id: &Pubkey,
+    poll_pda: &Pubkey,
+    party_pda: &Pubkey,
+    vote_type: VoteType,
+    extra_accounts: &[AccountMeta],
+) -> solana_program_test::BanksClientError {
+    let (ix, _voter_pda) = vote_instruction(payer, program_id, poll_pda, party_pda, vote_type, extra_accounts);
+    let tx = Transaction::new_signed_with_payer(&[ix], Some(&payer.pubkey()), &[payer], *recent_blockhash);
+
+    banks_client.process_transaction(tx).await.unwrap_err()
+}
+
+// Vote passing an arbitrary account in place of the voter PDA
+#[allow(clippy::too_many_arguments)]
+pub async fn vote_with_voter_account_expect_fail(
+    banks_client: &mut BanksClient,
+    payer: &Keypair,
+    recent_blockhash: &Hash,
+    program_id: &Pubkey,
+    poll_pda: &Pubkey,
+    party_pda: &Pubkey,
+    voter_account: &Pubkey,
+    vote_type: VoteType,
+) -> solana_program_test::BanksClientError {
+    let (mut ix, _voter_pda) = vote_instruction(payer, program_id, poll_pda, party_pda, vote_type, &[]);
+    ix.accounts[4] = AccountMeta::new(*voter_account, false);
+    let tx = Transaction::new_signed_with_payer(&[ix], Some(&payer.pubkey()), &[payer], *recent_blockhash);
+
+    banks_client.process_transaction(tx).await.unwrap_err()
+}
+
+
+#[derive(BorshSerialize)]
+struct CastBallotPayload {
+    ballot: Vec<BallotEntry>,
+}
+
+pub fn cast_ballot_instruction(
+    payer: &Keypair,
+    program_id: &Pubkey,
+    poll_pda: &Pubkey,
+    ballot: &[BallotEntry],
+    party_pdas: &[Pubkey],
+) -> (Instruction, Pubkey) {
+    let (voter_pda, _bump_seeds) = Pubkey::find_program_address(
+        &[b"voter", poll_pda.as_ref(), payer.pubkey().as_ref()],
+        program_id
+    );
+
+    let mut data = vec![25u8]; // discriminator = 25 (CastBallot)
+    let payload = CastBallotPayload { ballot: ballot.to_vec() };
+    data.extend(payload.try_to_vec().expect("borsh serialize"));
+
+    let mut accounts = vec![
+        AccountMeta::new(payer.pubkey(), true),
+        AccountMeta::new(*poll_pda, false),
+        AccountMeta::new_readonly(config_pda(program_id), false),
+        AccountMeta::new(voter_pda, false),
+        AccountMeta::new_readonly(system_program::id(), false),
+    ];
+    accounts.extend(party_pdas.iter().map(|party_pda| AccountMeta::new(*party_pda, false)));
+
+    let ix = Instruction {
+        program_id: *program_id,
+        accounts,
+        data,
+    };
+
+    (ix, voter_pda)
+}
+
+pub async fn cast_ballot(
+    banks_client: &mut BanksClient,
+    payer: &Keypair,
+    recent_blockhash: &Hash,
+    program_id: &Pubkey,
+    poll_pda: &Pubkey,
+    ballot: &[BallotEntry],
+) -> Pubkey {
+    let party_pdas: Vec<Pubkey> = ballot.iter().map(|entry| entry.party).collect();
+    let (ix, voter_pda) = cast_ballot_instruction(payer, program_id, poll_pda, ballot, &party_pdas);
+    let tx = Transaction::new_signed_with_payer(&[ix], Some(&payer.pubkey()), &[payer], *recent_blockhash);
+    banks_client.process_transaction(tx).await.unwrap();
+
+    voter_pda
+}
+
+pub async fn cast_ballot_expect_fail(
+    banks_client: &mut BanksClient,
+    payer: &Keypair,
+    recent_blockhash: &Hash,
+    program_id: &Pubkey,
+    poll_pda: &Pubkey,
+    ballot: &[BallotEntry],
+) -> solana_program_test::BanksClientError {
+    let party_pdas: Vec<Pubkey> = ballot.iter().map(|entry| entry.party).collect();
+    cast_ballot_with_parties_expect_fail(banks_client, payer, recent_blockhash, program_id, poll_pda, ballot, &party_pdas).await
+}
+
+// Ballot passing party accounts that do not have to match its entries
+#[allow(clippy::too_many_arguments)]
+pub async fn cast_ballot_with_parties_expect_fail(
+    banks_client: &mut BanksClient,
+    payer: &Keypair,
+    recent_blockhash: &Hash,
+    program_id: &Pubkey,
+    poll_pda: &Pubkey,
+    ballot: &[BallotEntry],
+    party_pdas: &[Pubkey],
+) -> solana_program_test::BanksClientError {
+    let (ix, _voter_pda) = cast_ballot_instruction(payer, program_id, poll_pda, ballot, party_pdas);
+    let tx = Transaction::new_signed_with_payer(&[ix], Some(&payer.pubkey()), &[payer], *recent_blockhash);
+
+    banks_client.process_transaction(tx).await.unwrap_err()
+}
+
+
+// Compute units the program reports for a transaction, run as a simulation
+pub async fn simulate_compute_units(
+    banks_client: &mut BanksClient,
+    payer: &Keypair,
+    recent_blockhash: &Hash,
+    instructions: &[Instruction],
+) -> u64 {
+    let tx = Transaction::new_signed_with_payer(instructions, Some(&payer.pubkey()), &[payer], *recent_blockhash);
+    let simulation = banks_client.simulate_transaction(tx).await.unwrap();
+    simulation.result.unwrap().unwrap();
+
+    simulation.simulation_details.unwrap().units_consumed
+}
+
+
+pub fn end_voting_instruction(
+    payer: &Keypair,
+    program_id: &Pubkey,
+    poll_pda: &Pubkey,
+    party_pdas: &[Pubkey],
+) -> (Instruction, Pubkey) {
+    let (results_pda, _bump_seeds) = Pubkey::find_program_address(
+        &[b"results", poll_pda.as_ref()],
+        program_id
+    );
+
+    let data = vec![6u8]; // discriminator = 6 (EndVoting)
+
+    let mut accounts = vec![
+        AccountMeta::new(payer.pubkey(), true),
+        AccountMeta::new(*poll_pda, false),
+        AccountMeta::new_readonly(config_pda(program_id), false),
+        AccountMeta::new(results_pda, false),
+        AccountMeta::new_readonly(system_program::id(), false),
+    ];
+    accounts.extend(party_pdas.iter().map(|party_pda| AccountMeta::new_readonly(*party_pda, false)));
+
+    let ix = Instruction {
+        program_id: *program_id,
+        accounts,
+        data,
+    };
+
+    (ix, results_pda)
+}
+
+pub async fn end_voting(
+    banks_client: &mut BanksClient,
+    payer: &Keypair,
+    recent_blockhash: &Hash,
+    program_id: &Pubkey,
+    poll_pda: &Pubkey,
+    party_pdas: &[Pubkey],
+) -> Pubkey {
+    let (ix, results_pda) = end_voting_instruction(payer, program_id, poll_pda, party_pdas);
+    let tx = Transaction::new_signed_with_payer(&[ix], Some(&payer.pubkey()), &[payer], *recent_blockhash);
+    banks_client.process_transaction(tx).await.unwrap();
+
+    results_pda
+}
+
+pub async fn end_voting_expect_fail(
+    banks_client: &mut BanksClient,
+    payer: &Keypair,
+    recent_blockhash: &Hash,
+    program_id: &Pubkey,
+    poll_pda: &Pubkey,
+    party_pdas: &[Pubkey],
+) -> solana_program_test::BanksClientError {
+    let (ix, _results_pda) = end_voting_instruction(payer, program_id, poll_pda, party_pdas);
+    let tx = Transaction::new_signed_with_payer(&[ix], Some(&payer.pubkey()), &[payer], *recent_blockhash);
+
+    banks_client.process_transaction(tx).await.unwrap_err()
+}
+
+
+#[derive(BorshSerialize)]
+struct SetCommitteePayload {
+    committee: Committee,
+}
+
+fn set_committee_instruction(
+    payer: &Keypair,
+    program_id: &Pubkey,
+    poll_pda: &Pubkey,
+    committee: Committee,
+) -> Instruction {
+    let mut data = vec![22u8]; // discriminator = 22 (SetCommittee)
+    let payload = SetCommitteePayload { committee };
+    data.extend(payload.try_to_vec().expect("borsh serialize"));
+
+    Instruction {
+        program_id: *program_id,
+        accounts: vec![
+            AccountMeta::new(payer.pubkey(), true),
+            AccountMeta::new(*poll_pda, false),
+            AccountMeta::new_readonly(config_pda(program_id), false),
+            AccountMeta::new_readonly(system_program::id(), false),
+        ],
+        data,
+    }
+}
+
+pub async fn set_committee(
+    banks_client: &mut BanksClient,
+    payer: &Keypair,
+    recent_blockhash: &Hash,
+    program_id: &Pubkey,
+    poll_pda: &Pubkey,
+    committee: Committee,
+) {
+    let ix = set_committee_instruction(payer, program_id, poll_pda, committee);
+    let tx = Transaction::new_signed_with_payer(&[ix], Some(&payer.pubkey()), &[payer], *recent_blockhash);
+    banks_client.process_transaction(tx).await.unwrap();
+}
+
+pub async fn set_committee_expect_fail(
+    banks_client: &mut BanksClient,
+    payer: &Keypair,
+    recent_blockhash: &Hash,
+    program_id: &Pubkey,
+    poll_pda: &Pubkey,
+    committee: Committee,
+) -> solana_program_test::BanksClientError {
+    let ix = set_committee_instruction(payer, program_id, poll_pda, committee);
+    let tx = Transaction::new_signed_with_payer(&[ix], Some(&payer.pubkey()), &[payer], *recent_blockhash);
+
+    banks_client.process_transaction(tx).await.unwrap_err()
+}
+
+#[derive(BorshSerialize)]
+struct ProposeActionPayload {
+    action: ProposalAction,
+}
+
+// Proposals are numbered by the poll's proposal counter
+pub async fn propose_action(
+    banks_client: &mut BanksClient,
+    payer: &Keypair,
+    recent_blockhash: &Hash,
+    program_id: &Pubkey,
+    poll_pda: &Pubkey,
+    action: ProposalAction,
+) -> Pubkey {
+    let poll_account = banks_client.get_account(*poll_pda).await.unwrap().unwrap();
+    let proposal_index = PollState::deserialize(&mut &poll_account.data[..]).unwrap().proposal_counter;
+    let (proposal_pda, _bump_seeds) = Pubkey::find_program_address(
+        &[b"proposal", poll_pda.as_ref(), &proposal_index.to_le_bytes()],
+        program_id
+    );
+
+    let mut data = vec![23u8]; // discriminator = 23 (ProposeAction)
+    let payload = ProposeActionPayload { action };
+    data.extend(payload.try_to_vec().expect("borsh serialize"));
+
+    let ix = Instruction {
+        program_id: *program_id,
+        accounts: vec![
+            AccountMeta::new(payer.pubkey(), true),
+            AccountMeta::new(*poll_pda, false),
+            AccountMeta::new_readonly(config_pda(program_id), false),
+            AccountMeta::new(proposal_pda, false),
+            AccountMeta::new_readonly(system_program::id(), false),
+        ],
+        data,
+    };
+    let tx = Transaction::new_signed_with_payer(&[ix], Some(&payer.pubkey()), &[payer], *recent_blockhash);
+    banks_client.process_transaction(tx).await.unwrap();
+
+    proposal_pda
+}
+
+fn approve_proposal_instruction(
+    payer: &Keypair,
+    program_id: &Pubkey,
+    poll_pda: &Pubkey,
+    proposal_pda: &Pubkey,
+) -> Instruction {
+    let data = vec![24u8]; // discriminator = 24 (ApproveProposal)
+
+    Instruction {
+        program_id: *program_id,
+        accounts: vec![
+            AccountMeta::new(payer.pubkey(), true),
+            AccountMeta::new_readonly(*poll_pda, false),
+            AccountMeta::new_readonly(config_pda(program_id), false),
+            AccountMeta::new(*proposal_pda, false),
+        ],
+        data,
+    }
+}
+
+pub async fn approve_proposal(
+    banks_client: &mut BanksClient,
+    payer: &Keypair,
+    recent_blockhash: &Hash,
+    program_id: &Pubkey,
+    poll_pda: &Pubkey,
+    proposal_pda: &Pubkey,
+) {
+    let ix = approve_proposal_instruction(payer, program_id, poll_pda, proposal_pda);
+    let tx = Transaction::new_signed_with_payer(&[ix], Some(&payer.pubkey()), &[payer], *recent_blockhash);
+    banks_client.process_transaction(tx).await.unwrap();
+}
+
+pub async fn approve_proposal_expect_fail(
+    banks_client: &mut BanksClient,
+    payer: &Keypair,
+    recent_blockhash: &Hash,
+    program_id: &Pubkey,
+    poll_pda: &Pubkey,
+    proposal_pda: &Pubkey,
+) -> solana_program_test::BanksClientError {
+    let ix = approve_proposal_instruction(payer, program_id, poll_pda, proposal_pda);
+    let tx = Transaction::new_signed_with_payer(&[ix], Some(&payer.pubkey()), &[payer], *recent_blockhash);
+
+    banks_client.process_transaction(tx).await.unwrap_err()
+}
+
+// Committee polls pass the approved proposal after the fixed accounts of the gated instruction
+#[allow(clippy::too_many_arguments)]
+pub async fn execute_with_proposal(
+    banks_client: &mut BanksClient,
+    payer: &Keypair,
+    recent_blockhash: &Hash,
+    program_id: &Pubkey,
+    poll_pda: &Pubkey,
+    proposal_pda: &Pubkey,
+    action: &ProposalAction,
+    party_pdas: &[Pubkey],
+) -> Result<(), solana_program_test::BanksClientError> {
+    let ix = match action {
+        ProposalAction::StartVoting => Instruction {
+            program_id: *program_id,
+            accounts: vec![
+                AccountMeta::new(payer.pubkey(), true),
+                AccountMeta::new(*poll_pda, false),
+                AccountMeta::new_readonly(config_pda(program_id), false),
+                AccountMeta::new(*proposal_pda, false),
+            ],
+            data: vec![4u8], // discriminator = 4 (StartVoting)
+        },
+        ProposalAction::CreateParty { title, description, metadata_uri, metadata_hash } => {
+            let metadata = PartyMetadata {
+                description: description.clone(),
+                metadata_uri: metadata_uri.clone(),
+                metadata_hash: *metadata_hash,
+            };
+            let (mut ix, _party_pda) = create_party_instruction(payer, program_id, title, poll_pda, metadata);
+            ix.accounts.push(AccountMeta::new(*proposal_pda, false));
+            ix
+        }
+        ProposalAction::EndVoting => {
+            let (mut ix, _results_pda) = end_voting_instruction(payer, program_id, poll_pda, party_pdas);
+            ix.accounts.insert(5, AccountMeta::new(*proposal_pda, false));
+            ix
+        }
+        ProposalAction::RemoveParty { party } => {
+            let mut ix = remove_party_instruction(payer, program_id, poll_pda, party);
+            ix.accounts.push(AccountMeta::new(*proposal_pda, false));
+            ix
+        }
+        ProposalAction::UpdatePartyMetadata { party, description, metadata_uri, metadata_hash } => {
+            let metadata = PartyMetadata {
+                description: description.clone(),
+                metadata_uri: metadata_uri.clone(),
+                metadata_hash: *metadata_hash,
+            };
+            let mut ix = update_party_metadata_instruction(payer, program_id, poll_pda, party, metadata);
+            ix.accounts.push(AccountMeta::new(*proposal_pda, false));
+            ix
+        }
+        ProposalAction::UpdatePoll { title, description } => {
+            let mut ix = update_poll_instruction(payer, program_id, poll_pda, title, description);
+            ix.accounts.push(AccountMeta::new(*proposal_pda, false));
+            ix
+        }
+        ProposalAction::RegisterVoter { voter } => {
+            let (mut ix, _eligible_pda) = register_voter_instruction(payer, program_id, poll_pda, voter);
+            ix.accounts.push(AccountMeta::new(*proposal_pda, false));
+            ix
+        }
+        ProposalAction::UnregisterVoter { voter } => {
+            let mut ix = unregister_voter_instruction(payer, program_id, poll_pda, voter);
+            ix.accounts.push(AccountMeta::new(*proposal_pda, false));
+            ix
+        }
+        ProposalAction::SetPollPaused { paused, extend_deadline } => {
+            let mut ix = set_poll_paused_instruction(payer, program_id, poll_pda, *paused, *extend_deadline);
+            ix.accounts.push(AccountMeta::new(*proposal_pda, false));
+            ix
+        }
+    };
+    let tx = Transaction::new_signed_with_payer(&[ix], Some(&payer.pubkey()), &[payer], *recent_blockhash);
+
+    banks_client.process_transaction(tx).await
+}
+
+
+fn migrate_account_instruction(
+    payer: &Keypair,
+    program_id: &Pubkey,
+    account: &Pubkey,
+    poll: Option<&Pubkey>,
+) -> Instruction {
+    let data = vec![7u8]; // discriminator = 7 (MigrateAccount)
+
+    let mut accounts = vec![
+        AccountMeta::new(payer.pubkey(), true),
+        AccountMeta::new(*account, false),
+        AccountMeta::new_readonly(system_program::id(), false),
+    ];
+    if let Some(poll) = poll {
+        accounts.push(AccountMeta::new_readonly(*poll, false));
+    }
+
+    Instruction {
+        program_id: *program_id,
+        accounts,
+        data,
+    }
+}
+
+pub async fn migrate_account(
+    banks_client: &mut BanksClient,
+    payer: &Keypair,
+    recent_blockhash: &Hash,
+    program_id: &Pubkey,
+    account: &Pubkey,
+) -> Pubkey {
+    let ix = migrate_account_instruction(payer, program_id, account, None);
+    let tx = Transaction::new_signed_with_payer(&[ix], Some(&payer.pubkey()), &[payer], *recent_blockhash);
+    banks_client.process_transaction(tx).await.unwrap();
+
+    *account
+}
+
+pub async fn migrate_account_expect_fail(
+    banks_client: &mut BanksClient,
+    payer: &Keypair,
+    recent_blockhash: &Hash,
+    program_id: &Pubkey,
+    account: &Pubkey,
+) -> solana_program_test::BanksClientError {
+    let ix = migrate_account_instruction(payer, program_id, account, None);
+    let tx = Transaction::new_signed_with_payer(&[ix], Some(&payer.pubkey()), &[payer], *recent_blockhash);
+
+    banks_client.process_transaction(tx).await.unwrap_err()
+}
+
+// First release parties are migrated together with their poll
+pub async fn migrate_party(
+    banks_client: &mut BanksClient,
+    payer: &Keypair,
+    recent_blockhash: &Hash,
+    program_id: &Pubkey,
+    party: &Pubkey,
+    poll: &Pubkey,
+) -> Pubkey {
+    let ix = migrate_account_instruction(payer, program_id, party, Some(poll));
+    let tx = Transaction::new_signed_with_payer(&[ix], Some(&payer.pubkey()), &[payer], *recent_blockhash);
+    banks_client.process_transaction(tx).await.unwrap();
+
+    *party
+}
+
+pub fn retract_vote_instruction(
+    payer: &Keypair,
+    program_id: &Pubkey,
+    poll_pda: &Pubkey,
+    party_pda: &Pubkey,
+) -> Instruction {
+    let (voter_pda, _bump_seeds) = Pubkey::find_program_address(
+        &[b"voter", poll_pda.as_ref(), payer.pubkey().as_ref()],
+        program_id
+    );
+
+    let data = vec![8u8]; // discriminator = 8 (RetractVote)
+
+    Instruction {
+        program_id: *program_id,
+        accounts: vec![
+            AccountMeta::new(payer.pubkey(), true),
+            AccountMeta::new_readonly(*poll_pda, false),
+            AccountMeta::new_readonly(config_pda(program_id), false),
+            AccountMeta::new(*party_pda, false),
+            AccountMeta::new(voter_pda, false),
+        ],
+        data,
+    }
+}
+
+pub async fn retract_vote(
+    banks_client: &mut BanksClient,
+    payer: &Keypair,
+    recent_blockhash: &Hash,
+    program_id: &Pubkey,
+    poll_pda: &Pubkey,
+    party_pda: &Pubkey,
+) {
+    let ix = retract_vote_instruction(payer, program_id, poll_pda, party_pda);
+    let tx = Transaction::new_signed_with_payer(&[ix], Some(&payer.pubkey()), &[payer], *recent_blockhash);
+    banks_client.process_transaction(tx).await.unwrap();
+}
+
+pub async fn retract_vote_expect_fail(
+    banks_client: &mut BanksClient,
+    payer: &Keypair,
+    recent_blockhash: &Hash,
+    program_id: &Pubkey,
+    poll_pda: &Pubkey,
+    party_pda: &Pubkey,
+) -> solana_program_test::BanksClientError {
+    let ix = retract_vote_instruction(payer, program_id, poll_pda, party_pda);
+    let tx = Transaction::new_signed_with_payer(&[ix], Some(&payer.pubkey()), &[payer], *recent_blockhash);
+
+    banks_client.process_transaction(tx).await.unwrap_err()
+}
+
+
+#[derive(BorshSerialize)]
+struct RegisterVoterPayload {
+    voter: Pubkey,
+}
+
+fn register_voter_instruction(
+    payer: &Keypair,
+    program_id: &Pubkey,
+    poll_pda: &Pubkey,
+    voter: &Pubkey,
+) -> (Instruction, Pubkey) {
+    let (eligible_pda, _bump_seeds) = Pubkey::find_program_address(
+        &[b"eligible", poll_pda.as_ref(), voter.as_ref()],
+        program_id
+    );
+
+    let mut data = vec![9u8]; // discriminator = 9 (RegisterVoter)
+    let payload = RegisterVoterPayload { voter: *voter };
+    data.extend(payload.try_to_vec().expect("borsh serialize"));
+
+    let ix = Instruction {
+        program_id: *program_id,
+        accounts: vec![
+            AccountMeta::new(payer.pubkey(), true),
+            AccountMeta::new(*poll_pda, false),
+            AccountMeta::new_readonly(config_pda(program_id), false),
+            AccountMeta::new(eligible_pda, false),
+            AccountMeta::new_readonly(system_program::id(), false),
+        ],
+        data,
+    };
+
+    (ix, eligible_pda)
+}
+
+pub async fn register_voter(
+    banks_client: &mut BanksClient,
+    payer: &Keypair,
+    recent_blockhash: &Hash,
+    program_id: &Pubkey,
+    poll_pda: &Pubkey,
+    voter: &Pubkey,
+) -> Pubkey {
+    let (ix, eligible_pda) = register_voter_instruction(payer, program_id, poll_pda, voter);
+    let tx = Transaction::new_signed_with_payer(&[ix], Some(&payer.pubkey()), &[payer], *recent_blockhash);
+    banks_client.process_transaction(tx).await.unwrap();
+
+    eligible_pda
+}
+
+pub async fn register_voter_expect_fail(
+    banks_client: &mut BanksClient,
+    payer: &Keypair,
+    recent_blockhash: &Hash,
+    program_id: &Pubkey,
+    poll_pda: &Pubkey,
+    voter: &Pubkey,
+) -> solana_program_test::BanksClientError {
+    let (ix, _eligible_pda) = register_voter_instruction(payer, program_id, poll_pda, voter);
+    let tx = Transaction::new_signed_with_payer(&[ix], Some(&payer.pubkey()), &[payer], *recent_blockhash);
+
+    banks_client.process_transaction(tx).await.unwrap_err()
+}
+
+fn unregister_voter_instruction(
+    payer: &Keypair,
+    program_id: &Pubkey,
+    poll_pda: &Pubkey,
+    voter: &Pubkey,
+) -> Instruction {
+    let (eligible_pda, _bump_seeds) = Pubkey::find_program_address(
+        &[b"eligible", poll_pda.as_ref(), voter.as_ref()],
+        program_id
+    );
+
+    let mut data = vec![26u8]; // discriminator = 26 (UnregisterVoter)
+    let payload = RegisterVoterPayload { voter: *voter };
+    data.extend(payload.try_to_vec().expect("borsh serialize"));
+
+    Instruction {
+        program_id: *program_id,
+        accounts: vec![
+            AccountMeta::new(payer.pubkey(), true),
+            AccountMeta::new(*poll_pda, false),
+            AccountMeta::new_readonly(config_pda(program_id), false),
+            AccountMeta::new(eligible_pda, false),
+            AccountMeta::new(payer.pubkey(), false),
+        ],
+        data,
+    }
+}
+
+pub async fn unregister_voter(
+    banks_client: &mut BanksClient,
+    payer: &Keypair,
+    recent_blockhash: &Hash,
+    program_id: &Pubkey,
+    poll_pda: &Pubkey,
+    voter: &Pubkey,
+) {
+    let ix = unregister_voter_instruction(payer, program_id, poll_pda, voter);
+    let tx = Transaction::new_signed_with_payer(&[ix], Some(&payer.pubkey()), &[payer], *recent_blockhash);
+    banks_client.process_transaction(tx).await.unwrap();
+}
+
+pub async fn unregister_voter_expect_fail(
+    banks_client: &mut BanksClient,
+    payer: &Keypair,
+    recent_blockhash: &Hash,
+    program_id: &Pubkey,
+    poll_pda: &Pubkey,
+    voter: &Pubkey,
+) -> solana_program_test::BanksClientError {
+    let ix = unregister_voter_instruction(payer, program_id, poll_pda, voter);
+    let tx = Transaction::new_signed_with_payer(&[ix], Some(&payer.pubkey()), &[payer], *recent_blockhash);
+
+    banks_client.process_transaction(tx).await.unwrap_err()
+}
+
+
+pub fn vault_pda(program_id: &Pubkey, poll_pda: &Pubkey) -> Pubkey {
+    Pubkey::find_program_address(&[b"vault", poll_pda.as_ref()], program_id).0
+}
+
+// Extra vote accounts for the first vote in a token-weighted poll
+pub fn token_weighted_vote_accounts(program_id: &Pubkey, poll_pda: &Pubkey, token_account: &Pubkey, mint: &Pubkey) -> Vec<AccountMeta> {
+    vec![
+        AccountMeta::new(*token_account, false),
+        AccountMeta::new(vault_pda(program_id, poll_pda), false),
+        AccountMeta::new_readonly(*mint, false),
+        AccountMeta::new_readonly(spl_token::id(), false),
+    ]
+}
+
+fn unlock_tokens_instruction(
+    payer: &Keypair,
+    program_id: &Pubkey,
+    poll_pda: &Pubkey,
+    token_account: &Pubkey,
+) -> Instruction {
+    let (voter_pda, _bump_seeds) = Pubkey::find_program_address(
+        &[b"voter", poll_pda.as_ref(), payer.pubkey().as_ref()],
+        program_id
+    );
+
+    let data = vec![10u8]; // discriminator = 10 (UnlockTokens)
+
+    Instruction {
+        program_id: *program_id,
+        accounts: vec![
+            AccountMeta::new(payer.pubkey(), true),
+            AccountMeta::new_readonly(*poll_pda, false),
+            AccountMeta::new_readonly(config_pda(program_id), false),
+            AccountMeta::new(voter_pda, false),
+            AccountMeta::new(*token_account, false),
+            AccountMeta::new(vault_pda(program_id, poll_pda), false),
+            AccountMeta::new_readonly(spl_token::id(), false),
+        ],
+        data,
+    }
+}
+
+pub async fn unlock_tokens(
+    banks_client: &mut BanksClient,
+    payer: &Keypair,
+    recent_blockhash: &Hash,
+    program_id: &Pubkey,
+    poll_pda: &Pubkey,
+    token_account: &Pubkey,
+) {
+    let ix = unlock_tokens_instruction(payer, program_id, poll_pda, token_account);
+    let tx = Transaction::new_signed_with_payer(&[ix], Some(&payer.pubkey()), &[payer], *recent_blockhash);
+    banks_client.process_transaction(tx).await.unwrap();
+}
+
+pub async fn unlock_tokens_expect_fail(
+    banks_client: &mut BanksClient,
+    payer: &Keypair,
+    recent_blockhash: &Hash,
+    program_id: &Pubkey,
+    poll_pda: &Pubkey,
+    token_account: &Pubkey,
+) -> solana_program_test::BanksClientError {
+    let ix = unlock_tokens_instruction(payer, program_id, poll_pda, token_account);
+    let tx = Transaction::new_signed_with_payer(&[ix], Some(&payer.pubkey()), &[payer], *recent_blockhash);
+
+    banks_client.process_transaction(tx).await.unwrap_err()
+}
+
+
+fn close_poll_instruction(
+    payer: &Keypair,
+    program_id: &Pubkey,
+    poll_pda: &Pubkey,
+    rent_payer: &Pubkey,
+    vault_rent_payer: &Pubkey,
+) -> Instruction {
+    let (results_pda, _bump_seeds) = Pubkey::find_program_address(
+        &[b"results", poll_pda.as_ref()],
+        program_id
+    );
+
+    let data = vec![11u8]; // discriminator = 11 (ClosePoll)
+
+    // the poll rent payer also ran EndVoting
+    Instruction {
+        program_id: *program_id,
+        accounts: vec![
+            AccountMeta::new(payer.pubkey(), true),
+            AccountMeta::new(*poll_pda, false),
+            AccountMeta::new_readonly(config_pda(program_id), false),
+            AccountMeta::new(*rent_payer, false),
+            AccountMeta::new(results_pda, false),
+            AccountMeta::new(*rent_payer, false),
+            AccountMeta::new(vault_pda(program_id, poll_pda), false),
+            AccountMeta::new(*vault_rent_payer, false),
+            AccountMeta::new_readonly(spl_token::id(), false),
+        ],
+        data,
+    }
+}
+
+pub async fn close_poll(
+    banks_client: &mut BanksClient,
+    payer: &Keypair,
+    recent_blockhash: &Hash,
+    program_id: &Pubkey,
+    poll_pda: &Pubkey,
+) {
+    let ix = close_poll_instruction(payer, program_id, poll_pda, &payer.pubkey(), &payer.pubkey());
+    let tx = Transaction::new_signed_with_payer(&[ix], Some(&payer.pubkey()), &[payer], *recent_blockhash);
+    banks_client.process_transaction(tx).await.unwrap();
+}
+
+pub async fn close_poll_expect_fail(
+    banks_client: &mut BanksClient,
+    payer: &Keypair,
+    recent_blockhash: &Hash,
+    program_id: &Pubkey,
+    poll_pda: &Pubkey,
+) -> solana_program_test::BanksClientError {
+    let ix = close_poll_instruction(payer, program_id, poll_pda, &payer.pubkey(), &payer.pubkey());
+    let tx = Transaction::new_signed_with_payer(&[ix], Some(&payer.pubkey()), &[payer], *recent_blockhash);
+
+    banks_client.process_transaction(tx).await.unwrap_err()
+}
+
+// Token vault rent goes back to the voter whose first vote created it
+pub async fn close_token_weighted_poll(
+    banks_client: &mut BanksClient,
+    payer: &Keypair,
+    recent_blockhash: &Hash,
+    program_id: &Pubkey,
+    poll_pda: &Pubkey,
+    vault_rent_payer: &Pubkey,
+) {
+    let ix = close_poll_instruction(payer, program_id, poll_pda, &payer.pubkey(), vault_rent_payer);
+    let tx = Transaction::new_signed_with_payer(&[ix], Some(&payer.pubkey()), &[payer], *recent_blockhash);
+    banks_client.process_transaction(tx).await.unwrap();
+}
+
+fn close_party_instruction(
+    payer: &Keypair,
+    program_id: &Pubkey,
+    poll_pda: &Pubkey,
+    party_pda: &Pubkey,
+    rent_payer: &Pubkey,
+) -> Instruction {
+    let data = vec![12u8]; // discriminator = 12 (CloseParty)
+
+    Instruction {
+        program_id: *program_id,
+        accounts: vec![
+            AccountMeta::new(payer.pubkey(), true),
+            AccountMeta::new(*poll_pda, false),
+            AccountMeta::new_readonly(config_pda(program_id), false),
+            AccountMeta::new(*party_pda, false),
+            AccountMeta::new(*rent_payer, false),
+        ],
+        data,
+    }
+}
+
+pub async fn close_party(
+    banks_client: &mut BanksClient,
+    payer: &Keypair,
+    recent_blockhash: &Hash,
+    program_id: &Pubkey,
+    poll_pda: &Pubkey,
+    party_pda: &Pubkey,
+) {
+    let ix = close_party_instruction(payer, program_id, poll_pda, party_pda, &payer.pubkey());
+    let tx = Transaction::new_signed_with_payer(&[ix], Some(&payer.pubkey()), &[payer], *recent_blockhash);
+    banks_client.process_transaction(tx).await.unwrap();
+}
+
+pub async fn close_party_expect_fail(
+    banks_client: &mut BanksClient,
+    payer: &Keypair,
+    recent_blockhash: &Hash,
+    program_id: &Pubkey,
+    poll_pda: &Pubkey,
+    party_pda: &Pubkey,
+) -> solana_program_test::BanksClientError {
+    let ix = close_party_instruction(payer, program_id, poll_pda, party_pda, &payer.pubkey());
+    let tx = Transaction::new_signed_with_payer(&[ix], Some(&payer.pubkey()), &[payer], *recent_blockhash);
+
+    banks_client.process_transaction(tx).await.unwrap_err()
+}
+
+fn close_voter_instruction(
+    payer: &Keypair,
+    program_id: &Pubkey,
+    poll_pda: &Pubkey,
+) -> Instruction {
+    let (voter_pda, _bump_seeds) = Pubkey::find_program_address(
+        &[b"voter", poll_pda.as_ref(), payer.pubkey().as_ref()],
+        program_id
+    );
+
+    let data = vec![13u8]; // discriminator = 13 (CloseVoter)
+
+    Instruction {
+        program_id: *program_id,
+        accounts: vec![
+            AccountMeta::new(payer.pubkey(), true),
+            AccountMeta::new_readonly(*poll_pda, false),
+            AccountMeta::new_readonly(config_pda(program_id), false),
+            AccountMeta::new(voter_pda, false),
+        ],
+        data,
+    }
+}
+
+pub async fn close_voter(
+    banks_client: &mut BanksClient,
+    payer: &Keypair,
+    recent_blockhash: &Hash,
+    program_id: &Pubkey,
+    poll_pda: &Pubkey,
+) {
+    let ix = close_voter_instruction(payer, program_id, poll_pda);
+    let tx = Transaction::new_signed_with_payer(&[ix], Some(&payer.pubkey()), &[payer], *recent_blockhash);
+    banks_client.process_transaction(tx).await.unwrap();
+}
+
+pub async fn close_voter_expect_fail(
+    banks_client: &mut BanksClient,
+    payer: &Keypair,
+    recent_blockhash: &Hash,
+    program_id: &Pubkey,
+    poll_pda: &Pubkey,
+) -> solana_program_test::BanksClientError {
+    let ix = close_voter_instruction(payer, program_id, poll_pda);
+    let tx = Transaction::new_signed_with_payer(&[ix], Some(&payer.pubkey()), &[payer], *recent_blockhash);
+
+    banks_client.process_transaction(tx).await.unwrap_err()
+}
+
+fn close_eligible_voter_instruction(
+    payer: &Keypair,
+    program_id: &Pubkey,
+    poll_pda: &Pubkey,
+    voter: &Pubkey,
+) -> Instruction {
+    let (eligible_pda, _bump_seeds) = Pubkey::find_program_address(
+        &[b"eligible", poll_pda.as_ref(), voter.as_ref()],
+        program_id
+    );
+
+    let data = vec![27u8]; // discriminator = 27 (CloseEligibleVoter)
+
+    Instruction {
+        program_id: *program_id,
+        accounts: vec![
+            AccountMeta::new(payer.pubkey(), true),
+            AccountMeta::new(*poll_pda, false),
+            AccountMeta::new_readonly(config_pda(program_id), false),
+            AccountMeta::new(eligible_pda, false),
+            AccountMeta::new(payer.pubkey(), false),
+        ],
+        data,
+    }
+}
+
+pub async fn close_eligible_voter(
+    banks_client: &mut BanksClient,
+    payer: &Keypair,
+    recent_blockhash: &Hash,
+    program_id: &Pubkey,
+    poll_pda: &Pubkey,
+    voter: &Pubkey,
+) {
+    let ix = close_eligible_voter_instruction(payer, program_id, poll_pda, voter);
+    let tx = Transaction::new_signed_with_payer(&[ix], Some(&payer.pubkey()), &[payer], *recent_blockhash);
+    banks_client.process_transaction(tx).await.unwrap();
+}
+
+fn close_proposal_instruction(
+    payer: &Keypair,
+    program_id: &Pubkey,
+    poll_pda: &Pubkey,
+    proposal_pda: &Pubkey,
+) -> Instruction {
+    let data = vec![28u8]; // discriminator = 28 (CloseProposal)
+
+    Instruction {
+        program_id: *program_id,
+        accounts: vec![
+            AccountMeta::new(payer.pubkey(), true),
+            AccountMeta::new_readonly(*poll_pda, false),
+            AccountMeta::new_readonly(config_pda(program_id), false),
+            AccountMeta::new(*proposal_pda, false),
+        ],
+        data,
+    }
+}
+
+pub async fn close_proposal(
+    banks_client: &mut BanksClient,
+    payer: &Keypair,
+    recent_blockhash: &Hash,
+    program_id: &Pubkey,
+    poll_pda: &Pubkey,
+    proposal_pda: &Pubkey,
+) {
+    let ix = close_proposal_instruction(payer, program_id, poll_pda, proposal_pda);
+    let tx = Transaction::new_signed_with_payer(&[ix], Some(&payer.pubkey()), &[payer], *recent_blockhash);
+    banks_client.process_transaction(tx).await.unwrap();
+}
+
+pub async fn close_proposal_expect_fail(
+    banks_client: &mut BanksClient,
+    payer: &Keypair,
+    recent_blockhash: &Hash,
+    program_id: &Pubkey,
+    poll_pda: &Pubkey,
+    proposal_pda: &Pubkey,
+) -> solana_program_test::BanksClientError {
+    let ix = close_proposal_instruction(payer, program_id, poll_pda, proposal_pda);
+    let tx = Transaction::new_signed_with_payer(&[ix], Some(&payer.pubkey()), &[payer], *recent_blockhash);
+
+    banks_client.process_transaction(tx).await.unwrap_err()
+}
+
+
+// Writes an initialized SPL mint straight into the test bank
+pub fn set_mint(ctx: &mut ProgramTestContext, mint_authority: &Pubkey) -> Pubkey {
+    let mint = Pubkey::new_unique();
+    let mut data = vec![0u8; spl_token::state::Mint::LEN];
+    spl_token::state::Mint::pack(
+        spl_token::state::Mint {
+            mint_authority: COption::Some(*mint_authority),
+            supply: 0,
+            decimals: 0,
+            is_initialized: true,
+            freeze_authority: COption::None,
+        },
+        &mut data,
+    ).unwrap();
+
+    ctx.set_account(&mint, &Account {
+        lamports: 1_000_000_000,
+        data,
+        owner: spl_token::id(),
+        executable: false,
+        rent_epoch: 0,
+    }.into());
+
+    mint
+}
+
+// Writes an initialized SPL token account straight into the test bank
+pub fn set_token_account(ctx: &mut ProgramTestContext, mint: &Pubkey, owner: &Pubkey, amount: u64) -> Pubkey {
+    let token_account = Pubkey::new_unique();
+    let mut data = vec![0u8; spl_token::state::Account::LEN];
+    spl_token::state::Account::pack(
+        spl_token::state::Account {
+            mint: *mint,
+            owner: *owner,
+            amount,
+            delegate: COption::None,
+            state: spl_token::state::AccountState::Initialized,
+            is_native: COption::None,
+            delegated_amount: 0,
+            close_authority: COption::None,
+        },
+        &mut data,
+    ).unwrap();
+
+    ctx.set_account(&token_account, &Account {
+        lamports: 1_000_000_000,
+        data,
+        owner: spl_token::id(),
+        executable: false,
+        rent_epoch: 0,
+    }.into());
+
+    token_account
 }
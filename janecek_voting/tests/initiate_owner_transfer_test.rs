@@ -15,7 +15,7 @@ async fn test_initiate_owner_transfer_success() {
 
     let poll_pda = create_poll(
         &mut ctx.banks_client,
-        &payer,
+        payer,
         &recent_blockhash,
         &program_id,
         &poll_title,
@@ -26,14 +26,14 @@ async fn test_initiate_owner_transfer_success() {
 
     initiate_owner_transfer(
         &mut ctx.banks_client,
-        &payer,
+        payer,
         &recent_blockhash,
         &program_id,
         &new_owner.pubkey(),
         &poll_pda,
     ).await;
 
-    let poll_state = PollState::try_from_slice(&ctx.banks_client.get_account(poll_pda).await.unwrap().unwrap().data).unwrap();
+    let poll_state = PollState::deserialize(&mut &ctx.banks_client.get_account(poll_pda).await.unwrap().unwrap().data[..]).unwrap();
     
     assert_eq!(poll_state.phase as u8, VotingPhase::Registration as u8);
     assert_eq!(poll_state.owner, payer.pubkey());
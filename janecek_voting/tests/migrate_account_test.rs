@@ -0,0 +1,385 @@
+mod helpers;
+use helpers::{setup_test_env, create_party, start_voting, start_voting_expect_fail, migrate_account, migrate_account_expect_fail, migrate_party, vote, vote_expect_fail, close_poll};
+use janecek_voting::state::{AccountType, LegacyPollState, PartyAccount, PollState, VoterAccount, VoteRules, VoteType, VotingPhase, ZeroCopyAccount};
+use borsh::{BorshDeserialize, BorshSerialize};
+use solana_program_test::ProgramTestContext;
+use solana_sdk::{account::Account, clock::Clock, pubkey::Pubkey, rent::Rent, signature::Keypair, signer::Signer, system_program, transaction::TransactionError, instruction::InstructionError};
+
+
+// Accounts as the first release wrote them: Borsh behind a string name, created at exactly their size
+fn baseline_string(text: &str) -> Vec<u8> {
+    let mut data = (text.len() as u32).to_le_bytes().to_vec();
+    data.extend_from_slice(text.as_bytes());
+    data
+}
+
+fn baseline_poll(owner: &Pubkey, phase: VotingPhase, party_counter: u64, created_at: i64, voting_start_at: i64) -> Vec<u8> {
+    LegacyPollState {
+        discriminator: "poll".to_string(),
+        title: "Presidential Election".to_string(),
+        description: "Vote for the next president".to_string(),
+        phase,
+        party_counter,
+        owner: *owner,
+        expected_new_owner: *owner,
+        created_at,
+        voting_start_at,
+    }.try_to_vec().unwrap()
+}
+
+fn baseline_party(poll: &Pubkey, title: &str, positive_votes: u64, negative_votes: u64) -> Vec<u8> {
+    let mut data = baseline_string("party");
+    data.extend_from_slice(poll.as_ref());
+    data.extend_from_slice(&baseline_string(title));
+    data.extend_from_slice(&positive_votes.to_le_bytes());
+    data.extend_from_slice(&negative_votes.to_le_bytes());
+    data
+}
+
+// Sized for the 3 votes of the first release however many were cast
+fn baseline_voter(poll: &Pubkey, voter: &Pubkey, positive_used: u8, negative_used: u8, voted_parties: &[Pubkey]) -> Vec<u8> {
+    let mut data = baseline_string("voter");
+    data.extend_from_slice(poll.as_ref());
+    data.extend_from_slice(voter.as_ref());
+    data.push(positive_used);
+    data.push(negative_used);
+    data.extend_from_slice(&(voted_parties.len() as u32).to_le_bytes());
+    for party in voted_parties {
+        data.extend_from_slice(party.as_ref());
+    }
+    data.resize(9 + 32 + 32 + 1 + 1 + 4 + 32 * 3, 0);
+    data
+}
+
+async fn set_program_account(ctx: &mut ProgramTestContext, program_id: &Pubkey, address: &Pubkey, data: Vec<u8>) {
+    let rent: Rent = ctx.banks_client.get_sysvar().await.unwrap();
+
+    ctx.set_account(address, &Account {
+        lamports: rent.minimum_balance(data.len()),
+        data,
+        owner: *program_id,
+        executable: false,
+        rent_epoch: 0,
+    }.into());
+}
+
+fn assert_error(err: TransactionError, expected: InstructionError) {
+    match err {
+        TransactionError::InstructionError(_, actual) => assert_eq!(actual, expected),
+        other => panic!("Unexpected transport error type: {:?}", other),
+    }
+}
+
+
+#[tokio::test]
+async fn test_migrate_legacy_poll_success() {
+    let (mut ctx, program_id) = setup_test_env().await;
+    let rent: Rent = ctx.banks_client.get_sysvar().await.unwrap();
+    let clock: Clock = ctx.banks_client.get_sysvar().await.unwrap();
+
+    // poll created before durations were stored, voting opened 6 days after creation
+    let poll_pda = Pubkey::new_unique();
+    let poll_data = baseline_poll(
+        &ctx.payer.pubkey(),
+        VotingPhase::Voting,
+        1,
+        clock.unix_timestamp - (60 * 60 * 24 * 8),
+        clock.unix_timestamp - (60 * 60 * 24 * 2),
+    );
+    set_program_account(&mut ctx, &program_id, &poll_pda, poll_data).await;
+
+    let party_pda = Pubkey::new_unique();
+    set_program_account(&mut ctx, &program_id, &party_pda, baseline_party(&poll_pda, "Party A", 0, 0)).await;
+
+    migrate_account(
+        &mut ctx.banks_client,
+        &ctx.payer,
+        &ctx.last_blockhash,
+        &program_id,
+        &poll_pda,
+    ).await;
+
+    migrate_party(
+        &mut ctx.banks_client,
+        &ctx.payer,
+        &ctx.last_blockhash,
+        &program_id,
+        &party_pda,
+        &poll_pda,
+    ).await;
+
+    let poll_account = ctx.banks_client.get_account(poll_pda).await.unwrap().unwrap();
+    let poll_state = PollState::deserialize(&mut &poll_account.data[..]).unwrap();
+
+    assert_eq!(poll_account.data.len(), PollState::SIZE);
+    assert!(poll_account.lamports >= rent.minimum_balance(poll_account.data.len()));
+    assert_eq!(poll_state.party_counter, 1);
+    assert_eq!(poll_state.registration_duration, PollState::LEGACY_REGISTRATION_DURATION);
+    assert_eq!(poll_state.voting_duration, PollState::LEGACY_VOTING_DURATION);
+    assert_eq!(poll_state.rent_payer, ctx.payer.pubkey());
+
+    // first release parties refund the poll owner once closed
+    let party_account = ctx.banks_client.get_account(party_pda).await.unwrap().unwrap();
+    let party_state = *PartyAccount::from_bytes(&party_account.data);
+
+    assert_eq!(party_account.data.len(), PartyAccount::SIZE);
+    assert!(party_account.lamports >= rent.minimum_balance(party_account.data.len()));
+    assert_eq!(party_state.title(), "Party A");
+    assert_eq!(party_state.rent_payer, ctx.payer.pubkey());
+
+    // 8 days after creation the poll is still open, the window is measured from voting start
+    vote(
+        &mut ctx.banks_client,
+        &ctx.payer,
+        &ctx.last_blockhash,
+        &program_id,
+        &poll_pda,
+        &party_pda,
+        VoteType::Positive,
+    ).await;
+
+    let party_state = *PartyAccount::from_bytes(&ctx.banks_client.get_account(party_pda).await.unwrap().unwrap().data);
+    assert_eq!(party_state.positive_votes, 1);
+}
+
+
+#[tokio::test]
+async fn test_version_0_accounts_load_and_migrate() {
+    let (mut ctx, program_id) = setup_test_env().await;
+    let rent: Rent = ctx.banks_client.get_sysvar().await.unwrap();
+    let clock: Clock = ctx.banks_client.get_sysvar().await.unwrap();
+
+    // poll in voting with +A already cast by the payer
+    let poll_pda = Pubkey::new_unique();
+    let poll_data = baseline_poll(
+        &ctx.payer.pubkey(),
+        VotingPhase::Voting,
+        2,
+        clock.unix_timestamp - (60 * 60 * 24 * 2),
+        clock.unix_timestamp - (60 * 60),
+    );
+    let poll_len = poll_data.len();
+    set_program_account(&mut ctx, &program_id, &poll_pda, poll_data).await;
+
+    let party_pda_a = Pubkey::new_unique();
+    let party_pda_b = Pubkey::new_unique();
+    set_program_account(&mut ctx, &program_id, &party_pda_a, baseline_party(&poll_pda, "Party A", 1, 0)).await;
+    set_program_account(&mut ctx, &program_id, &party_pda_b, baseline_party(&poll_pda, "Party B", 0, 0)).await;
+
+    let (voter_pda, voter_bump) = Pubkey::find_program_address(
+        &[b"voter", poll_pda.as_ref(), ctx.payer.pubkey().as_ref()],
+        &program_id,
+    );
+    let voter_data = baseline_voter(&poll_pda, &ctx.payer.pubkey(), 1, 0, &[party_pda_a]);
+    set_program_account(&mut ctx, &program_id, &voter_pda, voter_data).await;
+
+    // expect error when voting for a party still too small for the current layout
+    let err = vote_expect_fail(
+        &mut ctx.banks_client,
+        &ctx.payer,
+        &ctx.last_blockhash,
+        &program_id,
+        &poll_pda,
+        &party_pda_b,
+        VoteType::Positive,
+    ).await.unwrap();
+    assert_error(err, InstructionError::AccountDataTooSmall);
+
+    migrate_party(
+        &mut ctx.banks_client,
+        &ctx.payer,
+        &ctx.last_blockhash,
+        &program_id,
+        &party_pda_b,
+        &poll_pda,
+    ).await;
+
+    // update blockhash
+    ctx.last_blockhash = ctx.get_new_latest_blockhash().await.unwrap();
+
+    // +B, the voter grows to the slots of the rules and is saved in the current layout
+    vote(
+        &mut ctx.banks_client,
+        &ctx.payer,
+        &ctx.last_blockhash,
+        &program_id,
+        &poll_pda,
+        &party_pda_b,
+        VoteType::Positive,
+    ).await;
+
+    let party_data_b = ctx.banks_client.get_account(party_pda_b).await.unwrap().unwrap().data;
+    assert_eq!(party_data_b[..8], PartyAccount::DISCRIMINATOR);
+    assert_eq!(party_data_b[8], PartyAccount::VERSION);
+    assert_eq!(PartyAccount::from_bytes(&party_data_b).positive_votes, 1);
+
+    let voter_account = ctx.banks_client.get_account(voter_pda).await.unwrap().unwrap();
+    let (voter_state, voted_parties) = VoterAccount::unpack(&voter_account.data);
+    assert_eq!(voter_account.data.len(), VoterAccount::get_account_size(&VoteRules::default()));
+    assert!(voter_account.lamports >= rent.minimum_balance(voter_account.data.len()));
+    assert_eq!(voter_state.version, VoterAccount::VERSION);
+    assert_eq!(voter_state.bump, voter_bump);
+    assert_eq!(voter_state.positive_used, 2);
+    assert_eq!(voted_parties.len(), 2);
+    assert_eq!(voted_parties[0].party, party_pda_a);
+    assert_eq!(voted_parties[0].vote_type(), VoteType::Positive);
+    assert_eq!(voted_parties[1].party, party_pda_b);
+
+    // poll and party A were only read, they keep the old layout until migrated
+    let poll_account = ctx.banks_client.get_account(poll_pda).await.unwrap().unwrap();
+    assert_eq!(poll_account.data.len(), poll_len);
+    assert_ne!(poll_account.data[..8], PollState::DISCRIMINATOR);
+
+    migrate_account(
+        &mut ctx.banks_client,
+        &ctx.payer,
+        &ctx.last_blockhash,
+        &program_id,
+        &poll_pda,
+    ).await;
+
+    migrate_party(
+        &mut ctx.banks_client,
+        &ctx.payer,
+        &ctx.last_blockhash,
+        &program_id,
+        &party_pda_a,
+        &poll_pda,
+    ).await;
+
+    let poll_account = ctx.banks_client.get_account(poll_pda).await.unwrap().unwrap();
+    let poll_state = PollState::deserialize(&mut &poll_account.data[..]).unwrap();
+    assert_eq!(poll_state.version, PollState::VERSION);
+    assert_eq!(poll_state.title, "Presidential Election");
+    assert_eq!(poll_state.party_counter, 2);
+    assert_eq!(poll_account.data.len(), PollState::SIZE);
+
+    let party_state_a = *PartyAccount::from_bytes(&ctx.banks_client.get_account(party_pda_a).await.unwrap().unwrap().data);
+    assert_eq!(party_state_a.version, PartyAccount::VERSION);
+    assert_eq!(party_state_a.title(), "Party A");
+    assert_eq!(party_state_a.positive_votes, 1);
+
+    // update blockhash
+    ctx.last_blockhash = ctx.get_new_latest_blockhash().await.unwrap();
+
+    // expect error when the account already uses the current layout
+    let err = migrate_account_expect_fail(
+        &mut ctx.banks_client,
+        &ctx.payer,
+        &ctx.last_blockhash,
+        &program_id,
+        &poll_pda,
+    ).await.unwrap();
+    assert_error(err, InstructionError::InvalidAccountData);
+}
+
+
+#[tokio::test]
+async fn test_version_0_poll_grows_on_save() {
+    let (mut ctx, program_id) = setup_test_env().await;
+    let rent: Rent = ctx.banks_client.get_sysvar().await.unwrap();
+    let clock: Clock = ctx.banks_client.get_sysvar().await.unwrap();
+
+    // poll still in registration, created 2 days ago
+    let poll_pda = Pubkey::new_unique();
+    let poll_data = baseline_poll(&ctx.payer.pubkey(), VotingPhase::Registration, 0, clock.unix_timestamp - (60 * 60 * 24 * 2), 0);
+    set_program_account(&mut ctx, &program_id, &poll_pda, poll_data).await;
+
+    // expect error when the instruction can not pay for the larger layout
+    let err = start_voting_expect_fail(
+        &mut ctx.banks_client,
+        &ctx.payer,
+        &ctx.last_blockhash,
+        &program_id,
+        &poll_pda,
+    ).await.unwrap();
+    assert_error(err, InstructionError::AccountDataTooSmall);
+
+    // creating a party grows the poll, the payer tops up the rent
+    create_party(
+        &mut ctx.banks_client,
+        &ctx.payer,
+        &ctx.last_blockhash,
+        &program_id,
+        "Party A",
+        &poll_pda,
+    ).await;
+
+    let poll_account = ctx.banks_client.get_account(poll_pda).await.unwrap().unwrap();
+    let poll_state = PollState::deserialize(&mut &poll_account.data[..]).unwrap();
+    assert_eq!(poll_account.data.len(), PollState::SIZE);
+    assert!(poll_account.lamports >= rent.minimum_balance(poll_account.data.len()));
+    assert_eq!(poll_state.version, PollState::VERSION);
+    assert_eq!(poll_state.party_counter, 1);
+
+    // update blockhash
+    ctx.last_blockhash = ctx.get_new_latest_blockhash().await.unwrap();
+
+    start_voting(
+        &mut ctx.banks_client,
+        &ctx.payer,
+        &ctx.last_blockhash,
+        &program_id,
+        &poll_pda,
+    ).await;
+
+    let poll_account = ctx.banks_client.get_account(poll_pda).await.unwrap().unwrap();
+    let poll_state = PollState::deserialize(&mut &poll_account.data[..]).unwrap();
+    assert!(matches!(poll_state.phase, VotingPhase::Voting));
+}
+
+
+#[tokio::test]
+async fn test_stranger_migration_refunds_owner() {
+    let (mut ctx, program_id) = setup_test_env().await;
+    let clock: Clock = ctx.banks_client.get_sysvar().await.unwrap();
+
+    // finished poll of the first release without parties
+    let poll_pda = Pubkey::new_unique();
+    let poll_data = baseline_poll(
+        &ctx.payer.pubkey(),
+        VotingPhase::Results,
+        0,
+        clock.unix_timestamp - (60 * 60 * 24 * 10),
+        clock.unix_timestamp - (60 * 60 * 24 * 9),
+    );
+    set_program_account(&mut ctx, &program_id, &poll_pda, poll_data).await;
+
+    let stranger = Keypair::new();
+    ctx.set_account(&stranger.pubkey(), &Account {
+        lamports: 1_000_000_000,
+        data: vec![],
+        owner: system_program::id(),
+        executable: false,
+        rent_epoch: 0,
+    }.into());
+
+    // the stranger pays for growing the poll but does not become its rent payer
+    migrate_account(
+        &mut ctx.banks_client,
+        &stranger,
+        &ctx.last_blockhash,
+        &program_id,
+        &poll_pda,
+    ).await;
+
+    let poll_account = ctx.banks_client.get_account(poll_pda).await.unwrap().unwrap();
+    let poll_state = PollState::deserialize(&mut &poll_account.data[..]).unwrap();
+    assert_eq!(poll_state.rent_payer, ctx.payer.pubkey());
+
+    let owner_balance = ctx.banks_client.get_balance(ctx.payer.pubkey()).await.unwrap();
+    let stranger_balance = ctx.banks_client.get_balance(stranger.pubkey()).await.unwrap();
+
+    close_poll(
+        &mut ctx.banks_client,
+        &ctx.payer,
+        &ctx.last_blockhash,
+        &program_id,
+        &poll_pda,
+    ).await;
+
+    // owner gets the whole poll balance, the stranger nothing
+    assert!(ctx.banks_client.get_account(poll_pda).await.unwrap().is_none());
+    assert!(ctx.banks_client.get_balance(ctx.payer.pubkey()).await.unwrap() >= owner_balance + poll_account.lamports - 10_000);
+    assert_eq!(ctx.banks_client.get_balance(stranger.pubkey()).await.unwrap(), stranger_balance);
+}
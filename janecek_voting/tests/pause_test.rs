@@ -0,0 +1,359 @@
+mod helpers;
+use helpers::{
+    setup_test_env,
+    assert_custom_error,
+    create_poll,
+    create_poll_expect_fail,
+    create_party,
+    create_party_expect_fail,
+    start_voting,
+    vote,
+    vote_expect_fail,
+    set_poll_paused,
+    set_poll_paused_expect_fail,
+    set_program_paused,
+    get_config,
+    PartyMetadata,
+    PollParams,
+};
+use janecek_voting::{error::JanecekError, state::{PartyAccount, PollState, VoteType}};
+use borsh::{BorshDeserialize};
+use solana_sdk::{clock::Clock, signature::Keypair, signer::Signer, system_instruction, transaction::{Transaction, TransactionError}, instruction::InstructionError};
+
+
+#[tokio::test]
+async fn test_pause_poll_extends_deadline() {
+    let (mut ctx, program_id) = setup_test_env().await;
+
+    let title = "Presidential Election".to_string();
+    let description = "Vote for the next president".to_string();
+    let outsider = Keypair::new();
+
+    // transfer SOL to outsider
+    let transfer_ix = system_instruction::transfer(
+        &ctx.payer.pubkey(),
+        &outsider.pubkey(),
+        1_000_000_000, // 1 SOL
+    );
+    let tx = Transaction::new_signed_with_payer(
+        &[transfer_ix],
+        Some(&ctx.payer.pubkey()),
+        &[&ctx.payer],
+        ctx.last_blockhash,
+    );
+    ctx.banks_client.process_transaction(tx).await.unwrap();
+
+    // create poll
+    let poll_pda = create_poll(
+        &mut ctx.banks_client,
+        &ctx.payer,
+        &ctx.last_blockhash,
+        &program_id,
+        &title,
+        &description,
+    ).await;
+
+    // create party
+    let party_pda = create_party(
+        &mut ctx.banks_client,
+        &ctx.payer,
+        &ctx.last_blockhash,
+        &program_id,
+        "Party A",
+        &poll_pda,
+    ).await;
+
+    // update timestamp to 24 hours and 1 minute
+    let mut clock: Clock = ctx.banks_client.get_sysvar().await.unwrap();
+    clock.unix_timestamp += (60 * 60 * 24) + 60;
+    ctx.set_sysvar(&clock);
+
+    // start voting
+    start_voting(
+        &mut ctx.banks_client,
+        &ctx.payer,
+        &ctx.last_blockhash,
+        &program_id,
+        &poll_pda,
+    ).await;
+
+    // expect error when someone other than the owner pauses the poll
+    let err = set_poll_paused_expect_fail(
+        &mut ctx.banks_client,
+        &outsider,
+        &ctx.last_blockhash,
+        &program_id,
+        &poll_pda,
+        true,
+        false,
+    ).await.unwrap();
+
+    match err {
+        TransactionError::InstructionError(_, InstructionError::IllegalOwner) => {}
+        other => panic!("Unexpected transport error type: {:?}", other),
+    }
+
+    // owner pauses the poll
+    set_poll_paused(
+        &mut ctx.banks_client,
+        &ctx.payer,
+        &ctx.last_blockhash,
+        &program_id,
+        &poll_pda,
+        true,
+        false,
+    ).await;
+
+    // expect error when voting on a paused poll
+    let err = vote_expect_fail(
+        &mut ctx.banks_client,
+        &ctx.payer,
+        &ctx.last_blockhash,
+        &program_id,
+        &poll_pda,
+        &party_pda,
+        VoteType::Positive,
+    ).await.unwrap();
+    assert_custom_error(err, JanecekError::PollPaused);
+
+    // update timestamp by one hour
+    let mut clock: Clock = ctx.banks_client.get_sysvar().await.unwrap();
+    clock.unix_timestamp += 60 * 60;
+    ctx.set_sysvar(&clock);
+
+    // owner resumes and gives the hour back
+    set_poll_paused(
+        &mut ctx.banks_client,
+        &ctx.payer,
+        &ctx.last_blockhash,
+        &program_id,
+        &poll_pda,
+        false,
+        true,
+    ).await;
+
+    let poll_state = PollState::deserialize(&mut &ctx.banks_client.get_account(poll_pda).await.unwrap().unwrap().data[..]).unwrap();
+    assert!(!poll_state.paused);
+    assert_eq!(poll_state.paused_duration, 60 * 60);
+    assert_eq!(poll_state.voting_ends_at(&get_config(&mut ctx.banks_client, &program_id).await), poll_state.voting_start_at + poll_state.voting_duration + 60 * 60);
+
+    // update blockhash
+    ctx.last_blockhash = ctx.get_new_latest_blockhash().await.unwrap();
+
+    // +A
+    vote(
+        &mut ctx.banks_client,
+        &ctx.payer,
+        &ctx.last_blockhash,
+        &program_id,
+        &poll_pda,
+        &party_pda,
+        VoteType::Positive,
+    ).await;
+
+    let party_state = *PartyAccount::from_bytes(&ctx.banks_client.get_account(party_pda).await.unwrap().unwrap().data);
+    assert_eq!(party_state.positive_votes, 1);
+}
+
+
+#[tokio::test]
+async fn test_pause_program() {
+    let (mut ctx, program_id) = setup_test_env().await;
+
+    let title = "Presidential Election".to_string();
+    let description = "Vote for the next president".to_string();
+
+    // create poll
+    let poll_pda = create_poll(
+        &mut ctx.banks_client,
+        &ctx.payer,
+        &ctx.last_blockhash,
+        &program_id,
+        &title,
+        &description,
+    ).await;
+
+    // admin halts every poll
+    set_program_paused(
+        &mut ctx.banks_client,
+        &ctx.payer,
+        &ctx.last_blockhash,
+        &program_id,
+        true,
+    ).await;
+
+    // expect error when creating polls or parties while the program is paused
+    let err = create_poll_expect_fail(
+        &mut ctx.banks_client,
+        &ctx.payer,
+        &ctx.last_blockhash,
+        &program_id,
+        &title,
+        &description,
+        PollParams::default(),
+    ).await.unwrap();
+    assert_custom_error(err, JanecekError::ProgramPaused);
+
+    let err = create_party_expect_fail(
+        &mut ctx.banks_client,
+        &ctx.payer,
+        &ctx.last_blockhash,
+        &program_id,
+        "Party A",
+        &poll_pda,
+        PartyMetadata::default(),
+    ).await.unwrap();
+    assert_custom_error(err, JanecekError::ProgramPaused);
+
+    // admin resumes the program
+    set_program_paused(
+        &mut ctx.banks_client,
+        &ctx.payer,
+        &ctx.last_blockhash,
+        &program_id,
+        false,
+    ).await;
+
+    // update blockhash
+    ctx.last_blockhash = ctx.get_new_latest_blockhash().await.unwrap();
+
+    let party_pda = create_party(
+        &mut ctx.banks_client,
+        &ctx.payer,
+        &ctx.last_blockhash,
+        &program_id,
+        "Party A",
+        &poll_pda,
+    ).await;
+
+    assert!(ctx.banks_client.get_account(party_pda).await.unwrap().is_some());
+}
+
+
+#[tokio::test]
+async fn test_pause_after_deadline() {
+    let (mut ctx, program_id) = setup_test_env().await;
+
+    // create poll
+    let poll_pda = create_poll(
+        &mut ctx.banks_client,
+        &ctx.payer,
+        &ctx.last_blockhash,
+        &program_id,
+        "Presidential Election",
+        "Vote for the next president",
+    ).await;
+
+    // update timestamp to 24 hours and 1 minute
+    let mut clock: Clock = ctx.banks_client.get_sysvar().await.unwrap();
+    clock.unix_timestamp += (60 * 60 * 24) + 60;
+    ctx.set_sysvar(&clock);
+
+    start_voting(
+        &mut ctx.banks_client,
+        &ctx.payer,
+        &ctx.last_blockhash,
+        &program_id,
+        &poll_pda,
+    ).await;
+
+    // update timestamp past the 7 day voting period
+    let mut clock: Clock = ctx.banks_client.get_sysvar().await.unwrap();
+    clock.unix_timestamp += (60 * 60 * 24 * 7) + 60;
+    ctx.set_sysvar(&clock);
+
+    // expect error when the pause would only hold back EndVoting
+    let err = set_poll_paused_expect_fail(
+        &mut ctx.banks_client,
+        &ctx.payer,
+        &ctx.last_blockhash,
+        &program_id,
+        &poll_pda,
+        true,
+        false,
+    ).await.unwrap();
+    assert_custom_error(err, JanecekError::VotingPeriodFinished);
+}
+
+
+#[tokio::test]
+async fn test_pause_program_extends_deadline() {
+    let (mut ctx, program_id) = setup_test_env().await;
+
+    // create poll
+    let poll_pda = create_poll(
+        &mut ctx.banks_client,
+        &ctx.payer,
+        &ctx.last_blockhash,
+        &program_id,
+        "Presidential Election",
+        "Vote for the next president",
+    ).await;
+
+    let party_pda = create_party(
+        &mut ctx.banks_client,
+        &ctx.payer,
+        &ctx.last_blockhash,
+        &program_id,
+        "Party A",
+        &poll_pda,
+    ).await;
+
+    // update timestamp to 24 hours and 1 minute
+    let mut clock: Clock = ctx.banks_client.get_sysvar().await.unwrap();
+    clock.unix_timestamp += (60 * 60 * 24) + 60;
+    ctx.set_sysvar(&clock);
+
+    start_voting(
+        &mut ctx.banks_client,
+        &ctx.payer,
+        &ctx.last_blockhash,
+        &program_id,
+        &poll_pda,
+    ).await;
+
+    // admin halts the program for a day of the voting period
+    set_program_paused(
+        &mut ctx.banks_client,
+        &ctx.payer,
+        &ctx.last_blockhash,
+        &program_id,
+        true,
+    ).await;
+
+    let mut clock: Clock = ctx.banks_client.get_sysvar().await.unwrap();
+    clock.unix_timestamp += 60 * 60 * 24;
+    ctx.set_sysvar(&clock);
+
+    set_program_paused(
+        &mut ctx.banks_client,
+        &ctx.payer,
+        &ctx.last_blockhash,
+        &program_id,
+        false,
+    ).await;
+
+    let config = get_config(&mut ctx.banks_client, &program_id).await;
+    let poll_state = PollState::deserialize(&mut &ctx.banks_client.get_account(poll_pda).await.unwrap().unwrap().data[..]).unwrap();
+    assert_eq!(config.paused_duration, 60 * 60 * 24);
+    assert_eq!(poll_state.voting_ends_at(&config), poll_state.voting_start_at + poll_state.voting_duration + 60 * 60 * 24);
+
+    // update timestamp to an hour after the original deadline
+    let mut clock: Clock = ctx.banks_client.get_sysvar().await.unwrap();
+    clock.unix_timestamp = poll_state.voting_start_at + poll_state.voting_duration + 60 * 60;
+    ctx.set_sysvar(&clock);
+
+    // +A still counts, the paused day was added to the deadline
+    vote(
+        &mut ctx.banks_client,
+        &ctx.payer,
+        &ctx.last_blockhash,
+        &program_id,
+        &poll_pda,
+        &party_pda,
+        VoteType::Positive,
+    ).await;
+
+    let party_state = *PartyAccount::from_bytes(&ctx.banks_client.get_account(party_pda).await.unwrap().unwrap().data);
+    assert_eq!(party_state.positive_votes, 1);
+}
@@ -0,0 +1,374 @@
+mod helpers;
+use helpers::{
+    setup_test_env,
+    create_poll,
+    create_poll_with_params,
+    create_party,
+    start_voting,
+    register_voter,
+    register_voter_expect_fail,
+    unregister_voter,
+    unregister_voter_expect_fail,
+    vote_with_accounts,
+    vote_with_accounts_expect_fail,
+    PollParams,
+};
+use janecek_voting::{error::JanecekError, state::{EligibleVoter, PartyAccount, PollState, VoteType}};
+use borsh::{BorshDeserialize};
+use solana_sdk::{clock::Clock, instruction::{AccountMeta, InstructionError}, signature::Keypair, signer::Signer, system_instruction, transaction::{Transaction, TransactionError}};
+
+
+#[tokio::test]
+async fn test_register_voter_closed_poll() {
+    let (mut ctx, program_id) = setup_test_env().await;
+
+    let title = "Board Election".to_string();
+    let description = "Internal vote of the team".to_string();
+    let title_a = "Party A".to_string();
+    let member = Keypair::new();
+    let outsider = Keypair::new();
+
+    // transfer SOL to member and outsider
+    for voter in [&member, &outsider] {
+        let transfer_ix = system_instruction::transfer(
+            &ctx.payer.pubkey(),
+            &voter.pubkey(),
+            1_000_000_000, // 1 SOL
+        );
+        let tx = Transaction::new_signed_with_payer(
+            &[transfer_ix],
+            Some(&ctx.payer.pubkey()),
+            &[&ctx.payer],
+            ctx.last_blockhash,
+        );
+        ctx.banks_client.process_transaction(tx).await.unwrap();
+    }
+
+    // create closed poll
+    let poll_pda = create_poll_with_params(
+        &mut ctx.banks_client,
+        &ctx.payer,
+        &ctx.last_blockhash,
+        &program_id,
+        &title,
+        &description,
+        PollParams { closed: true, ..PollParams::default() },
+    ).await;
+
+    let poll_state = PollState::deserialize(&mut &ctx.banks_client.get_account(poll_pda).await.unwrap().unwrap().data[..]).unwrap();
+    assert!(poll_state.closed);
+    assert_eq!(poll_state.eligible_voters, 0);
+
+    // create party
+    let party_pda_a = create_party(
+        &mut ctx.banks_client,
+        &ctx.payer,
+        &ctx.last_blockhash,
+        &program_id,
+        &title_a,
+        &poll_pda,
+    ).await;
+
+    // expect error when someone other than the owner registers a voter
+    let err = register_voter_expect_fail(
+        &mut ctx.banks_client,
+        &outsider,
+        &ctx.last_blockhash,
+        &program_id,
+        &poll_pda,
+        &outsider.pubkey(),
+    ).await.unwrap();
+
+    match err {
+        TransactionError::InstructionError(_, InstructionError::IllegalOwner) => {}
+        other => panic!("Unexpected transport error type: {:?}", other),
+    }
+
+    // owner registers the member
+    let member_eligible_pda = register_voter(
+        &mut ctx.banks_client,
+        &ctx.payer,
+        &ctx.last_blockhash,
+        &program_id,
+        &poll_pda,
+        &member.pubkey(),
+    ).await;
+
+    let poll_state = PollState::deserialize(&mut &ctx.banks_client.get_account(poll_pda).await.unwrap().unwrap().data[..]).unwrap();
+    let eligible_state = EligibleVoter::try_from_slice(&ctx.banks_client.get_account(member_eligible_pda).await.unwrap().unwrap().data).unwrap();
+
+    assert_eq!(poll_state.eligible_voters, 1);
+    assert_eq!(eligible_state.poll_key, poll_pda);
+    assert_eq!(eligible_state.voter_key, member.pubkey());
+
+    // update timestamp to 24 hours and 1 minute
+    let mut clock: Clock = ctx.banks_client.get_sysvar().await.unwrap();
+    clock.unix_timestamp += (60 * 60 * 24) + 60;
+    ctx.set_sysvar(&clock);
+
+    // start voting
+    start_voting(
+        &mut ctx.banks_client,
+        &ctx.payer,
+        &ctx.last_blockhash,
+        &program_id,
+        &poll_pda,
+    ).await;
+
+    // expect error when an unregistered voter passes the member's eligibility account
+    let err = vote_with_accounts_expect_fail(
+        &mut ctx.banks_client,
+        &outsider,
+        &ctx.last_blockhash,
+        &program_id,
+        &poll_pda,
+        &party_pda_a,
+        VoteType::Positive,
+        &[AccountMeta::new_readonly(member_eligible_pda, false)],
+    ).await.unwrap();
+
+    match err {
+        TransactionError::InstructionError(_, InstructionError::Custom(code)) => {
+            assert_eq!(
+                code,
+                JanecekError::VoterNotEligible as u32,
+                "Expected VoterNotEligible error"
+            );
+        }
+        other => panic!("Unexpected transport error type: {:?}", other),
+    }
+
+    // expect error when the eligibility account is missing
+    let err = vote_with_accounts_expect_fail(
+        &mut ctx.banks_client,
+        &member,
+        &ctx.last_blockhash,
+        &program_id,
+        &poll_pda,
+        &party_pda_a,
+        VoteType::Positive,
+        &[],
+    ).await.unwrap();
+
+    match err {
+        TransactionError::InstructionError(_, InstructionError::Custom(code)) => {
+            assert_eq!(
+                code,
+                JanecekError::VoterNotEligible as u32,
+                "Expected VoterNotEligible error"
+            );
+        }
+        other => panic!("Unexpected transport error type: {:?}", other),
+    }
+
+    // registered member can vote
+    vote_with_accounts(
+        &mut ctx.banks_client,
+        &member,
+        &ctx.last_blockhash,
+        &program_id,
+        &poll_pda,
+        &party_pda_a,
+        VoteType::Positive,
+        &[AccountMeta::new_readonly(member_eligible_pda, false)],
+    ).await;
+
+    let party_state_a = *PartyAccount::from_bytes(&ctx.banks_client.get_account(party_pda_a).await.unwrap().unwrap().data);
+    assert_eq!(party_state_a.positive_votes, 1);
+
+    // expect error when registering voters after registration phase
+    let err = register_voter_expect_fail(
+        &mut ctx.banks_client,
+        &ctx.payer,
+        &ctx.last_blockhash,
+        &program_id,
+        &poll_pda,
+        &outsider.pubkey(),
+    ).await.unwrap();
+
+    match err {
+        TransactionError::InstructionError(_, InstructionError::InvalidAccountData) => {}
+        other => panic!("Unexpected transport error type: {:?}", other),
+    }
+}
+
+
+#[tokio::test]
+async fn test_register_voter_open_poll() {
+    let (mut ctx, program_id) = setup_test_env().await;
+    let voter = Keypair::new();
+
+    // create open poll
+    let poll_pda = create_poll(
+        &mut ctx.banks_client,
+        &ctx.payer,
+        &ctx.last_blockhash,
+        &program_id,
+        "Public Election",
+        "Everyone can vote",
+    ).await;
+
+    // expect error when registering voters in an open poll
+    let err = register_voter_expect_fail(
+        &mut ctx.banks_client,
+        &ctx.payer,
+        &ctx.last_blockhash,
+        &program_id,
+        &poll_pda,
+        &voter.pubkey(),
+    ).await.unwrap();
+
+    match err {
+        TransactionError::InstructionError(_, InstructionError::Custom(code)) => {
+            assert_eq!(
+                code,
+                JanecekError::PollNotClosed as u32,
+                "Expected PollNotClosed error"
+            );
+        }
+        other => panic!("Unexpected transport error type: {:?}", other),
+    }
+
+    let poll_state = PollState::deserialize(&mut &ctx.banks_client.get_account(poll_pda).await.unwrap().unwrap().data[..]).unwrap();
+    assert!(!poll_state.closed);
+}
+
+
+#[tokio::test]
+async fn test_unregister_voter() {
+    let (mut ctx, program_id) = setup_test_env().await;
+    let member = Keypair::new();
+    let outsider = Keypair::new();
+
+    // transfer SOL to the member
+    let transfer_ix = system_instruction::transfer(
+        &ctx.payer.pubkey(),
+        &member.pubkey(),
+        1_000_000_000, // 1 SOL
+    );
+    let tx = Transaction::new_signed_with_payer(
+        &[transfer_ix],
+        Some(&ctx.payer.pubkey()),
+        &[&ctx.payer],
+        ctx.last_blockhash,
+    );
+    ctx.banks_client.process_transaction(tx).await.unwrap();
+
+    // create closed poll
+    let poll_pda = create_poll_with_params(
+        &mut ctx.banks_client,
+        &ctx.payer,
+        &ctx.last_blockhash,
+        &program_id,
+        "Board Election",
+        "Internal vote of the team",
+        PollParams { closed: true, ..PollParams::default() },
+    ).await;
+
+    // create party
+    let party_pda_a = create_party(
+        &mut ctx.banks_client,
+        &ctx.payer,
+        &ctx.last_blockhash,
+        &program_id,
+        "Party A",
+        &poll_pda,
+    ).await;
+
+    let member_eligible_pda = register_voter(
+        &mut ctx.banks_client,
+        &ctx.payer,
+        &ctx.last_blockhash,
+        &program_id,
+        &poll_pda,
+        &member.pubkey(),
+    ).await;
+
+    // expect error when unregistering a voter that was never registered
+    let err = unregister_voter_expect_fail(
+        &mut ctx.banks_client,
+        &ctx.payer,
+        &ctx.last_blockhash,
+        &program_id,
+        &poll_pda,
+        &outsider.pubkey(),
+    ).await.unwrap();
+
+    match err {
+        TransactionError::InstructionError(_, InstructionError::IncorrectProgramId) => {}
+        other => panic!("Unexpected transport error type: {:?}", other),
+    }
+
+    // owner takes the member off the list and gets the rent back
+    let owner_lamports = ctx.banks_client.get_balance(ctx.payer.pubkey()).await.unwrap();
+    let eligible_lamports = ctx.banks_client.get_balance(member_eligible_pda).await.unwrap();
+
+    unregister_voter(
+        &mut ctx.banks_client,
+        &ctx.payer,
+        &ctx.last_blockhash,
+        &program_id,
+        &poll_pda,
+        &member.pubkey(),
+    ).await;
+
+    assert!(ctx.banks_client.get_account(member_eligible_pda).await.unwrap().is_none());
+    assert!(ctx.banks_client.get_balance(ctx.payer.pubkey()).await.unwrap() > owner_lamports + eligible_lamports - 20_000);
+
+    let poll_state = PollState::deserialize(&mut &ctx.banks_client.get_account(poll_pda).await.unwrap().unwrap().data[..]).unwrap();
+    assert!(poll_state.closed);
+    assert_eq!(poll_state.eligible_voters, 0);
+
+    // update timestamp to 24 hours and 1 minute
+    let mut clock: Clock = ctx.banks_client.get_sysvar().await.unwrap();
+    clock.unix_timestamp += (60 * 60 * 24) + 60;
+    ctx.set_sysvar(&clock);
+
+    // start voting
+    start_voting(
+        &mut ctx.banks_client,
+        &ctx.payer,
+        &ctx.last_blockhash,
+        &program_id,
+        &poll_pda,
+    ).await;
+
+    // poll stays closed with nobody registered, the former member can not vote
+    let err = vote_with_accounts_expect_fail(
+        &mut ctx.banks_client,
+        &member,
+        &ctx.last_blockhash,
+        &program_id,
+        &poll_pda,
+        &party_pda_a,
+        VoteType::Positive,
+        &[AccountMeta::new_readonly(member_eligible_pda, false)],
+    ).await.unwrap();
+
+    match err {
+        TransactionError::InstructionError(_, InstructionError::Custom(code)) => {
+            assert_eq!(
+                code,
+                JanecekError::VoterNotEligible as u32,
+                "Expected VoterNotEligible error"
+            );
+        }
+        other => panic!("Unexpected transport error type: {:?}", other),
+    }
+
+    // expect error when unregistering voters after registration phase
+    ctx.last_blockhash = ctx.get_new_latest_blockhash().await.unwrap();
+    let err = unregister_voter_expect_fail(
+        &mut ctx.banks_client,
+        &ctx.payer,
+        &ctx.last_blockhash,
+        &program_id,
+        &poll_pda,
+        &member.pubkey(),
+    ).await.unwrap();
+
+    match err {
+        TransactionError::InstructionError(_, InstructionError::InvalidAccountData) => {}
+        other => panic!("Unexpected transport error type: {:?}", other),
+    }
+}
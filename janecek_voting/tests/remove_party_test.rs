@@ -0,0 +1,124 @@
+mod helpers;
+use helpers::{setup_test_env, create_poll, create_party, start_voting, remove_party, remove_party_expect_fail};
+use janecek_voting::state::PollState;
+use borsh::{BorshDeserialize};
+use solana_sdk::{clock::Clock, signature::Keypair, signer::Signer, system_instruction, transaction::{Transaction, TransactionError}, instruction::InstructionError};
+
+
+#[tokio::test]
+async fn test_remove_party_success() {
+    let (mut ctx, program_id) = setup_test_env().await;
+
+    let title = "Presidential Election".to_string();
+    let description = "Vote for the next president".to_string();
+    let outsider = Keypair::new();
+
+    // transfer SOL to outsider
+    let transfer_ix = system_instruction::transfer(
+        &ctx.payer.pubkey(),
+        &outsider.pubkey(),
+        1_000_000_000, // 1 SOL
+    );
+    let tx = Transaction::new_signed_with_payer(
+        &[transfer_ix],
+        Some(&ctx.payer.pubkey()),
+        &[&ctx.payer],
+        ctx.last_blockhash,
+    );
+    ctx.banks_client.process_transaction(tx).await.unwrap();
+
+    // create poll
+    let poll_pda = create_poll(
+        &mut ctx.banks_client,
+        &ctx.payer,
+        &ctx.last_blockhash,
+        &program_id,
+        &title,
+        &description,
+    ).await;
+
+    // create parties, one with a typo
+    let mut party_pdas = Vec::new();
+    for party_title in ["Party A", "Prty B"] {
+        let party_pda = create_party(
+            &mut ctx.banks_client,
+            &ctx.payer,
+            &ctx.last_blockhash,
+            &program_id,
+            party_title,
+            &poll_pda,
+        ).await;
+        party_pdas.push(party_pda);
+    }
+
+    // expect error when someone other than the owner removes a party
+    let err = remove_party_expect_fail(
+        &mut ctx.banks_client,
+        &outsider,
+        &ctx.last_blockhash,
+        &program_id,
+        &poll_pda,
+        &party_pdas[1],
+    ).await.unwrap();
+
+    match err {
+        TransactionError::InstructionError(_, InstructionError::IllegalOwner) => {}
+        other => panic!("Unexpected transport error type: {:?}", other),
+    }
+
+    // owner removes the party and recreates it under the right title
+    remove_party(
+        &mut ctx.banks_client,
+        &ctx.payer,
+        &ctx.last_blockhash,
+        &program_id,
+        &poll_pda,
+        &party_pdas[1],
+    ).await;
+
+    assert!(ctx.banks_client.get_account(party_pdas[1]).await.unwrap().is_none());
+
+    let poll_state = PollState::deserialize(&mut &ctx.banks_client.get_account(poll_pda).await.unwrap().unwrap().data[..]).unwrap();
+    assert_eq!(poll_state.party_counter, 1);
+
+    create_party(
+        &mut ctx.banks_client,
+        &ctx.payer,
+        &ctx.last_blockhash,
+        &program_id,
+        "Party B",
+        &poll_pda,
+    ).await;
+
+    let poll_state = PollState::deserialize(&mut &ctx.banks_client.get_account(poll_pda).await.unwrap().unwrap().data[..]).unwrap();
+    assert_eq!(poll_state.party_counter, 2);
+
+    // update timestamp to 24 hours and 1 minute
+    let mut clock: Clock = ctx.banks_client.get_sysvar().await.unwrap();
+    clock.unix_timestamp += (60 * 60 * 24) + 60;
+    ctx.set_sysvar(&clock);
+
+    // start voting
+    start_voting(
+        &mut ctx.banks_client,
+        &ctx.payer,
+        &ctx.last_blockhash,
+        &program_id,
+        &poll_pda,
+    ).await;
+
+    // expect error when removing a party after registration
+    let err = remove_party_expect_fail(
+        &mut ctx.banks_client,
+        &ctx.payer,
+        &ctx.last_blockhash,
+        &program_id,
+        &poll_pda,
+        &party_pdas[0],
+    ).await.unwrap();
+
+    match err {
+        TransactionError::InstructionError(_, InstructionError::InvalidAccountData) => {}
+        other => panic!("Unexpected transport error type: {:?}", other),
+    }
+}
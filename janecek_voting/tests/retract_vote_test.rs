@@ -0,0 +1,134 @@
+mod helpers;
+use helpers::{setup_test_env, setup_voting_poll, assert_custom_error, vote, retract_vote, retract_vote_expect_fail};
+use janecek_voting::{error::JanecekError, state::{PartyAccount, VoteType, VoterAccount}};
+
+
+#[tokio::test]
+async fn test_retract_positive_vote_and_reassign() {
+    let (mut ctx, program_id) = setup_test_env().await;
+    let (poll_pda, party_pdas) = setup_voting_poll(&mut ctx, &program_id, 2).await;
+
+    // +A
+    let voter_pda = vote(
+        &mut ctx.banks_client,
+        &ctx.payer,
+        &ctx.last_blockhash,
+        &program_id,
+        &poll_pda,
+        &party_pdas[0],
+        VoteType::Positive,
+    ).await;
+
+    // mis-click, take it back
+    retract_vote(
+        &mut ctx.banks_client,
+        &ctx.payer,
+        &ctx.last_blockhash,
+        &program_id,
+        &poll_pda,
+        &party_pdas[0],
+    ).await;
+
+    // +B
+    vote(
+        &mut ctx.banks_client,
+        &ctx.payer,
+        &ctx.last_blockhash,
+        &program_id,
+        &poll_pda,
+        &party_pdas[1],
+        VoteType::Positive,
+    ).await;
+
+    let party_state_a = *PartyAccount::from_bytes(&ctx.banks_client.get_account(party_pdas[0]).await.unwrap().unwrap().data);
+    let party_state_b = *PartyAccount::from_bytes(&ctx.banks_client.get_account(party_pdas[1]).await.unwrap().unwrap().data);
+
+    let voter_account = ctx.banks_client.get_account(voter_pda).await.unwrap().unwrap();
+    let (voter_state, voted_parties) = VoterAccount::unpack(&voter_account.data);
+
+    assert_eq!(party_state_a.positive_votes, 0);
+    assert_eq!(party_state_b.positive_votes, 1);
+
+    assert_eq!(voter_state.positive_used, 1);
+    assert_eq!(voter_state.negative_used, 0);
+    assert_eq!(voted_parties.len(), 1);
+    assert_eq!(voted_parties[0].party, party_pdas[1]);
+}
+
+
+#[tokio::test]
+async fn test_retract_negative_before_positive() {
+    let (mut ctx, program_id) = setup_test_env().await;
+    let (poll_pda, party_pdas) = setup_voting_poll(&mut ctx, &program_id, 4).await;
+
+    // +A, +B, -C
+    let mut voter_pda = None;
+    for (party_pda, vote_type) in [
+        (party_pdas[0], VoteType::Positive),
+        (party_pdas[1], VoteType::Positive),
+        (party_pdas[2], VoteType::Negative),
+    ] {
+        voter_pda = Some(vote(
+            &mut ctx.banks_client,
+            &ctx.payer,
+            &ctx.last_blockhash,
+            &program_id,
+            &poll_pda,
+            &party_pda,
+            vote_type,
+        ).await);
+    }
+
+    // expect error when retracting a positive vote would leave the negative vote uncovered
+    let err = retract_vote_expect_fail(
+        &mut ctx.banks_client,
+        &ctx.payer,
+        &ctx.last_blockhash,
+        &program_id,
+        &poll_pda,
+        &party_pdas[0],
+    ).await.unwrap();
+    assert_custom_error(err, JanecekError::MustRetractNegativeFirst);
+
+    // expect error when retracting a party the voter has not voted for
+    let err = retract_vote_expect_fail(
+        &mut ctx.banks_client,
+        &ctx.payer,
+        &ctx.last_blockhash,
+        &program_id,
+        &poll_pda,
+        &party_pdas[3],
+    ).await.unwrap();
+    assert_custom_error(err, JanecekError::VoteNotFound);
+
+    // update blockhash
+    ctx.last_blockhash = ctx.get_new_latest_blockhash().await.unwrap();
+
+    // -C first, then +A
+    for party_pda in [party_pdas[2], party_pdas[0]] {
+        retract_vote(
+            &mut ctx.banks_client,
+            &ctx.payer,
+            &ctx.last_blockhash,
+            &program_id,
+            &poll_pda,
+            &party_pda,
+        ).await;
+    }
+
+    let party_state_a = *PartyAccount::from_bytes(&ctx.banks_client.get_account(party_pdas[0]).await.unwrap().unwrap().data);
+    let party_state_b = *PartyAccount::from_bytes(&ctx.banks_client.get_account(party_pdas[1]).await.unwrap().unwrap().data);
+    let party_state_c = *PartyAccount::from_bytes(&ctx.banks_client.get_account(party_pdas[2]).await.unwrap().unwrap().data);
+
+    let voter_account = ctx.banks_client.get_account(voter_pda.unwrap()).await.unwrap().unwrap();
+    let (voter_state, voted_parties) = VoterAccount::unpack(&voter_account.data);
+
+    assert_eq!(party_state_a.positive_votes, 0);
+    assert_eq!(party_state_b.positive_votes, 1);
+    assert_eq!(party_state_c.negative_votes, 0);
+
+    assert_eq!(voter_state.positive_used, 1);
+    assert_eq!(voter_state.negative_used, 0);
+    assert_eq!(voted_parties.len(), 1);
+    assert_eq!(voted_parties[0].party, party_pdas[1]);
+}
@@ -1,8 +1,8 @@
 mod helpers;
-use helpers::{setup_test_env, create_poll, start_voting};
-use janecek_voting::state::{PollState, VotingPhase};
+use helpers::{setup_test_env, create_poll, create_poll_with_params, create_party, start_voting, PollParams, start_voting_expect_fail};
+use janecek_voting::{error::JanecekError, state::{PollState, VotingPhase}};
 use borsh::{BorshDeserialize};
-use solana_sdk::{clock::Clock};
+use solana_sdk::{clock::Clock, transaction::TransactionError, instruction::InstructionError};
 
 
 #[tokio::test]
@@ -36,6 +36,112 @@ async fn test_start_voting_success() {
     ).await;
 
     let poll_account = ctx.banks_client.get_account(poll_pda).await.unwrap().unwrap();
-    let poll_state = PollState::try_from_slice(&poll_account.data).unwrap();
+    let poll_state = PollState::deserialize(&mut &poll_account.data[..]).unwrap();
     assert_eq!(poll_state.phase as u8, VotingPhase::Voting as u8);
-}
\ No newline at end of file
+}
+
+
+#[tokio::test]
+async fn test_start_voting_uses_poll_schedule() {
+    let (mut ctx, program_id) = setup_test_env().await;
+
+    let poll_title = "Lunch Straw Poll".to_string();
+    let poll_description = "Where do we eat today".to_string();
+
+    // 5 minutes registration instead of the default 24 hours
+    let poll_pda = create_poll_with_params(
+        &mut ctx.banks_client,
+        &ctx.payer,
+        &ctx.last_blockhash,
+        &program_id,
+        &poll_title,
+        &poll_description,
+        PollParams {
+            registration_duration: Some(60 * 5),
+            voting_duration: Some(60 * 30),
+            ..PollParams::default()
+        },
+    ).await;
+
+    // expect error when registration period is not finished
+    let err = start_voting_expect_fail(
+        &mut ctx.banks_client,
+        &ctx.payer,
+        &ctx.last_blockhash,
+        &program_id,
+        &poll_pda,
+    ).await.unwrap();
+
+    match err {
+        TransactionError::InstructionError(_, InstructionError::Custom(code)) => {
+            assert_eq!(
+                code,
+                JanecekError::RegistrationPhaseTooShort as u32,
+                "Expected RegistrationPhaseTooShort error"
+            );
+        }
+        other => panic!("Unexpected transport error type: {:?}", other),
+    }
+
+    // update timestamp to 5 minutes and 1 second
+    let mut clock: Clock = ctx.banks_client.get_sysvar().await.unwrap();
+    clock.unix_timestamp += (60 * 5) + 1;
+    ctx.set_sysvar(&clock);
+
+    // update blockhash
+    ctx.last_blockhash = ctx.get_new_latest_blockhash().await.unwrap();
+
+    start_voting(
+        &mut ctx.banks_client,
+        &ctx.payer,
+        &ctx.last_blockhash,
+        &program_id,
+        &poll_pda,
+    ).await;
+
+    let poll_account = ctx.banks_client.get_account(poll_pda).await.unwrap().unwrap();
+    let poll_state = PollState::deserialize(&mut &poll_account.data[..]).unwrap();
+    assert_eq!(poll_state.phase as u8, VotingPhase::Voting as u8);
+    assert_eq!(poll_state.voting_start_at, clock.unix_timestamp);
+}
+
+
+#[tokio::test]
+async fn test_start_voting_with_party_as_poll() {
+    let (mut ctx, program_id) = setup_test_env().await;
+
+    // create poll
+    let poll_pda = create_poll(
+        &mut ctx.banks_client,
+        &ctx.payer,
+        &ctx.last_blockhash,
+        &program_id,
+        "Presidential Election",
+        "Vote for the next president",
+    ).await;
+
+    let party_pda = create_party(
+        &mut ctx.banks_client,
+        &ctx.payer,
+        &ctx.last_blockhash,
+        &program_id,
+        "Party A",
+        &poll_pda,
+    ).await;
+
+    // expect error when a party account is passed in place of the poll
+    let err = start_voting_expect_fail(
+        &mut ctx.banks_client,
+        &ctx.payer,
+        &ctx.last_blockhash,
+        &program_id,
+        &party_pda,
+    ).await.unwrap();
+
+    match err {
+        TransactionError::InstructionError(_, InstructionError::Custom(code)) => {
+            assert_eq!(code, JanecekError::AccountTypeMismatch as u32, "Expected AccountTypeMismatch error");
+        }
+        other => panic!("Unexpected transport error type: {:?}", other),
+    }
+}
@@ -0,0 +1,138 @@
+mod helpers;
+use helpers::{
+    setup_test_env,
+    create_poll_with_params,
+    create_party,
+    start_voting,
+    set_mint,
+    set_token_account,
+    vote_with_accounts,
+    vote_with_accounts_expect_fail,
+    PollParams,
+};
+use janecek_voting::{error::JanecekError, state::{PartyAccount, PollState, TokenGate, VoteType}};
+use borsh::{BorshDeserialize};
+use solana_sdk::{clock::Clock, instruction::{AccountMeta, InstructionError}, signature::Keypair, signer::Signer, system_instruction, transaction::{Transaction, TransactionError}};
+
+
+#[tokio::test]
+async fn test_token_gated_vote() {
+    let (mut ctx, program_id) = setup_test_env().await;
+
+    let title = "DAO Proposal".to_string();
+    let description = "Token holders pick the next grant".to_string();
+    let title_a = "Party A".to_string();
+    let holder = Keypair::new();
+    let small_holder = Keypair::new();
+
+    // transfer SOL to voters
+    for voter in [&holder, &small_holder] {
+        let transfer_ix = system_instruction::transfer(
+            &ctx.payer.pubkey(),
+            &voter.pubkey(),
+            1_000_000_000, // 1 SOL
+        );
+        let tx = Transaction::new_signed_with_payer(
+            &[transfer_ix],
+            Some(&ctx.payer.pubkey()),
+            &[&ctx.payer],
+            ctx.last_blockhash,
+        );
+        ctx.banks_client.process_transaction(tx).await.unwrap();
+    }
+
+    // token accounts for the gate mint
+    let mint = set_mint(&mut ctx, &Keypair::new().pubkey());
+    let other_mint = set_mint(&mut ctx, &Keypair::new().pubkey());
+    let holder_token_account = set_token_account(&mut ctx, &mint, &holder.pubkey(), 100);
+    let holder_other_token_account = set_token_account(&mut ctx, &other_mint, &holder.pubkey(), 1_000);
+    let small_holder_token_account = set_token_account(&mut ctx, &mint, &small_holder.pubkey(), 10);
+
+    let token_gate = TokenGate {
+        mint,
+        min_balance: 50,
+    };
+
+    // create poll
+    let poll_pda = create_poll_with_params(
+        &mut ctx.banks_client,
+        &ctx.payer,
+        &ctx.last_blockhash,
+        &program_id,
+        &title,
+        &description,
+        PollParams {
+            token_gate: Some(token_gate),
+            ..PollParams::default()
+        },
+    ).await;
+
+    let poll_account = ctx.banks_client.get_account(poll_pda).await.unwrap().unwrap();
+    let poll_state = PollState::deserialize(&mut &poll_account.data[..]).unwrap();
+    assert_eq!(poll_state.token_gate, Some(token_gate));
+    assert_eq!(poll_account.data.len(), PollState::SIZE + TokenGate::SIZE);
+
+    // create party
+    let party_pda_a = create_party(
+        &mut ctx.banks_client,
+        &ctx.payer,
+        &ctx.last_blockhash,
+        &program_id,
+        &title_a,
+        &poll_pda,
+    ).await;
+
+    // update timestamp to 24 hours and 1 minute
+    let mut clock: Clock = ctx.banks_client.get_sysvar().await.unwrap();
+    clock.unix_timestamp += (60 * 60 * 24) + 60;
+    ctx.set_sysvar(&clock);
+
+    // start voting
+    start_voting(
+        &mut ctx.banks_client,
+        &ctx.payer,
+        &ctx.last_blockhash,
+        &program_id,
+        &poll_pda,
+    ).await;
+
+    // expect errors for a balance below the minimum, a foreign token account and a wrong mint
+    for (voter, token_account, expected) in [
+        (&small_holder, small_holder_token_account, JanecekError::InsufficientTokenBalance),
+        (&small_holder, holder_token_account, JanecekError::VoterNotEligible),
+        (&holder, holder_other_token_account, JanecekError::VoterNotEligible),
+    ] {
+        let err = vote_with_accounts_expect_fail(
+            &mut ctx.banks_client,
+            voter,
+            &ctx.last_blockhash,
+            &program_id,
+            &poll_pda,
+            &party_pda_a,
+            VoteType::Positive,
+            &[AccountMeta::new_readonly(token_account, false)],
+        ).await.unwrap();
+
+        match err {
+            TransactionError::InstructionError(_, InstructionError::Custom(code)) => {
+                assert_eq!(code, expected as u32, "Expected {:?} error", expected);
+            }
+            other => panic!("Unexpected transport error type: {:?}", other),
+        }
+    }
+
+    // token holder can vote
+    vote_with_accounts(
+        &mut ctx.banks_client,
+        &holder,
+        &ctx.last_blockhash,
+        &program_id,
+        &poll_pda,
+        &party_pda_a,
+        VoteType::Positive,
+        &[AccountMeta::new_readonly(holder_token_account, false)],
+    ).await;
+
+    let party_state_a = *PartyAccount::from_bytes(&ctx.banks_client.get_account(party_pda_a).await.unwrap().unwrap().data);
+    assert_eq!(party_state_a.positive_votes, 1);
+}
@@ -0,0 +1,409 @@
+mod helpers;
+use helpers::{
+    setup_test_env,
+    create_poll_with_params,
+    create_poll_expect_fail,
+    create_party,
+    start_voting,
+    set_mint,
+    set_token_account,
+    token_weighted_vote_accounts,
+    vault_pda,
+    vote_with_accounts,
+    retract_vote,
+    end_voting,
+    unlock_tokens,
+    unlock_tokens_expect_fail,
+    close_party,
+    close_poll_expect_fail,
+    close_token_weighted_poll,
+    PollParams,
+};
+use janecek_voting::{error::JanecekError, state::{PartyAccount, PollResults, PollState, TokenGate, VoteType, VoterAccount, VotingMode}};
+use borsh::{BorshDeserialize};
+use solana_program_test::ProgramTestContext;
+use solana_sdk::{clock::Clock, instruction::InstructionError, program_pack::Pack, pubkey::Pubkey, rent::Rent, signature::Keypair, signer::Signer, system_instruction, transaction::{Transaction, TransactionError}};
+
+
+async fn token_balance(ctx: &mut ProgramTestContext, token_account: &Pubkey) -> u64 {
+    let account = ctx.banks_client.get_account(*token_account).await.unwrap().unwrap();
+    spl_token::state::Account::unpack(&account.data).unwrap().amount
+}
+
+
+#[tokio::test]
+async fn test_token_weighted_requires_token_gate() {
+    let (mut ctx, program_id) = setup_test_env().await;
+
+    // expect error when token-weighted poll has no mint to weigh by
+    let err = create_poll_expect_fail(
+        &mut ctx.banks_client,
+        &ctx.payer,
+        &ctx.last_blockhash,
+        &program_id,
+        "DAO Proposal",
+        "Token holders pick the next grant",
+        PollParams {
+            voting_mode: VotingMode::TokenWeighted,
+            ..PollParams::default()
+        },
+    ).await.unwrap();
+
+    match err {
+        TransactionError::InstructionError(_, InstructionError::Custom(code)) => {
+            assert_eq!(code, JanecekError::InvalidTokenGate as u32, "Expected InvalidTokenGate error");
+        }
+        other => panic!("Unexpected transport error type: {:?}", other),
+    }
+}
+
+
+#[tokio::test]
+async fn test_token_weighted_vote_and_unlock() {
+    let (mut ctx, program_id) = setup_test_env().await;
+
+    let title = "DAO Proposal".to_string();
+    let description = "Token holders pick the next grant".to_string();
+    let whale = Keypair::new();
+    let holder = Keypair::new();
+
+    // transfer SOL to voters
+    for voter in [&whale, &holder] {
+        let transfer_ix = system_instruction::transfer(
+            &ctx.payer.pubkey(),
+            &voter.pubkey(),
+            1_000_000_000, // 1 SOL
+        );
+        let tx = Transaction::new_signed_with_payer(
+            &[transfer_ix],
+            Some(&ctx.payer.pubkey()),
+            &[&ctx.payer],
+            ctx.last_blockhash,
+        );
+        ctx.banks_client.process_transaction(tx).await.unwrap();
+    }
+
+    // token accounts for the gate mint
+    let mint = set_mint(&mut ctx, &Keypair::new().pubkey());
+    let whale_token_account = set_token_account(&mut ctx, &mint, &whale.pubkey(), 100);
+    let holder_token_account = set_token_account(&mut ctx, &mint, &holder.pubkey(), 10);
+
+    // create poll
+    let poll_pda = create_poll_with_params(
+        &mut ctx.banks_client,
+        &ctx.payer,
+        &ctx.last_blockhash,
+        &program_id,
+        &title,
+        &description,
+        PollParams {
+            token_gate: Some(TokenGate { mint, min_balance: 1 }),
+            voting_mode: VotingMode::TokenWeighted,
+            ..PollParams::default()
+        },
+    ).await;
+
+    let poll_state = PollState::deserialize(&mut &ctx.banks_client.get_account(poll_pda).await.unwrap().unwrap().data[..]).unwrap();
+    assert_eq!(poll_state.voting_mode, VotingMode::TokenWeighted);
+
+    // create parties
+    let mut party_pdas = Vec::new();
+    for party_title in ["Party A", "Party B"] {
+        let party_pda = create_party(
+            &mut ctx.banks_client,
+            &ctx.payer,
+            &ctx.last_blockhash,
+            &program_id,
+            party_title,
+            &poll_pda,
+        ).await;
+        party_pdas.push(party_pda);
+    }
+
+    // update timestamp to 24 hours and 1 minute
+    let mut clock: Clock = ctx.banks_client.get_sysvar().await.unwrap();
+    clock.unix_timestamp += (60 * 60 * 24) + 60;
+    ctx.set_sysvar(&clock);
+
+    // start voting
+    start_voting(
+        &mut ctx.banks_client,
+        &ctx.payer,
+        &ctx.last_blockhash,
+        &program_id,
+        &poll_pda,
+    ).await;
+
+    // first votes lock the whole balance in the poll vault
+    for (voter, token_account) in [(&whale, whale_token_account), (&holder, holder_token_account)] {
+        vote_with_accounts(
+            &mut ctx.banks_client,
+            voter,
+            &ctx.last_blockhash,
+            &program_id,
+            &poll_pda,
+            &party_pdas[0],
+            VoteType::Positive,
+            &token_weighted_vote_accounts(&program_id, &poll_pda, &token_account, &mint),
+        ).await;
+    }
+
+    // second vote reuses the locked weight without token accounts
+    let whale_voter_pda = vote_with_accounts(
+        &mut ctx.banks_client,
+        &whale,
+        &ctx.last_blockhash,
+        &program_id,
+        &poll_pda,
+        &party_pdas[1],
+        VoteType::Positive,
+        &[],
+    ).await;
+
+    let vault = vault_pda(&program_id, &poll_pda);
+    assert_eq!(token_balance(&mut ctx, &vault).await, 110);
+
+    let poll_state = PollState::deserialize(&mut &ctx.banks_client.get_account(poll_pda).await.unwrap().unwrap().data[..]).unwrap();
+    assert_eq!(poll_state.vault_rent_payer, whale.pubkey());
+    assert_eq!(token_balance(&mut ctx, &whale_token_account).await, 0);
+    assert_eq!(token_balance(&mut ctx, &holder_token_account).await, 0);
+
+    let voter_account = ctx.banks_client.get_account(whale_voter_pda).await.unwrap().unwrap();
+    let (voter_state, _) = VoterAccount::unpack(&voter_account.data);
+
+    assert_eq!(voter_state.vote_weight, 100);
+    assert_eq!(voter_state.locked_tokens, 100);
+
+    let party_state_a = *PartyAccount::from_bytes(&ctx.banks_client.get_account(party_pdas[0]).await.unwrap().unwrap().data);
+    let party_state_b = *PartyAccount::from_bytes(&ctx.banks_client.get_account(party_pdas[1]).await.unwrap().unwrap().data);
+    assert_eq!(party_state_a.positive_votes, 110);
+    assert_eq!(party_state_b.positive_votes, 100);
+
+    // retracting removes the full weight
+    retract_vote(
+        &mut ctx.banks_client,
+        &whale,
+        &ctx.last_blockhash,
+        &program_id,
+        &poll_pda,
+        &party_pdas[1],
+    ).await;
+
+    let party_state_b = *PartyAccount::from_bytes(&ctx.banks_client.get_account(party_pdas[1]).await.unwrap().unwrap().data);
+    assert_eq!(party_state_b.positive_votes, 0);
+
+    // expect error when unlocking before the poll is finalized
+    let err = unlock_tokens_expect_fail(
+        &mut ctx.banks_client,
+        &whale,
+        &ctx.last_blockhash,
+        &program_id,
+        &poll_pda,
+        &whale_token_account,
+    ).await.unwrap();
+
+    match err {
+        TransactionError::InstructionError(_, InstructionError::InvalidAccountData) => {}
+        other => panic!("Unexpected transport error type: {:?}", other),
+    }
+
+    // owner ends voting
+    let results_pda = end_voting(
+        &mut ctx.banks_client,
+        &ctx.payer,
+        &ctx.last_blockhash,
+        &program_id,
+        &poll_pda,
+        &party_pdas,
+    ).await;
+
+    let results_state = PollResults::try_from_slice(&ctx.banks_client.get_account(results_pda).await.unwrap().unwrap().data).unwrap();
+    assert_eq!(results_state.parties[0].party, party_pdas[0]);
+    assert_eq!(results_state.parties[0].net_votes, 110);
+
+    // update blockhash
+    ctx.last_blockhash = ctx.get_new_latest_blockhash().await.unwrap();
+
+    // whale gets the tokens back
+    unlock_tokens(
+        &mut ctx.banks_client,
+        &whale,
+        &ctx.last_blockhash,
+        &program_id,
+        &poll_pda,
+        &whale_token_account,
+    ).await;
+
+    assert_eq!(token_balance(&mut ctx, &whale_token_account).await, 100);
+    assert_eq!(token_balance(&mut ctx, &vault).await, 10);
+
+    // update blockhash
+    ctx.last_blockhash = ctx.get_new_latest_blockhash().await.unwrap();
+
+    // expect error when unlocking twice
+    let err = unlock_tokens_expect_fail(
+        &mut ctx.banks_client,
+        &whale,
+        &ctx.last_blockhash,
+        &program_id,
+        &poll_pda,
+        &whale_token_account,
+    ).await.unwrap();
+
+    match err {
+        TransactionError::InstructionError(_, InstructionError::Custom(code)) => {
+            assert_eq!(code, JanecekError::NoLockedTokens as u32, "Expected NoLockedTokens error");
+        }
+        other => panic!("Unexpected transport error type: {:?}", other),
+    }
+
+    for party_pda in &party_pdas {
+        close_party(
+            &mut ctx.banks_client,
+            &ctx.payer,
+            &ctx.last_blockhash,
+            &program_id,
+            &poll_pda,
+            party_pda,
+        ).await;
+    }
+
+    // expect error when closing the poll while the vault still holds tokens
+    let err = close_poll_expect_fail(
+        &mut ctx.banks_client,
+        &ctx.payer,
+        &ctx.last_blockhash,
+        &program_id,
+        &poll_pda,
+    ).await.unwrap();
+
+    match err {
+        TransactionError::InstructionError(_, InstructionError::Custom(code)) => {
+            assert_eq!(code, JanecekError::TokensStillLocked as u32, "Expected TokensStillLocked error");
+        }
+        other => panic!("Unexpected transport error type: {:?}", other),
+    }
+
+    // once the vault is empty it is closed with the poll, the whale paid for it
+    unlock_tokens(
+        &mut ctx.banks_client,
+        &holder,
+        &ctx.last_blockhash,
+        &program_id,
+        &poll_pda,
+        &holder_token_account,
+    ).await;
+
+    let whale_balance = ctx.banks_client.get_balance(whale.pubkey()).await.unwrap();
+    let vault_lamports = ctx.banks_client.get_balance(vault).await.unwrap();
+
+    close_token_weighted_poll(
+        &mut ctx.banks_client,
+        &ctx.payer,
+        &ctx.last_blockhash,
+        &program_id,
+        &poll_pda,
+        &whale.pubkey(),
+    ).await;
+
+    assert_eq!(ctx.banks_client.get_balance(whale.pubkey()).await.unwrap(), whale_balance + vault_lamports);
+    for closed_pda in [poll_pda, results_pda, vault] {
+        assert!(ctx.banks_client.get_account(closed_pda).await.unwrap().is_none());
+    }
+}
+
+
+#[tokio::test]
+async fn test_token_weighted_vote_with_prefunded_vault() {
+    let (mut ctx, program_id) = setup_test_env().await;
+
+    let holder = Keypair::new();
+
+    // transfer SOL to voter
+    let transfer_ix = system_instruction::transfer(
+        &ctx.payer.pubkey(),
+        &holder.pubkey(),
+        1_000_000_000, // 1 SOL
+    );
+    let tx = Transaction::new_signed_with_payer(
+        &[transfer_ix],
+        Some(&ctx.payer.pubkey()),
+        &[&ctx.payer],
+        ctx.last_blockhash,
+    );
+    ctx.banks_client.process_transaction(tx).await.unwrap();
+
+    // token account for the gate mint
+    let mint = set_mint(&mut ctx, &Keypair::new().pubkey());
+    let holder_token_account = set_token_account(&mut ctx, &mint, &holder.pubkey(), 10);
+
+    // create poll
+    let poll_pda = create_poll_with_params(
+        &mut ctx.banks_client,
+        &ctx.payer,
+        &ctx.last_blockhash,
+        &program_id,
+        "DAO Proposal",
+        "Token holders pick the next grant",
+        PollParams {
+            token_gate: Some(TokenGate { mint, min_balance: 1 }),
+            voting_mode: VotingMode::TokenWeighted,
+            ..PollParams::default()
+        },
+    ).await;
+
+    // create party
+    let party_pda = create_party(
+        &mut ctx.banks_client,
+        &ctx.payer,
+        &ctx.last_blockhash,
+        &program_id,
+        "Party A",
+        &poll_pda,
+    ).await;
+
+    // update timestamp to 24 hours and 1 minute
+    let mut clock: Clock = ctx.banks_client.get_sysvar().await.unwrap();
+    clock.unix_timestamp += (60 * 60 * 24) + 60;
+    ctx.set_sysvar(&clock);
+
+    // start voting
+    start_voting(
+        &mut ctx.banks_client,
+        &ctx.payer,
+        &ctx.last_blockhash,
+        &program_id,
+        &poll_pda,
+    ).await;
+
+    // someone funds the vault address before the first vote, which must not block voting
+    let vault = vault_pda(&program_id, &poll_pda);
+    let transfer_ix = system_instruction::transfer(&ctx.payer.pubkey(), &vault, 1_000_000);
+    let tx = Transaction::new_signed_with_payer(
+        &[transfer_ix],
+        Some(&ctx.payer.pubkey()),
+        &[&ctx.payer],
+        ctx.last_blockhash,
+    );
+    ctx.banks_client.process_transaction(tx).await.unwrap();
+
+    vote_with_accounts(
+        &mut ctx.banks_client,
+        &holder,
+        &ctx.last_blockhash,
+        &program_id,
+        &poll_pda,
+        &party_pda,
+        VoteType::Positive,
+        &token_weighted_vote_accounts(&program_id, &poll_pda, &holder_token_account, &mint),
+    ).await;
+
+    let rent: Rent = ctx.banks_client.get_sysvar().await.unwrap();
+    let vault_account = ctx.banks_client.get_account(vault).await.unwrap().unwrap();
+    assert_eq!(vault_account.owner, spl_token::id());
+    assert!(vault_account.lamports >= rent.minimum_balance(vault_account.data.len()));
+    assert_eq!(token_balance(&mut ctx, &vault).await, 10);
+
+    let party_state = *PartyAccount::from_bytes(&ctx.banks_client.get_account(party_pda).await.unwrap().unwrap().data);
+    assert_eq!(party_state.positive_votes, 10);
+}
@@ -0,0 +1,153 @@
+mod helpers;
+use helpers::{setup_test_env, create_poll, create_party, remove_party, start_voting, update_party_metadata, update_party_metadata_expect_fail, PartyMetadata};
+use janecek_voting::{error::JanecekError, state::PartyAccount};
+use solana_sdk::{clock::Clock, transaction::TransactionError, instruction::InstructionError};
+
+
+#[tokio::test]
+async fn test_update_party_metadata_success() {
+    let (mut ctx, program_id) = setup_test_env().await;
+
+    let title = "Presidential Election".to_string();
+    let description = "Vote for the next president".to_string();
+    let metadata = PartyMetadata {
+        description: "Party of the green future".to_string(),
+        metadata_uri: "https://example.com/party-a.json".to_string(),
+        metadata_hash: [7u8; 32],
+    };
+
+    // create poll
+    let poll_pda = create_poll(
+        &mut ctx.banks_client,
+        &ctx.payer,
+        &ctx.last_blockhash,
+        &program_id,
+        &title,
+        &description,
+    ).await;
+
+    // create party with a typo
+    let party_pda = create_party(
+        &mut ctx.banks_client,
+        &ctx.payer,
+        &ctx.last_blockhash,
+        &program_id,
+        "Prty A",
+        &poll_pda,
+    ).await;
+
+    // add metadata in place, the title stays
+    update_party_metadata(
+        &mut ctx.banks_client,
+        &ctx.payer,
+        &ctx.last_blockhash,
+        &program_id,
+        &poll_pda,
+        &party_pda,
+        metadata.clone(),
+    ).await;
+
+    let party_account = ctx.banks_client.get_account(party_pda).await.unwrap().unwrap();
+    let party_state = *PartyAccount::from_bytes(&party_account.data);
+
+    assert_eq!(party_state.title(), "Prty A");
+    assert_eq!(party_state.description(), metadata.description);
+    assert_eq!(party_state.metadata_uri(), metadata.metadata_uri);
+    assert_eq!(party_state.metadata_hash, metadata.metadata_hash);
+    assert_eq!(party_account.data.len(), PartyAccount::SIZE);
+
+    // clear the metadata again
+    update_party_metadata(
+        &mut ctx.banks_client,
+        &ctx.payer,
+        &ctx.last_blockhash,
+        &program_id,
+        &poll_pda,
+        &party_pda,
+        PartyMetadata::default(),
+    ).await;
+
+    let party_account = ctx.banks_client.get_account(party_pda).await.unwrap().unwrap();
+    let party_state = *PartyAccount::from_bytes(&party_account.data);
+
+    assert_eq!(party_state.title(), "Prty A");
+    assert_eq!(party_state.description(), "");
+    assert_eq!(party_state.metadata_uri(), "");
+    assert_eq!(party_account.data.len(), PartyAccount::SIZE);
+
+    // expect error when the metadata URI is too long
+    let err = update_party_metadata_expect_fail(
+        &mut ctx.banks_client,
+        &ctx.payer,
+        &ctx.last_blockhash,
+        &program_id,
+        &poll_pda,
+        &party_pda,
+        PartyMetadata {
+            metadata_uri: "a".repeat(PartyAccount::MAX_METADATA_URI_LEN + 1),
+            ..PartyMetadata::default()
+        },
+    ).await.unwrap();
+
+    match err {
+        TransactionError::InstructionError(_, InstructionError::Custom(code)) => {
+            assert_eq!(code, JanecekError::InvalidDataLength as u32, "Expected InvalidDataLength error");
+        }
+        other => panic!("Unexpected transport error type: {:?}", other),
+    }
+
+    // fixing the typo moves the party to the address of the new title
+    remove_party(
+        &mut ctx.banks_client,
+        &ctx.payer,
+        &ctx.last_blockhash,
+        &program_id,
+        &poll_pda,
+        &party_pda,
+    ).await;
+
+    let renamed_party_pda = create_party(
+        &mut ctx.banks_client,
+        &ctx.payer,
+        &ctx.last_blockhash,
+        &program_id,
+        "Party A",
+        &poll_pda,
+    ).await;
+
+    assert_ne!(renamed_party_pda, party_pda);
+    assert!(ctx.banks_client.get_account(party_pda).await.unwrap().is_none());
+
+    let party_state = *PartyAccount::from_bytes(&ctx.banks_client.get_account(renamed_party_pda).await.unwrap().unwrap().data);
+    assert_eq!(party_state.title(), "Party A");
+
+    // update timestamp to 24 hours and 1 minute
+    let mut clock: Clock = ctx.banks_client.get_sysvar().await.unwrap();
+    clock.unix_timestamp += (60 * 60 * 24) + 60;
+    ctx.set_sysvar(&clock);
+
+    // start voting
+    start_voting(
+        &mut ctx.banks_client,
+        &ctx.payer,
+        &ctx.last_blockhash,
+        &program_id,
+        &poll_pda,
+    ).await;
+
+    // expect error when updating a party after registration
+    let err = update_party_metadata_expect_fail(
+        &mut ctx.banks_client,
+        &ctx.payer,
+        &ctx.last_blockhash,
+        &program_id,
+        &poll_pda,
+        &renamed_party_pda,
+        metadata,
+    ).await.unwrap();
+
+    match err {
+        TransactionError::InstructionError(_, InstructionError::InvalidAccountData) => {}
+        other => panic!("Unexpected transport error type: {:?}", other),
+    }
+}
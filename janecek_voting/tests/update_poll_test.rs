@@ -0,0 +1,260 @@
+mod helpers;
+use helpers::{setup_test_env, create_poll, migrate_account, update_poll, update_poll_expect_fail, initiate_owner_transfer, accept_owner_transfer};
+use janecek_voting::{error::JanecekError, state::{AccountType, LegacyPollState, PollSeeds, PollState, VotingPhase}};
+use borsh::{BorshDeserialize, BorshSerialize};
+use solana_program::hash::hash;
+use solana_sdk::{account::Account, clock::Clock, pubkey::Pubkey, rent::Rent, signature::Keypair, signer::Signer, system_instruction, transaction::{Transaction, TransactionError}, instruction::InstructionError};
+
+
+#[tokio::test]
+async fn test_update_poll_success() {
+    let (mut ctx, program_id) = setup_test_env().await;
+
+    let title = "Presidential Election".to_string();
+    let description = "Vote for the next presidnet".to_string();
+    let outsider = Keypair::new();
+
+    // transfer SOL to outsider
+    let transfer_ix = system_instruction::transfer(
+        &ctx.payer.pubkey(),
+        &outsider.pubkey(),
+        1_000_000_000, // 1 SOL
+    );
+    let tx = Transaction::new_signed_with_payer(
+        &[transfer_ix],
+        Some(&ctx.payer.pubkey()),
+        &[&ctx.payer],
+        ctx.last_blockhash,
+    );
+    ctx.banks_client.process_transaction(tx).await.unwrap();
+
+    // create poll with a typo
+    let poll_pda = create_poll(
+        &mut ctx.banks_client,
+        &ctx.payer,
+        &ctx.last_blockhash,
+        &program_id,
+        &title,
+        &description,
+    ).await;
+
+    // expect error when someone other than the owner edits the poll
+    let err = update_poll_expect_fail(
+        &mut ctx.banks_client,
+        &outsider,
+        &ctx.last_blockhash,
+        &program_id,
+        &poll_pda,
+        &title,
+        "Vote for the next president",
+    ).await.unwrap();
+
+    match err {
+        TransactionError::InstructionError(_, InstructionError::IllegalOwner) => {}
+        other => panic!("Unexpected transport error type: {:?}", other),
+    }
+
+    // expect error when the new description is too long
+    let err = update_poll_expect_fail(
+        &mut ctx.banks_client,
+        &ctx.payer,
+        &ctx.last_blockhash,
+        &program_id,
+        &poll_pda,
+        &title,
+        &"a".repeat(PollState::MAX_DESCRIPTION_LEN + 1),
+    ).await.unwrap();
+
+    match err {
+        TransactionError::InstructionError(_, InstructionError::Custom(code)) => {
+            assert_eq!(code, JanecekError::InvalidDataLength as u32, "Expected InvalidDataLength error");
+        }
+        other => panic!("Unexpected transport error type: {:?}", other),
+    }
+
+    // owner fixes the typo
+    update_poll(
+        &mut ctx.banks_client,
+        &ctx.payer,
+        &ctx.last_blockhash,
+        &program_id,
+        &poll_pda,
+        &title,
+        "Vote for the next president",
+    ).await;
+
+    let poll_account = ctx.banks_client.get_account(poll_pda).await.unwrap().unwrap();
+    let poll_state = PollState::deserialize(&mut &poll_account.data[..]).unwrap();
+
+    assert_eq!(poll_state.description, "Vote for the next president");
+    assert_eq!(poll_account.data.len(), PollState::SIZE);
+
+    // poll address does not depend on the texts
+    assert_eq!(poll_state.seeds, PollSeeds::Creator { creator: ctx.payer.pubkey(), poll_nonce: 0 });
+    assert_eq!(poll_state.seeds.find_address(&program_id).0, poll_pda);
+}
+
+
+#[tokio::test]
+async fn test_update_migrated_poll_refunds_rent() {
+    let (mut ctx, program_id) = setup_test_env().await;
+    let rent: Rent = ctx.banks_client.get_sysvar().await.unwrap();
+    let clock: Clock = ctx.banks_client.get_sysvar().await.unwrap();
+
+    // legacy poll with a description beyond today's limit
+    let legacy_state = LegacyPollState {
+        discriminator: PollState::LEGACY_NAME.to_string(),
+        title: "Presidential Election".to_string(),
+        description: "a".repeat(PollState::SIZE),
+        phase: VotingPhase::Registration,
+        party_counter: 0,
+        owner: ctx.payer.pubkey(),
+        expected_new_owner: ctx.payer.pubkey(),
+        created_at: clock.unix_timestamp,
+        voting_start_at: 0,
+    };
+    let legacy_data = legacy_state.try_to_vec().unwrap();
+    let poll_pda = Pubkey::new_unique();
+    ctx.set_account(&poll_pda, &Account {
+        lamports: rent.minimum_balance(legacy_data.len()),
+        data: legacy_data,
+        owner: program_id,
+        executable: false,
+        rent_epoch: 0,
+    }.into());
+
+    migrate_account(
+        &mut ctx.banks_client,
+        &ctx.payer,
+        &ctx.last_blockhash,
+        &program_id,
+        &poll_pda,
+    ).await;
+
+    let poll_account = ctx.banks_client.get_account(poll_pda).await.unwrap().unwrap();
+    assert!(poll_account.data.len() > PollState::SIZE);
+    let excess = poll_account.lamports - rent.minimum_balance(PollState::SIZE);
+    let owner_lamports = ctx.banks_client.get_balance(ctx.payer.pubkey()).await.unwrap();
+
+    // shorter description shrinks the account back to the fixed size
+    update_poll(
+        &mut ctx.banks_client,
+        &ctx.payer,
+        &ctx.last_blockhash,
+        &program_id,
+        &poll_pda,
+        "Presidential Election",
+        "Vote for the next president",
+    ).await;
+
+    let poll_account = ctx.banks_client.get_account(poll_pda).await.unwrap().unwrap();
+    let poll_state = PollState::deserialize(&mut &poll_account.data[..]).unwrap();
+
+    assert_eq!(poll_state.description, "Vote for the next president");
+    assert_eq!(poll_account.data.len(), PollState::SIZE);
+    assert_eq!(poll_account.lamports, rent.minimum_balance(PollState::SIZE));
+
+    // the owner paid the rent of the legacy poll and gets the excess back, less the fee
+    let owner_lamports_after = ctx.banks_client.get_balance(ctx.payer.pubkey()).await.unwrap();
+    assert!(owner_lamports_after > owner_lamports + excess - 10_000);
+
+    // legacy polls keep their text seeds
+    assert_eq!(poll_state.seeds, PollSeeds::Text {
+        title_seed: hash("Presidential Election".as_bytes()).to_bytes(),
+        description_seed: hash("a".repeat(PollState::SIZE).as_bytes()).to_bytes(),
+    });
+}
+
+
+#[tokio::test]
+async fn test_update_migrated_poll_keeps_rent_of_previous_owner() {
+    let (mut ctx, program_id) = setup_test_env().await;
+    let rent: Rent = ctx.banks_client.get_sysvar().await.unwrap();
+    let clock: Clock = ctx.banks_client.get_sysvar().await.unwrap();
+    let new_owner = Keypair::new();
+
+    // transfer SOL to the new owner
+    let transfer_ix = system_instruction::transfer(
+        &ctx.payer.pubkey(),
+        &new_owner.pubkey(),
+        1_000_000_000, // 1 SOL
+    );
+    let tx = Transaction::new_signed_with_payer(
+        &[transfer_ix],
+        Some(&ctx.payer.pubkey()),
+        &[&ctx.payer],
+        ctx.last_blockhash,
+    );
+    ctx.banks_client.process_transaction(tx).await.unwrap();
+
+    // legacy poll of the payer with a description beyond today's limit
+    let legacy_state = LegacyPollState {
+        discriminator: PollState::LEGACY_NAME.to_string(),
+        title: "Presidential Election".to_string(),
+        description: "a".repeat(PollState::SIZE),
+        phase: VotingPhase::Registration,
+        party_counter: 0,
+        owner: ctx.payer.pubkey(),
+        expected_new_owner: ctx.payer.pubkey(),
+        created_at: clock.unix_timestamp,
+        voting_start_at: 0,
+    };
+    let legacy_data = legacy_state.try_to_vec().unwrap();
+    let poll_pda = Pubkey::new_unique();
+    ctx.set_account(&poll_pda, &Account {
+        lamports: rent.minimum_balance(legacy_data.len()),
+        data: legacy_data,
+        owner: program_id,
+        executable: false,
+        rent_epoch: 0,
+    }.into());
+
+    migrate_account(
+        &mut ctx.banks_client,
+        &ctx.payer,
+        &ctx.last_blockhash,
+        &program_id,
+        &poll_pda,
+    ).await;
+
+    initiate_owner_transfer(
+        &mut ctx.banks_client,
+        &ctx.payer,
+        &ctx.last_blockhash,
+        &program_id,
+        &new_owner.pubkey(),
+        &poll_pda,
+    ).await;
+
+    accept_owner_transfer(
+        &mut ctx.banks_client,
+        &new_owner,
+        &ctx.last_blockhash,
+        &program_id,
+        &poll_pda,
+    ).await;
+
+    let poll_lamports = ctx.banks_client.get_balance(poll_pda).await.unwrap();
+    let new_owner_lamports = ctx.banks_client.get_balance(new_owner.pubkey()).await.unwrap();
+
+    // the new owner shrinks the poll but did not pay its rent
+    update_poll(
+        &mut ctx.banks_client,
+        &new_owner,
+        &ctx.last_blockhash,
+        &program_id,
+        &poll_pda,
+        "Presidential Election",
+        "Vote for the next president",
+    ).await;
+
+    let poll_account = ctx.banks_client.get_account(poll_pda).await.unwrap().unwrap();
+    let poll_state = PollState::deserialize(&mut &poll_account.data[..]).unwrap();
+
+    assert_eq!(poll_state.rent_payer, ctx.payer.pubkey());
+    assert_eq!(poll_account.data.len(), PollState::SIZE);
+
+    // the excess stays in the poll until it is closed to the rent payer
+    assert_eq!(poll_account.lamports, poll_lamports);
+    assert!(ctx.banks_client.get_balance(new_owner.pubkey()).await.unwrap() < new_owner_lamports);
+}
@@ -1,8 +1,8 @@
 mod helpers;
-use helpers::{setup_test_env, create_poll, create_party, vote, start_voting, vote_expect_fail};
-use janecek_voting::state::{PollState, VoteType, VotingPhase, PartyAccount, VoterAccount};
-use borsh::{BorshDeserialize};
-use solana_sdk::{clock::Clock, signature::Keypair, signer::Signer, system_instruction, transaction::{Transaction, TransactionError}, instruction::InstructionError};
+use helpers::{setup_test_env, create_poll, create_poll_with_params, create_party, vote, start_voting, vote_expect_fail, vote_with_voter_account_expect_fail, end_voting, get_config, PollParams};
+use janecek_voting::{error::JanecekError, state::{PollState, VoteRules, VoteType, VotingPhase, PartyAccount, VoterAccount}};
+use borsh::BorshDeserialize;
+use solana_sdk::{account::Account, clock::Clock, pubkey::Pubkey, rent::Rent, signature::Keypair, signer::Signer, system_instruction, transaction::{Transaction, TransactionError}, instruction::InstructionError};
 
 #[tokio::test]
 async fn test_vote_success() {
@@ -11,10 +11,10 @@ async fn test_vote_success() {
     // data for pda generation
     let title = "Presidential Election".to_string();
     let description = "Vote for the next president".to_string();
-    let title_A = "Party A".to_string();
-    let title_B = "Party B".to_string();
-    let title_C = "Party C".to_string();
-    let title_D = "Party D".to_string();
+    let title_a = "Party A".to_string();
+    let title_b = "Party B".to_string();
+    let title_c = "Party C".to_string();
+    let title_d = "Party D".to_string();
     let voter = Keypair::new();
     
 
@@ -46,39 +46,39 @@ async fn test_vote_success() {
 
 
     // create parties
-    let party_pda_A = create_party(
+    let party_pda_a = create_party(
         &mut ctx.banks_client,
         &ctx.payer,
         &ctx.last_blockhash,
         &program_id,
-        &title_A,
+        &title_a,
         &poll_pda,
     ).await;
 
-    let party_pda_B = create_party(
+    let party_pda_b = create_party(
         &mut ctx.banks_client,
         &ctx.payer,
         &ctx.last_blockhash,
         &program_id,
-        &title_B,
+        &title_b,
         &poll_pda,
     ).await;
 
-    let party_pda_C = create_party(
+    let party_pda_c = create_party(
         &mut ctx.banks_client,
         &ctx.payer,
         &ctx.last_blockhash,
         &program_id,
-        &title_C,
+        &title_c,
         &poll_pda,
     ).await;
 
-    let party_pda_D = create_party(
+    let party_pda_d = create_party(
         &mut ctx.banks_client,
         &ctx.payer,
         &ctx.last_blockhash,
         &program_id,
-        &title_D,
+        &title_d,
         &poll_pda,
     ).await;
 
@@ -104,7 +104,7 @@ async fn test_vote_success() {
         &ctx.last_blockhash,
         &program_id,
         &poll_pda,
-        &party_pda_A,
+        &party_pda_a,
         VoteType::Positive,
     ).await;
 
@@ -118,7 +118,7 @@ async fn test_vote_success() {
         &ctx.last_blockhash,
         &program_id,
         &poll_pda,
-        &party_pda_A,
+        &party_pda_a,
         VoteType::Positive,
     ).await.unwrap();
 
@@ -140,7 +140,7 @@ async fn test_vote_success() {
         &ctx.last_blockhash,
         &program_id,
         &poll_pda,
-        &party_pda_B,
+        &party_pda_b,
         VoteType::Negative,
     ).await.unwrap();
 
@@ -162,7 +162,7 @@ async fn test_vote_success() {
         &ctx.last_blockhash,
         &program_id,
         &poll_pda,
-        &party_pda_B,
+        &party_pda_b,
         VoteType::Positive,
     ).await;
 
@@ -173,7 +173,7 @@ async fn test_vote_success() {
         &ctx.last_blockhash,
         &program_id,
         &poll_pda,
-        &party_pda_C,
+        &party_pda_c,
         VoteType::Negative,
     ).await;
 
@@ -187,7 +187,7 @@ async fn test_vote_success() {
         &ctx.last_blockhash,
         &program_id,
         &poll_pda,
-        &party_pda_D,
+        &party_pda_d,
         VoteType::Positive,
     ).await.unwrap();
 
@@ -210,7 +210,7 @@ async fn test_vote_success() {
         &ctx.last_blockhash,
         &program_id,
         &poll_pda,
-        &party_pda_D,
+        &party_pda_d,
         VoteType::Negative,
     ).await.unwrap();
 
@@ -226,45 +226,53 @@ async fn test_vote_success() {
     }
 
     let poll_account = ctx.banks_client.get_account(poll_pda).await.unwrap().unwrap();
-    let poll_state = PollState::try_from_slice(&poll_account.data).unwrap();
+    let poll_state = PollState::deserialize(&mut &poll_account.data[..]).unwrap();
 
-    let party_account_A = ctx.banks_client.get_account(party_pda_A).await.unwrap().unwrap();
-    let party_state_A = PartyAccount::try_from_slice(&party_account_A.data).unwrap();
+    let party_account_a = ctx.banks_client.get_account(party_pda_a).await.unwrap().unwrap();
+    let party_state_a = *PartyAccount::from_bytes(&party_account_a.data);
 
-    let party_account_B = ctx.banks_client.get_account(party_pda_B).await.unwrap().unwrap();
-    let party_state_B = PartyAccount::try_from_slice(&party_account_B.data).unwrap();
+    let party_account_b = ctx.banks_client.get_account(party_pda_b).await.unwrap().unwrap();
+    let party_state_b = *PartyAccount::from_bytes(&party_account_b.data);
 
-    let party_account_C = ctx.banks_client.get_account(party_pda_C).await.unwrap().unwrap();
-    let party_state_C = PartyAccount::try_from_slice(&party_account_C.data).unwrap();
+    let party_account_c = ctx.banks_client.get_account(party_pda_c).await.unwrap().unwrap();
+    let party_state_c = *PartyAccount::from_bytes(&party_account_c.data);
 
-    let party_account_D = ctx.banks_client.get_account(party_pda_D).await.unwrap().unwrap();
-    let party_state_D = PartyAccount::try_from_slice(&party_account_D.data).unwrap();
+    let party_account_d = ctx.banks_client.get_account(party_pda_d).await.unwrap().unwrap();
+    let party_state_d = *PartyAccount::from_bytes(&party_account_d.data);
 
     let voter_account = ctx.banks_client.get_account(voter_pda).await.unwrap().unwrap();
-    let mut cursor = std::io::Cursor::new(&voter_account.data);
-    let voter_state = VoterAccount::deserialize_reader(&mut cursor).unwrap();
+    let (voter_state, voted_parties) = VoterAccount::unpack(&voter_account.data);
 
     assert_eq!(poll_state.phase as u8, VotingPhase::Voting as u8);
     assert_eq!(poll_state.party_counter, 4);
 
-    assert_eq!(party_state_A.positive_votes, 1);
-    assert_eq!(party_state_A.negative_votes, 0);
+    assert_eq!(party_state_a.positive_votes, 1);
+    assert_eq!(party_state_a.negative_votes, 0);
 
-    assert_eq!(party_state_B.positive_votes, 1);
-    assert_eq!(party_state_B.negative_votes, 0);
+    assert_eq!(party_state_b.positive_votes, 1);
+    assert_eq!(party_state_b.negative_votes, 0);
 
-    assert_eq!(party_state_C.positive_votes, 0);
-    assert_eq!(party_state_C.negative_votes, 1);
+    assert_eq!(party_state_c.positive_votes, 0);
+    assert_eq!(party_state_c.negative_votes, 1);
 
-    assert_eq!(party_state_D.positive_votes, 0);
-    assert_eq!(party_state_D.negative_votes, 0);
+    assert_eq!(party_state_d.positive_votes, 0);
+    assert_eq!(party_state_d.negative_votes, 0);
 
     assert_eq!(voter_state.positive_used, 2);
     assert_eq!(voter_state.negative_used, 1);
-    assert_eq!(voter_state.voted_parties.len(), 3);
-    assert_eq!(voter_state.voted_parties[0], party_pda_A);
-    assert_eq!(voter_state.voted_parties[1], party_pda_B);
-    assert_eq!(voter_state.voted_parties[2], party_pda_C);
+    assert_eq!(voted_parties.len(), 3);
+    assert_eq!(voted_parties[0].party, party_pda_a);
+    assert_eq!(voted_parties[1].party, party_pda_b);
+    assert_eq!(voted_parties[2].party, party_pda_c);
+    assert_eq!(voted_parties[0].vote_type(), VoteType::Positive);
+    assert_eq!(voted_parties[1].vote_type(), VoteType::Positive);
+    assert_eq!(voted_parties[2].vote_type(), VoteType::Negative);
+
+    let clock: Clock = ctx.banks_client.get_sysvar().await.unwrap();
+    for voted in voted_parties {
+        assert_eq!(voted.voted_at, clock.unix_timestamp);
+        assert!(voted.slot.get() > 0 && voted.slot.get() <= clock.slot);
+    }
 
 }
 
@@ -275,7 +283,7 @@ async fn test_vote_after_voting_period() {
     // data for pda generation
     let title = "Presidential Election".to_string();
     let description = "Vote for the next president".to_string();
-    let title_A = "Party A".to_string();
+    let title_a = "Party A".to_string();
     let voter = Keypair::new();
     
 
@@ -283,7 +291,7 @@ async fn test_vote_after_voting_period() {
     let transfer_ix_1 = system_instruction::transfer(
         &ctx.payer.pubkey(),
         &voter.pubkey(),
-        1_000_000, // 1 SOL
+        1_000_000_000, // 1 SOL
     );
 
     let tx = Transaction::new_signed_with_payer(
@@ -307,12 +315,12 @@ async fn test_vote_after_voting_period() {
 
 
     // create parties
-    let party_pda_A = create_party(
+    let party_pda_a = create_party(
         &mut ctx.banks_client,
         &ctx.payer,
         &ctx.last_blockhash,
         &program_id,
-        &title_A,
+        &title_a,
         &poll_pda,
     ).await;
 
@@ -338,31 +346,583 @@ async fn test_vote_after_voting_period() {
     clock.unix_timestamp += (60 * 60 * 24 * 7) + 60;
     ctx.set_sysvar(&clock);
 
-    // expect tx without error when user try to vote after voting period, but it will not be counted, just update Vote state
-    vote(
+    // expect error when user try to vote after voting period
+    let err = vote_expect_fail(
         &mut ctx.banks_client,
         &ctx.payer,
         &ctx.last_blockhash,
         &program_id,
         &poll_pda,
-        &party_pda_A,
+        &party_pda_a,
         VoteType::Positive,
+    ).await.unwrap();
+
+    match err {
+        TransactionError::InstructionError(_, InstructionError::Custom(code)) => {
+            assert_eq!(
+                code,
+                janecek_voting::error::JanecekError::VotingPeriodFinished as u32,
+                "Expected VotingPeriodFinished error"
+            );
+        }
+        other => panic!("Unexpected transport error type: {:?}", other),
+    }
+
+    // the failed vote does not touch the poll
+    let poll_account = ctx.banks_client.get_account(poll_pda).await.unwrap().unwrap();
+    let poll_state = PollState::deserialize(&mut &poll_account.data[..]).unwrap();
+    assert_eq!(poll_state.phase as u8, VotingPhase::Voting as u8);
+
+    // any user can finalize the expired poll
+    end_voting(
+        &mut ctx.banks_client,
+        &voter,
+        &ctx.last_blockhash,
+        &program_id,
+        &poll_pda,
+        &[party_pda_a],
     ).await;
-    
+
+    // update blockhash
+    ctx.last_blockhash = ctx.get_new_latest_blockhash().await.unwrap();
+
+    // expect error when user try to vote after the poll is finalized
+    let err = vote_expect_fail(
+        &mut ctx.banks_client,
+        &ctx.payer,
+        &ctx.last_blockhash,
+        &program_id,
+        &poll_pda,
+        &party_pda_a,
+        VoteType::Positive,
+    ).await.unwrap();
+
+    match err {
+        TransactionError::InstructionError(_, InstructionError::Custom(code)) => {
+            assert_eq!(
+                code,
+                janecek_voting::error::JanecekError::VotingPeriodFinished as u32,
+                "Expected VotingPeriodFinished error"
+            );
+        }
+        other => panic!("Unexpected transport error type: {:?}", other),
+    }
 
     let poll_account = ctx.banks_client.get_account(poll_pda).await.unwrap().unwrap();
-    let poll_state = PollState::try_from_slice(&poll_account.data).unwrap();
+    let poll_state = PollState::deserialize(&mut &poll_account.data[..]).unwrap();
 
-    let party_account_A = ctx.banks_client.get_account(party_pda_A).await.unwrap().unwrap();
-    let party_state_A = PartyAccount::try_from_slice(&party_account_A.data).unwrap();
+    let party_account_a = ctx.banks_client.get_account(party_pda_a).await.unwrap().unwrap();
+    let party_state_a = *PartyAccount::from_bytes(&party_account_a.data);
 
     assert_eq!(poll_state.party_counter, 1);
 
-    assert_eq!(party_state_A.positive_votes, 0);
-    assert_eq!(party_state_A.negative_votes, 0);
+    assert_eq!(party_state_a.positive_votes, 0);
+    assert_eq!(party_state_a.negative_votes, 0);
 
     assert_eq!(poll_state.phase as u8, VotingPhase::Results as u8);
 
 }
 
 
+
+#[tokio::test]
+async fn test_vote_after_long_registration() {
+    let (mut ctx, program_id) = setup_test_env().await;
+
+    // data for pda generation
+    let title = "Presidential Election".to_string();
+    let description = "Vote for the next president".to_string();
+    let title_a = "Party A".to_string();
+
+    // create poll
+    let poll_pda = create_poll(
+        &mut ctx.banks_client,
+        &ctx.payer,
+        &ctx.last_blockhash,
+        &program_id,
+        &title,
+        &description,
+    ).await;
+
+    // create parties
+    let party_pda_a = create_party(
+        &mut ctx.banks_client,
+        &ctx.payer,
+        &ctx.last_blockhash,
+        &program_id,
+        &title_a,
+        &poll_pda,
+    ).await;
+
+    // registration runs for 6 days
+    let mut clock: Clock = ctx.banks_client.get_sysvar().await.unwrap();
+    clock.unix_timestamp += 60 * 60 * 24 * 6;
+    ctx.set_sysvar(&clock);
+
+    // start voting
+    start_voting(
+        &mut ctx.banks_client,
+        &ctx.payer,
+        &ctx.last_blockhash,
+        &program_id,
+        &poll_pda,
+    ).await;
+
+    // 6 days and 23 hours into voting, 12 days and 23 hours after poll creation
+    let mut clock: Clock = ctx.banks_client.get_sysvar().await.unwrap();
+    clock.unix_timestamp += (60 * 60 * 24 * 7) - (60 * 60);
+    ctx.set_sysvar(&clock);
+
+    // vote is still counted, the window is measured from voting start
+    vote(
+        &mut ctx.banks_client,
+        &ctx.payer,
+        &ctx.last_blockhash,
+        &program_id,
+        &poll_pda,
+        &party_pda_a,
+        VoteType::Positive,
+    ).await;
+
+    let poll_account = ctx.banks_client.get_account(poll_pda).await.unwrap().unwrap();
+    let poll_state = PollState::deserialize(&mut &poll_account.data[..]).unwrap();
+
+    let party_account_a = ctx.banks_client.get_account(party_pda_a).await.unwrap().unwrap();
+    let party_state_a = *PartyAccount::from_bytes(&party_account_a.data);
+
+    assert_eq!(poll_state.voting_ends_at(&get_config(&mut ctx.banks_client, &program_id).await), poll_state.voting_start_at + (60 * 60 * 24 * 7));
+    assert_eq!(poll_state.phase as u8, VotingPhase::Voting as u8);
+    assert_eq!(party_state_a.positive_votes, 1);
+}
+
+
+#[tokio::test]
+async fn test_vote_with_custom_rules() {
+    let (mut ctx, program_id) = setup_test_env().await;
+
+    // 4 positive and 2 negative votes, each negative needs 2 positives
+    let vote_rules = VoteRules {
+        positive_votes: 4,
+        negative_votes: 2,
+        positives_per_negative: 2,
+        allow_negative: true,
+    };
+
+    // create poll
+    let poll_pda = create_poll_with_params(
+        &mut ctx.banks_client,
+        &ctx.payer,
+        &ctx.last_blockhash,
+        &program_id,
+        "Parliament Election",
+        "Vote for the next parliament",
+        PollParams {
+            vote_rules: Some(vote_rules),
+            ..PollParams::default()
+        },
+    ).await;
+
+    // create parties
+    let mut party_pdas = Vec::new();
+    for party_title in ["Party A", "Party B", "Party C", "Party D", "Party E", "Party F", "Party G"] {
+        let party_pda = create_party(
+            &mut ctx.banks_client,
+            &ctx.payer,
+            &ctx.last_blockhash,
+            &program_id,
+            party_title,
+            &poll_pda,
+        ).await;
+        party_pdas.push(party_pda);
+    }
+
+    // update timestamp to 24 hours and 1 minute
+    let mut clock: Clock = ctx.banks_client.get_sysvar().await.unwrap();
+    clock.unix_timestamp += (60 * 60 * 24) + 60;
+    ctx.set_sysvar(&clock);
+
+    // start voting
+    start_voting(
+        &mut ctx.banks_client,
+        &ctx.payer,
+        &ctx.last_blockhash,
+        &program_id,
+        &poll_pda,
+    ).await;
+
+    // +A, +B, then the first negative is unlocked: -E
+    for (party_pda, vote_type) in [
+        (party_pdas[0], VoteType::Positive),
+        (party_pdas[1], VoteType::Positive),
+        (party_pdas[4], VoteType::Negative),
+    ] {
+        vote(
+            &mut ctx.banks_client,
+            &ctx.payer,
+            &ctx.last_blockhash,
+            &program_id,
+            &poll_pda,
+            &party_pda,
+            vote_type,
+        ).await;
+    }
+
+    // expect error when the second negative vote needs 4 positive votes
+    let err = vote_expect_fail(
+        &mut ctx.banks_client,
+        &ctx.payer,
+        &ctx.last_blockhash,
+        &program_id,
+        &poll_pda,
+        &party_pdas[5],
+        VoteType::Negative,
+    ).await.unwrap();
+
+    match err {
+        TransactionError::InstructionError(_, InstructionError::Custom(code)) => {
+            assert_eq!(
+                code,
+                JanecekError::MustUseAllPositiveVoices as u32,
+                "Expected MustUseAllPositiveVoices error"
+            );
+        }
+        other => panic!("Unexpected transport error type: {:?}", other),
+    }
+
+    // update blockhash
+    ctx.last_blockhash = ctx.get_new_latest_blockhash().await.unwrap();
+
+    // +C, +D, -F
+    let mut voter_pda = None;
+    for (party_pda, vote_type) in [
+        (party_pdas[2], VoteType::Positive),
+        (party_pdas[3], VoteType::Positive),
+        (party_pdas[5], VoteType::Negative),
+    ] {
+        voter_pda = Some(vote(
+            &mut ctx.banks_client,
+            &ctx.payer,
+            &ctx.last_blockhash,
+            &program_id,
+            &poll_pda,
+            &party_pda,
+            vote_type,
+        ).await);
+    }
+
+    // expect error when the positive budget is spent
+    let err = vote_expect_fail(
+        &mut ctx.banks_client,
+        &ctx.payer,
+        &ctx.last_blockhash,
+        &program_id,
+        &poll_pda,
+        &party_pdas[6],
+        VoteType::Positive,
+    ).await.unwrap();
+
+    match err {
+        TransactionError::InstructionError(_, InstructionError::Custom(code)) => {
+            assert_eq!(
+                code,
+                JanecekError::NoPositiveVoice as u32,
+                "Expected NoPositiveVoice error"
+            );
+        }
+        other => panic!("Unexpected transport error type: {:?}", other),
+    }
+
+    let voter_account = ctx.banks_client.get_account(voter_pda.unwrap()).await.unwrap().unwrap();
+    let (voter_state, voted_parties) = VoterAccount::unpack(&voter_account.data);
+
+    assert_eq!(voter_account.data.len(), VoterAccount::get_account_size(&vote_rules));
+    assert_eq!(voter_state.positive_used, 4);
+    assert_eq!(voter_state.negative_used, 2);
+    assert_eq!(voted_parties.len(), 6);
+}
+
+
+#[tokio::test]
+async fn test_vote_with_spoofed_party() {
+    let (mut ctx, program_id) = setup_test_env().await;
+    let rent: Rent = ctx.banks_client.get_sysvar().await.unwrap();
+
+    // two polls, each with its own party
+    let poll_pda = create_poll(
+        &mut ctx.banks_client,
+        &ctx.payer,
+        &ctx.last_blockhash,
+        &program_id,
+        "Presidential Election",
+        "Vote for the next president",
+    ).await;
+
+    let other_poll_pda = create_poll(
+        &mut ctx.banks_client,
+        &ctx.payer,
+        &ctx.last_blockhash,
+        &program_id,
+        "Mayoral Election",
+        "Vote for the next mayor",
+    ).await;
+
+    let party_pda = create_party(
+        &mut ctx.banks_client,
+        &ctx.payer,
+        &ctx.last_blockhash,
+        &program_id,
+        "Party A",
+        &poll_pda,
+    ).await;
+
+    let other_party_pda = create_party(
+        &mut ctx.banks_client,
+        &ctx.payer,
+        &ctx.last_blockhash,
+        &program_id,
+        "Party A",
+        &other_poll_pda,
+    ).await;
+
+    // update timestamp to 24 hours and 1 minute
+    let mut clock: Clock = ctx.banks_client.get_sysvar().await.unwrap();
+    clock.unix_timestamp += (60 * 60 * 24) + 60;
+    ctx.set_sysvar(&clock);
+
+    start_voting(
+        &mut ctx.banks_client,
+        &ctx.payer,
+        &ctx.last_blockhash,
+        &program_id,
+        &poll_pda,
+    ).await;
+
+    // expect error when the party belongs to another poll
+    let err = vote_expect_fail(
+        &mut ctx.banks_client,
+        &ctx.payer,
+        &ctx.last_blockhash,
+        &program_id,
+        &poll_pda,
+        &other_party_pda,
+        VoteType::Positive,
+    ).await.unwrap();
+
+    match err {
+        TransactionError::InstructionError(_, InstructionError::Custom(code)) => {
+            assert_eq!(code, JanecekError::PartyListMismatch as u32, "Expected PartyListMismatch error");
+        }
+        other => panic!("Unexpected transport error type: {:?}", other),
+    }
+
+    // copy of a real party owned by another program
+    let party_data = ctx.banks_client.get_account(party_pda).await.unwrap().unwrap().data;
+    let forged_party_pda = Pubkey::new_unique();
+    ctx.set_account(&forged_party_pda, &Account {
+        lamports: rent.minimum_balance(party_data.len()),
+        data: party_data,
+        owner: Pubkey::new_unique(),
+        executable: false,
+        rent_epoch: 0,
+    }.into());
+
+    // expect error when the party is not owned by the program
+    let err = vote_expect_fail(
+        &mut ctx.banks_client,
+        &ctx.payer,
+        &ctx.last_blockhash,
+        &program_id,
+        &poll_pda,
+        &forged_party_pda,
+        VoteType::Positive,
+    ).await.unwrap();
+
+    match err {
+        TransactionError::InstructionError(_, InstructionError::IncorrectProgramId) => {}
+        other => panic!("Unexpected transport error type: {:?}", other),
+    }
+
+    // expect error when another account type is passed as the party
+    let err = vote_expect_fail(
+        &mut ctx.banks_client,
+        &ctx.payer,
+        &ctx.last_blockhash,
+        &program_id,
+        &poll_pda,
+        &other_poll_pda,
+        VoteType::Positive,
+    ).await.unwrap();
+
+    match err {
+        TransactionError::InstructionError(_, InstructionError::Custom(code)) => {
+            assert_eq!(code, JanecekError::AccountTypeMismatch as u32, "Expected AccountTypeMismatch error");
+        }
+        other => panic!("Unexpected transport error type: {:?}", other),
+    }
+
+    // real party still takes the vote
+    vote(
+        &mut ctx.banks_client,
+        &ctx.payer,
+        &ctx.last_blockhash,
+        &program_id,
+        &poll_pda,
+        &party_pda,
+        VoteType::Positive,
+    ).await;
+
+    let party_state = *PartyAccount::from_bytes(&ctx.banks_client.get_account(party_pda).await.unwrap().unwrap().data);
+    assert_eq!(party_state.positive_votes, 1);
+}
+
+
+#[tokio::test]
+async fn test_vote_with_spoofed_voter() {
+    let (mut ctx, program_id) = setup_test_env().await;
+    let rent: Rent = ctx.banks_client.get_sysvar().await.unwrap();
+    let outsider = Keypair::new();
+
+    // transfer SOL to outsider
+    let transfer_ix = system_instruction::transfer(
+        &ctx.payer.pubkey(),
+        &outsider.pubkey(),
+        1_000_000_000, // 1 SOL
+    );
+    let tx = Transaction::new_signed_with_payer(
+        &[transfer_ix],
+        Some(&ctx.payer.pubkey()),
+        &[&ctx.payer],
+        ctx.last_blockhash,
+    );
+    ctx.banks_client.process_transaction(tx).await.unwrap();
+
+    let poll_pda = create_poll(
+        &mut ctx.banks_client,
+        &ctx.payer,
+        &ctx.last_blockhash,
+        &program_id,
+        "Presidential Election",
+        "Vote for the next president",
+    ).await;
+
+    let party_pda_a = create_party(
+        &mut ctx.banks_client,
+        &ctx.payer,
+        &ctx.last_blockhash,
+        &program_id,
+        "Party A",
+        &poll_pda,
+    ).await;
+
+    let party_pda_b = create_party(
+        &mut ctx.banks_client,
+        &ctx.payer,
+        &ctx.last_blockhash,
+        &program_id,
+        "Party B",
+        &poll_pda,
+    ).await;
+
+    // update timestamp to 24 hours and 1 minute
+    let mut clock: Clock = ctx.banks_client.get_sysvar().await.unwrap();
+    clock.unix_timestamp += (60 * 60 * 24) + 60;
+    ctx.set_sysvar(&clock);
+
+    start_voting(
+        &mut ctx.banks_client,
+        &ctx.payer,
+        &ctx.last_blockhash,
+        &program_id,
+        &poll_pda,
+    ).await;
+
+    let payer_voter_pda = vote(
+        &mut ctx.banks_client,
+        &ctx.payer,
+        &ctx.last_blockhash,
+        &program_id,
+        &poll_pda,
+        &party_pda_a,
+        VoteType::Positive,
+    ).await;
+
+    // expect error when the outsider votes with someone else's voter account
+    let err = vote_with_voter_account_expect_fail(
+        &mut ctx.banks_client,
+        &outsider,
+        &ctx.last_blockhash,
+        &program_id,
+        &poll_pda,
+        &party_pda_b,
+        &payer_voter_pda,
+        VoteType::Positive,
+    ).await.unwrap();
+
+    match err {
+        TransactionError::InstructionError(_, InstructionError::InvalidArgument) => {}
+        other => panic!("Unexpected transport error type: {:?}", other),
+    }
+
+    // forged voter accounts at the outsider's real PDA
+    let (outsider_voter_pda, outsider_voter_bump) = Pubkey::find_program_address(
+        &[b"voter", poll_pda.as_ref(), outsider.pubkey().as_ref()],
+        &program_id,
+    );
+    let poll_state = PollState::deserialize(&mut &ctx.banks_client.get_account(poll_pda).await.unwrap().unwrap().data[..]).unwrap();
+    let voter_size = VoterAccount::get_account_size(&poll_state.vote_rules);
+
+    let forged_voter = |poll_key: Pubkey, owner: Pubkey| {
+        let mut data = bytemuck::bytes_of(&VoterAccount::new(poll_key, outsider.pubkey(), 1_000_000, 0, outsider_voter_bump)).to_vec();
+        data.resize(voter_size, 0);
+
+        Account {
+            lamports: rent.minimum_balance(voter_size),
+            data,
+            owner,
+            executable: false,
+            rent_epoch: 0,
+        }
+    };
+
+    // expect error when the voter account is owned by another program
+    ctx.set_account(&outsider_voter_pda, &forged_voter(poll_pda, Pubkey::new_unique()).into());
+
+    let err = vote_expect_fail(
+        &mut ctx.banks_client,
+        &outsider,
+        &ctx.last_blockhash,
+        &program_id,
+        &poll_pda,
+        &party_pda_b,
+        VoteType::Positive,
+    ).await.unwrap();
+
+    match err {
+        TransactionError::InstructionError(_, InstructionError::IncorrectProgramId) => {}
+        other => panic!("Unexpected transport error type: {:?}", other),
+    }
+
+    // expect error when the voter account records another poll
+    ctx.set_account(&outsider_voter_pda, &forged_voter(Pubkey::new_unique(), program_id).into());
+
+    // update blockhash
+    ctx.last_blockhash = ctx.get_new_latest_blockhash().await.unwrap();
+
+    let err = vote_expect_fail(
+        &mut ctx.banks_client,
+        &outsider,
+        &ctx.last_blockhash,
+        &program_id,
+        &poll_pda,
+        &party_pda_b,
+        VoteType::Positive,
+    ).await.unwrap();
+
+    match err {
+        TransactionError::InstructionError(_, InstructionError::InvalidAccountData) => {}
+        other => panic!("Unexpected transport error type: {:?}", other),
+    }
+
+    let party_state_b = *PartyAccount::from_bytes(&ctx.banks_client.get_account(party_pda_b).await.unwrap().unwrap().data);
+    assert_eq!(party_state_b.positive_votes, 0);
+}